@@ -0,0 +1,44 @@
+//! CRC32 throughput, alone and inside the split/recover paths
+//!
+//! CRC32 is the only checksum shares use, so there are no other algorithms to
+//! compare it with. Each size is measured for sealing and unsealing alone and
+//! for a full split and recover, which add the random pad and the XOR on top.
+//! Comparing the two shows how much of a split the checksum costs.
+//!
+//! Run with `cargo bench --bench crc32`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use xplit::{recover_secret, seal, split_secret, unseal};
+
+/// Secret sizes from a password up to a large key file
+const SIZES: [usize; 4] = [32, 4 * 1024, 256 * 1024, 4 * 1024 * 1024];
+
+fn crc32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crc32");
+    for size in SIZES {
+        let data = vec![0xA5u8; size];
+        let sealed = seal(&data);
+        let shares = split_secret(&data).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("seal", size), &data, |b, data| {
+            b.iter(|| seal(black_box(data)));
+        });
+        group.bench_with_input(BenchmarkId::new("unseal", size), &sealed, |b, sealed| {
+            b.iter(|| unseal(black_box(sealed)).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("split", size), &data, |b, data| {
+            b.iter(|| split_secret(black_box(data)).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("recover", size), &shares, |b, shares| {
+            b.iter(|| {
+                recover_secret(black_box(&shares.share1), black_box(&shares.share2)).unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, crc32);
+criterion_main!(benches);
@@ -6,12 +6,12 @@ let wasmModule = null;
 // Initialize WASM module
 async function initWasm() {
     try {
-        const { default: init, wasm_split, wasm_recover } = await import('./pkg/xplit.js');
+        const { default: init, wasm_split, wasm_recover_bytes } = await import('./pkg/xplit.js');
         await init();
 
         wasmModule = {
             split: wasm_split,
-            recover: wasm_recover
+            recoverBytes: wasm_recover_bytes
         };
 
         console.log('WASM module loaded successfully');
@@ -58,8 +58,7 @@ async function handleSplit(event) {
 
     try {
         // Call WASM split function
-        const resultJson = wasmModule.split(secretInput);
-        const result = JSON.parse(resultJson);
+        const result = wasmModule.split(secretInput);
 
         // Display shares
         document.getElementById('share1-display').textContent = result.share1;
@@ -113,8 +112,12 @@ async function handleRecover(event) {
     submitBtn.disabled = true;
 
     try {
-        // Call WASM recover function
-        const recoveredSecret = wasmModule.recover(share1Input, share2Input);
+        // Recover as bytes so binary secrets come back too
+        const recovered = wasmModule.recoverBytes(
+            decodeShare(share1Input, 1),
+            decodeShare(share2Input, 2)
+        );
+        const recoveredSecret = describeSecret(recovered);
 
         // Display recovered secret
         const secretElement = document.getElementById('recovered-secret');
@@ -131,6 +134,25 @@ async function handleRecover(event) {
     }
 }
 
+// Decode a base64 share into bytes
+function decodeShare(share, shareNumber) {
+    try {
+        return Uint8Array.from(atob(share), c => c.charCodeAt(0));
+    } catch {
+        throw new Error(`Failed to decode share${shareNumber} from base64`);
+    }
+}
+
+// Show a recovered secret as text, or as hex when it isn't UTF-8 (like the CLI)
+function describeSecret(bytes) {
+    try {
+        return new TextDecoder('utf-8', { fatal: true }).decode(bytes);
+    } catch {
+        const hex = Array.from(bytes, b => b.toString(16).padStart(2, '0')).join('');
+        return `Binary data (hex): ${hex}`;
+    }
+}
+
 // Copy share to clipboard
 window.copyShare = async function(shareNumber, buttonElement) {
     const shareText = window.currentShares[`share${shareNumber}`];
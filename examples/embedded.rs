@@ -0,0 +1,70 @@
+//! Embedded-style usage: caller-supplied RNG and fixed-size buffers
+//!
+//! On a microcontroller there is no OS entropy source, so the pad comes from
+//! whatever RNG the board provides (modelled here by a tiny xorshift generator
+//! seeded with a constant). Shares are copied into fixed-size arrays, as they
+//! would be before being written to flash or an EEPROM slot.
+//!
+//! The xorshift generator is NOT cryptographically secure; it only stands in
+//! for a hardware TRNG so the example is deterministic.
+//!
+//! Run with `cargo run --example embedded --no-default-features`.
+
+use rand::{Error, RngCore};
+use xplit::{recover_secret, split_secret_with_rng};
+
+const SECRET_LEN: usize = 16;
+const SHARE_LEN: usize = SECRET_LEN + 4; // payload + CRC32 trailer
+
+/// Stand-in for a hardware TRNG
+struct XorShift32(u32);
+
+impl RngCore for XorShift32 {
+    fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn main() {
+    let secret: [u8; SECRET_LEN] = *b"device-key-0001!";
+    let mut rng = XorShift32(0x1234_5678);
+
+    let shares = split_secret_with_rng(&secret, &mut rng).expect("secret is non-empty");
+
+    // Store each share in its own fixed-size slot
+    let mut slot1 = [0u8; SHARE_LEN];
+    let mut slot2 = [0u8; SHARE_LEN];
+    slot1.copy_from_slice(&shares.share1);
+    slot2.copy_from_slice(&shares.share2);
+
+    let recovered = recover_secret(slot1, slot2).expect("shares are intact");
+    assert_eq!(recovered, secret);
+
+    // Same seed, same shares: the whole run is reproducible
+    let again = split_secret_with_rng(&secret, &mut XorShift32(0x1234_5678)).expect("non-empty");
+    assert_eq!(again.share1, slot1);
+    assert_eq!(again.share2, slot2);
+
+    println!("slot1: {}", hex::encode(slot1));
+    println!("slot2: {}", hex::encode(slot2));
+    println!("recovered {} bytes", recovered.len());
+}
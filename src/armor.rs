@@ -0,0 +1,130 @@
+//! PEM-style armor for pasting shares into email and chat
+//!
+//! A bare base64 share gets mangled in transit: clients re-wrap long lines,
+//! quote replies with `> ` and surround it with prose. An armored share is
+//! marked off by header lines so it can be found again in all of that:
+//!
+//! ```text
+//! -----BEGIN XPLIT SHARE-----
+//! ZiTjk3OD6puSVM/JV3CYopI=
+//! -----END XPLIT SHARE-----
+//! ```
+//!
+//! The body is the share in base64, 64 characters to a line. The armor adds no
+//! checksum of its own; the share's checks still apply once it is read back.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+use crate::ShareError;
+
+/// Line opening an armored share
+pub const ARMOR_BEGIN: &str = "-----BEGIN XPLIT SHARE-----";
+
+/// Line closing an armored share
+pub const ARMOR_END: &str = "-----END XPLIT SHARE-----";
+
+const LINE_LEN: usize = 64;
+
+/// Armor a share as a text block
+#[must_use]
+pub fn armor_share(share: &[u8]) -> String {
+    let body = STANDARD.encode(share);
+    let mut text = String::with_capacity(body.len() + body.len() / LINE_LEN + 64);
+    text.push_str(ARMOR_BEGIN);
+    text.push('\n');
+    // base64 is ASCII, so every split point is a char boundary
+    for line in body.as_bytes().chunks(LINE_LEN) {
+        text.push_str(std::str::from_utf8(line).unwrap_or_default());
+        text.push('\n');
+    }
+    text.push_str(ARMOR_END);
+    text.push('\n');
+    text
+}
+
+/// Find the first armored share in `text` and decode it
+///
+/// Text around the block is ignored, as are `>` quote markers at the start of
+/// lines and any line breaks or spaces inside the block, so a block that was
+/// quoted or re-wrapped still reads. The share itself is not checked here.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::NoArmor`] if `text` has no begin line
+/// - [`ShareError::InvalidArmor`] if the block has no end line or its body
+///   is empty or isn't base64
+pub fn dearmor_share(text: &str) -> Result<Vec<u8>, ShareError> {
+    let unquoted: String = text
+        .lines()
+        .map(|line| line.trim_start_matches(|c: char| c == '>' || c.is_whitespace()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let start = unquoted.find(ARMOR_BEGIN).ok_or(ShareError::NoArmor)? + ARMOR_BEGIN.len();
+    let len = unquoted[start..]
+        .find(ARMOR_END)
+        .ok_or(ShareError::InvalidArmor)?;
+    let body: String = unquoted[start..start + len]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    match STANDARD.decode(body) {
+        Ok(share) if !share.is_empty() => Ok(share),
+        _ => Err(ShareError::InvalidArmor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_armor_roundtrip() {
+        let share = vec![0xA5; 100];
+        let armored = armor_share(&share);
+        assert!(armored.starts_with("-----BEGIN XPLIT SHARE-----\n"));
+        let lines: Vec<&str> = armored.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1].len(), LINE_LEN);
+        assert_eq!(lines[4], ARMOR_END);
+        assert_eq!(dearmor_share(&armored).unwrap(), share);
+    }
+
+    #[test]
+    fn test_dearmor_survives_mail_clients() {
+        let share = vec![0x5A; 100];
+        let armored = armor_share(&share);
+
+        let quoted = format!("> > {}", armored.trim_end().replace('\n', "\n> > "));
+        let reply = format!("On Monday you wrote:\n{quoted}\nThanks, got it.\n");
+        assert_eq!(dearmor_share(&reply).unwrap(), share);
+
+        let body = STANDARD.encode(&share);
+        let rewrapped = format!(
+            "Here it is: {ARMOR_BEGIN} {}\n{} {ARMOR_END} -- sent from my phone",
+            &body[..50],
+            &body[50..]
+        );
+        assert_eq!(dearmor_share(&rewrapped).unwrap(), share);
+    }
+
+    #[test]
+    fn test_dearmor_errors() {
+        assert_eq!(
+            dearmor_share("just some prose").unwrap_err(),
+            ShareError::NoArmor
+        );
+        assert_eq!(
+            dearmor_share(&format!("{ARMOR_BEGIN}\nZiTjk3OD6puSVM/JV3CYopI=\n")).unwrap_err(),
+            ShareError::InvalidArmor
+        );
+        assert_eq!(
+            dearmor_share(&format!("{ARMOR_BEGIN}\nnot base64!\n{ARMOR_END}")).unwrap_err(),
+            ShareError::InvalidArmor
+        );
+        assert_eq!(
+            dearmor_share(&format!("{ARMOR_BEGIN}\n{ARMOR_END}")).unwrap_err(),
+            ShareError::InvalidArmor
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! `xplit-server`: split and recover over HTTP, for a shared internal service
+//!
+//! ```text
+//! xplit-server [ADDR]    (default 127.0.0.1:8080)
+//!
+//! POST /split    {"secret": "text"} or {"secret_base64": "..."}
+//!             -> {"share1": "...", "share2": "..."}
+//! POST /recover  {"share1": "...", "share2": "..."}
+//!             -> {"secret": "text"} or {"secret_base64": "..."} for binary secrets
+//! ```
+//!
+//! Shares are base64, as printed by `xplit split`. Errors are
+//! `{"error": "..."}` with a 4xx status.
+//!
+//! Plaintext secrets transit the server in both directions and it speaks plain
+//! HTTP: bind it to localhost, or put it behind a TLS-terminating proxy on a
+//! network you trust. Nothing is stored, and the secrets the handlers hold,
+//! received or recovered, are zeroized once handled. Shares aren't, and neither
+//! are the copies in the HTTP layer's request and response buffers.
+
+#![forbid(unsafe_code)]
+
+use axum::extract::DefaultBodyLimit;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use xplit::{RecoveredSecret, ShareError, recover_typed, split_secret};
+use zeroize::Zeroizing;
+
+/// Largest request body accepted (1 MiB); larger ones get 413
+const MAX_BODY: usize = 1024 * 1024;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SplitRequest {
+    secret: Option<Zeroizing<String>>,
+    secret_base64: Option<Zeroizing<String>>,
+}
+
+#[derive(Serialize)]
+struct SplitResponse {
+    share1: String,
+    share2: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecoverRequest {
+    share1: String,
+    share2: String,
+}
+
+#[derive(Serialize)]
+struct RecoverResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<Zeroizing<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_base64: Option<Zeroizing<String>>,
+}
+
+/// A failed request, answered as `{"error": "..."}`
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+impl From<ShareError> for ApiError {
+    fn from(error: ShareError) -> Self {
+        let status = match error {
+            ShareError::EmptyInput => StatusCode::BAD_REQUEST,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+        Self(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+        (self.0, Json(Body { error: self.1 })).into_response()
+    }
+}
+
+async fn split(Json(request): Json<SplitRequest>) -> Result<Json<SplitResponse>, ApiError> {
+    let secret = Zeroizing::new(match (request.secret, request.secret_base64) {
+        (Some(text), None) => text.as_bytes().to_vec(),
+        (None, Some(encoded)) => BASE64
+            .decode(encoded.as_bytes())
+            .map_err(|e| ApiError::bad_request(format!("secret_base64 is not base64: {e}")))?,
+        _ => {
+            return Err(ApiError::bad_request(
+                "give exactly one of secret and secret_base64",
+            ));
+        }
+    });
+    let shares = split_secret(&secret)?;
+    Ok(Json(SplitResponse {
+        share1: BASE64.encode(shares.share1),
+        share2: BASE64.encode(shares.share2),
+    }))
+}
+
+async fn recover(Json(request): Json<RecoverRequest>) -> Result<Json<RecoverResponse>, ApiError> {
+    let decode = |name: &str, share: &str| {
+        BASE64
+            .decode(share.trim())
+            .map_err(|e| ApiError::bad_request(format!("{name} is not base64: {e}")))
+    };
+    let share1 = decode("share1", &request.share1)?;
+    let share2 = decode("share2", &request.share2)?;
+    Ok(Json(match recover_typed(&share1, &share2)? {
+        RecoveredSecret::Text(text) => RecoverResponse {
+            secret: Some(Zeroizing::new(text)),
+            secret_base64: None,
+        },
+        RecoveredSecret::Binary(bytes) => RecoverResponse {
+            secret: None,
+            secret_base64: Some(Zeroizing::new(BASE64.encode(Zeroizing::new(bytes)))),
+        },
+    }))
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/split", post(split))
+        .route("/recover", post(recover))
+        .layer(DefaultBodyLimit::max(MAX_BODY))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let addr = std::env::args().nth(1);
+    let addr: SocketAddr = match addr.as_deref().unwrap_or(DEFAULT_ADDR).parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("Error: invalid address: {e}\nUsage: xplit-server [ADDR]");
+            return ExitCode::FAILURE;
+        }
+    };
+    if !addr.ip().is_loopback() {
+        eprintln!(
+            "Warning: listening on {addr}; secrets travel in plain HTTP, so only expose this \
+             behind TLS on a trusted network"
+        );
+    }
+
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: failed to listen on {addr}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("Listening on http://{addr}");
+    if let Err(e) = axum::serve(listener, app()).await {
+        eprintln!("Error: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_split_then_recover() {
+        let Json(shares) = block_on(split(Json(SplitRequest {
+            secret: Some(Zeroizing::new("Hello, World!".to_owned())),
+            secret_base64: None,
+        })))
+        .unwrap();
+        let Json(recovered) = block_on(recover(Json(RecoverRequest {
+            share1: shares.share1,
+            share2: shares.share2,
+        })))
+        .unwrap();
+        assert_eq!(
+            recovered.secret.as_deref().map(String::as_str),
+            Some("Hello, World!")
+        );
+        assert!(recovered.secret_base64.is_none());
+    }
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        fn status<T>(result: Result<T, ApiError>) -> StatusCode {
+            result.map(|_| ()).unwrap_err().0
+        }
+
+        let empty = split(Json(SplitRequest {
+            secret: Some(Zeroizing::new(String::new())),
+            secret_base64: None,
+        }));
+        assert_eq!(status(block_on(empty)), StatusCode::BAD_REQUEST);
+
+        let corrupted = recover(Json(RecoverRequest {
+            share1: "ZiTjk3OD6puSVM/JV3CYopI=".to_owned(),
+            share2: "LkGP/xyvysz9JqOtdpOmJ8E=".to_owned(),
+        }));
+        assert_eq!(
+            status(block_on(corrupted)),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let not_base64 = recover(Json(RecoverRequest {
+            share1: "!".to_owned(),
+            share2: "!".to_owned(),
+        }));
+        assert_eq!(status(block_on(not_base64)), StatusCode::BAD_REQUEST);
+    }
+}
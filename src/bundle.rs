@@ -0,0 +1,153 @@
+//! Passphrase-protected bundles of both shares
+//!
+//! A bundle carries a pair of shares as one file, e.g. to an offline machine,
+//! encrypted together under a single passphrase with the same Argon2id +
+//! XChaCha20-Poly1305 envelope as [`crate::protect`]. Whoever has the
+//! passphrase has both shares, and so the secret: a bundle is for moving a
+//! pair, not for keeping shares apart.
+//!
+//! Layout (integers big-endian, see [`crate::format`]):
+//!
+//! ```text
+//! magic "XPB1" | m_cost u32 | t_cost u32 | p_cost u32 | salt [16] | check [8] | nonce [24] | ciphertext
+//! ```
+//!
+//! The plaintext is `share1 length u32 | share1 | share2`, each share exactly
+//! as given, so labels, signatures and protected envelopes survive the trip.
+
+use crate::format::read_u32_be;
+use crate::protect::{KdfParams, open_envelope, seal_envelope};
+use crate::{ShareError, TwoShares};
+
+/// Magic prefix identifying a bundle
+pub const BUNDLE_MAGIC: &[u8; 4] = b"XPB1";
+
+/// Returns `true` if `data` starts with the bundle magic
+#[must_use]
+pub fn is_bundle(data: &[u8]) -> bool {
+    data.starts_with(BUNDLE_MAGIC)
+}
+
+/// Encrypt both shares into one bundle under `passphrase`
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if either share is empty
+/// - [`ShareError::InvalidKdfParams`] if argon2 rejects the parameters
+/// - [`ShareError::LengthMismatch`] if share 1 is 4 GiB or more, past what
+///   the length field holds
+pub fn bundle_shares(
+    share1: &[u8],
+    share2: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>, ShareError> {
+    if share1.is_empty() || share2.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+    let len1 = u32::try_from(share1.len()).map_err(|_| ShareError::LengthMismatch)?;
+
+    let mut plaintext = Vec::with_capacity(4 + share1.len() + share2.len());
+    plaintext.extend_from_slice(&len1.to_be_bytes());
+    plaintext.extend_from_slice(share1);
+    plaintext.extend_from_slice(share2);
+    let bundle = seal_envelope(BUNDLE_MAGIC, &plaintext, passphrase, params);
+    wipe(&mut plaintext);
+    bundle
+}
+
+/// Decrypt a bundle made by [`bundle_shares`], returning both shares as given
+///
+/// The shares themselves are not checked here.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::WrongPassphrase`] if the passphrase does not match
+/// - [`ShareError::CorruptedBundle`] if the envelope is malformed, fails
+///   authentication, or doesn't hold two shares
+pub fn unbundle_shares(bundle: &[u8], passphrase: &str) -> Result<TwoShares, ShareError> {
+    let mut plaintext =
+        open_envelope(BUNDLE_MAGIC, bundle, passphrase).map_err(|error| match error {
+            ShareError::WrongPassphrase => error,
+            _ => ShareError::CorruptedBundle,
+        })?;
+
+    let len1 = read_u32_be(&plaintext, 0).map(usize::try_from);
+    let Ok(Ok(len1)) = len1 else {
+        wipe(&mut plaintext);
+        return Err(ShareError::CorruptedBundle);
+    };
+    if len1 == 0 || plaintext.len() <= 4 + len1 {
+        wipe(&mut plaintext);
+        return Err(ShareError::CorruptedBundle);
+    }
+    let share2 = plaintext.split_off(4 + len1);
+    let share1 = plaintext.split_off(4);
+    Ok(TwoShares { share1, share2 })
+}
+
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+    #[cfg(not(feature = "zeroize"))]
+    bytes.fill(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recover_secret, split_secret};
+
+    const CHEAP: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let shares = split_secret(b"Hello, World!").unwrap();
+        let bundle = bundle_shares(&shares.share1, &shares.share2, "transport", CHEAP).unwrap();
+        assert!(is_bundle(&bundle));
+
+        let opened = unbundle_shares(&bundle, "transport").unwrap();
+        assert_eq!(opened.share1, shares.share1);
+        assert_eq!(opened.share2, shares.share2);
+        assert_eq!(
+            recover_secret(&opened.share1, &opened.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_told_apart_from_corruption() {
+        let bundle = bundle_shares(b"share one", b"share two", "transport", CHEAP).unwrap();
+        assert_eq!(
+            unbundle_shares(&bundle, "guess").unwrap_err(),
+            ShareError::WrongPassphrase
+        );
+
+        let mut damaged = bundle.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            unbundle_shares(&damaged, "transport").unwrap_err(),
+            ShareError::CorruptedBundle
+        );
+        assert_eq!(
+            unbundle_shares(&bundle[..20], "transport").unwrap_err(),
+            ShareError::CorruptedBundle
+        );
+        // A protected share is the same envelope under another magic
+        let protected = crate::protect::protect_share(b"share", "transport").unwrap();
+        assert_eq!(
+            unbundle_shares(&protected, "transport").unwrap_err(),
+            ShareError::CorruptedBundle
+        );
+        assert_eq!(
+            bundle_shares(b"", b"share two", "transport", CHEAP).unwrap_err(),
+            ShareError::EmptyInput
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Chunk-checksummed shares
+//!
+//! A plain share has one CRC32 over the whole payload, so a single flipped byte
+//! only says "this share is corrupted". A chunked share checksums every
+//! `chunk_size` bytes separately, so damage can be pinned to a chunk and only
+//! that part re-read or re-scanned.
+//!
+//! Layout (integers big-endian, see [`crate::format`]):
+//!
+//! ```text
+//! magic "XPC1" | chunk_size u32 | (chunk | crc32) ...
+//! ```
+//!
+//! Every chunk is `chunk_size` bytes except possibly the last, which holds the
+//! remainder. A secret no longer than the chunk size is a single chunk.
+
+use crc32fast::hash;
+use std::ops::Range;
+
+use crate::format::read_u32_be;
+use crate::{ShareError, TwoShares, xor};
+
+/// Magic prefix identifying a chunked share
+pub const CHUNKED_MAGIC: &[u8; 4] = b"XPC1";
+
+/// Smallest accepted chunk size
+pub const MIN_CHUNK_SIZE: usize = 64;
+/// Largest accepted chunk size (1 GiB)
+pub const MAX_CHUNK_SIZE: usize = 1 << 30;
+
+const CRC_LEN: usize = 4;
+const HEADER_LEN: usize = CHUNKED_MAGIC.len() + 4;
+
+/// Returns `true` if `data` starts with the chunked-share magic
+#[must_use]
+pub fn is_chunked(data: &[u8]) -> bool {
+    data.starts_with(CHUNKED_MAGIC)
+}
+
+/// Length of each share [`split_chunked`] produces for a secret of `secret_len` bytes
+#[must_use]
+pub const fn chunked_len(secret_len: usize, chunk_size: usize) -> usize {
+    HEADER_LEN + secret_len + secret_len.div_ceil(chunk_size) * CRC_LEN
+}
+
+/// Split a secret into 2 shares with a CRC32 per `chunk_size` bytes
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the secret is empty
+/// - [`ShareError::InvalidChunkSize`] if `chunk_size` is outside
+///   [`MIN_CHUNK_SIZE`]`..=`[`MAX_CHUNK_SIZE`]
+pub fn split_chunked(secret: &[u8], chunk_size: usize) -> Result<TwoShares, ShareError> {
+    if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&chunk_size) {
+        return Err(ShareError::InvalidChunkSize);
+    }
+    let shares = crate::split_secret(secret)?;
+    // Drop the whole-payload CRC32; each chunk gets its own instead
+    let payload = |share: &[u8]| share[..share.len() - CRC_LEN].to_vec();
+    let chunk_size = chunk_size.min(secret.len());
+
+    Ok(TwoShares {
+        share1: encode(&payload(&shares.share1), chunk_size),
+        share2: encode(&payload(&shares.share2), chunk_size),
+    })
+}
+
+/// Layout and integrity of a chunked share
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkReport {
+    /// Chunk size recorded in the header
+    pub chunk_size: usize,
+    /// Number of chunks
+    pub chunks: usize,
+    /// Payload length, which is also the secret length
+    pub payload_len: usize,
+    /// Payload byte ranges of the chunks whose checksum fails, in order
+    pub bad: Vec<Range<usize>>,
+}
+
+/// Check every chunk of `share`, reporting which ones are damaged
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if `share` is empty
+/// - [`ShareError::ShareTooShort`] if the header or a chunk trailer is cut short
+/// - [`ShareError::InvalidChunkSize`] if the header records an invalid chunk size
+pub fn check_chunks(share: &[u8]) -> Result<ChunkReport, ShareError> {
+    let mut report = ChunkReport {
+        chunk_size: chunk_size(share)?,
+        chunks: 0,
+        payload_len: 0,
+        bad: Vec::new(),
+    };
+    for chunk in chunks(share)? {
+        report.chunks += 1;
+        report.payload_len = chunk.range.end;
+        if !chunk.is_intact() {
+            report.bad.push(chunk.range);
+        }
+    }
+    Ok(report)
+}
+
+/// Recover the secret from two chunked shares, verifying every chunk
+///
+/// # Errors
+///
+/// Returns:
+/// - the errors of [`check_chunks`] for either share
+/// - [`ShareError::InvalidChunk`] for the first chunk of either share that
+///   fails its checksum
+/// - [`ShareError::LengthMismatch`] if the shares differ in length or chunk size
+pub fn recover_chunked(share1: &[u8], share2: &[u8]) -> Result<Vec<u8>, ShareError> {
+    let payload1 = checked_payload(share1)?;
+    let payload2 = checked_payload(share2)?;
+    if chunk_size(share1)? != chunk_size(share2)? {
+        return Err(ShareError::LengthMismatch);
+    }
+    xor(&payload1, &payload2)
+}
+
+/// One chunk of a parsed share
+struct Chunk<'a> {
+    range: Range<usize>,
+    data: &'a [u8],
+    crc: [u8; CRC_LEN],
+}
+
+impl Chunk<'_> {
+    fn is_intact(&self) -> bool {
+        hash(self.data).to_be_bytes() == self.crc
+    }
+}
+
+fn encode(payload: &[u8], chunk_size: usize) -> Vec<u8> {
+    let mut share = Vec::with_capacity(chunked_len(payload.len(), chunk_size));
+    share.extend_from_slice(CHUNKED_MAGIC);
+    let size = u32::try_from(chunk_size).expect("chunk size is at most MAX_CHUNK_SIZE");
+    share.extend_from_slice(&size.to_be_bytes());
+    for chunk in payload.chunks(chunk_size) {
+        share.extend_from_slice(chunk);
+        share.extend_from_slice(&hash(chunk).to_be_bytes());
+    }
+    share
+}
+
+fn chunk_size(share: &[u8]) -> Result<usize, ShareError> {
+    if share.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+    if share.len() < HEADER_LEN || !is_chunked(share) {
+        return Err(ShareError::ShareTooShort);
+    }
+    let size = read_u32_be(share, CHUNKED_MAGIC.len())? as usize;
+    // A single-chunk share records the secret length, which may be below the minimum
+    if size == 0 || size > MAX_CHUNK_SIZE {
+        return Err(ShareError::InvalidChunkSize);
+    }
+    Ok(size)
+}
+
+/// Parse `share` into its chunks, checking the layout but not the checksums
+fn chunks(share: &[u8]) -> Result<impl Iterator<Item = Chunk<'_>>, ShareError> {
+    let size = chunk_size(share)?;
+    let body = &share[HEADER_LEN..];
+    let stride = size + CRC_LEN;
+    // Only the last chunk may be short, and it still needs data and a trailer
+    let tail = body.len() % stride;
+    if body.is_empty() || (tail != 0 && tail <= CRC_LEN) {
+        return Err(ShareError::ShareTooShort);
+    }
+
+    Ok(body.chunks(stride).enumerate().map(move |(index, raw)| {
+        let (data, crc) = raw.split_at(raw.len() - CRC_LEN);
+        let start = index * size;
+        Chunk {
+            range: start..start + data.len(),
+            data,
+            crc: [crc[0], crc[1], crc[2], crc[3]],
+        }
+    }))
+}
+
+/// Verify every chunk and concatenate the payload
+fn checked_payload(share: &[u8]) -> Result<Vec<u8>, ShareError> {
+    let mut payload = Vec::with_capacity(share.len());
+    for (index, chunk) in chunks(share)?.enumerate() {
+        if !chunk.is_intact() {
+            return Err(ShareError::InvalidChunk {
+                index,
+                start: chunk.range.start,
+                end: chunk.range.end,
+            });
+        }
+        payload.extend_from_slice(chunk.data);
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        let secret: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let shares = split_chunked(&secret, 256).unwrap();
+
+        assert!(is_chunked(&shares.share1));
+        assert_eq!(shares.share1.len(), chunked_len(1000, 256));
+        let report = check_chunks(&shares.share2).unwrap();
+        assert_eq!((report.chunks, report.payload_len), (4, 1000));
+        assert!(report.bad.is_empty());
+        assert_eq!(
+            recover_chunked(&shares.share1, &shares.share2).unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn test_small_secret_is_one_chunk() {
+        let shares = split_chunked(b"Hello, World!", 64 * 1024).unwrap();
+        assert_eq!(shares.share1.len(), chunked_len(13, 13));
+        assert_eq!(
+            recover_chunked(&shares.share1, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_corruption_is_pinned_to_its_chunk() {
+        let secret = vec![7u8; 300];
+        let mut shares = split_chunked(&secret, 100).unwrap();
+        // Third chunk: header, then two chunks of 100 bytes + CRC
+        shares.share2[HEADER_LEN + 2 * 104 + 5] ^= 1;
+
+        let bad = check_chunks(&shares.share2).unwrap().bad;
+        assert_eq!(bad.len(), 1);
+        assert_eq!(bad[0], 200..300);
+        assert_eq!(
+            recover_chunked(&shares.share1, &shares.share2),
+            Err(ShareError::InvalidChunk {
+                index: 2,
+                start: 200,
+                end: 300
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_chunk_sizes_are_rejected() {
+        assert_eq!(
+            split_chunked(b"secret", MIN_CHUNK_SIZE - 1).err(),
+            Some(ShareError::InvalidChunkSize)
+        );
+        assert_eq!(
+            split_chunked(b"secret", MAX_CHUNK_SIZE + 1).err(),
+            Some(ShareError::InvalidChunkSize)
+        );
+        assert_eq!(
+            check_chunks(b"XPC1\0\0\0\0data"),
+            Err(ShareError::InvalidChunkSize)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! System clipboard for `recover --to-clip` (`clipboard` feature)
+//!
+//! The secret is only left on the clipboard for a limited time. xplit stays
+//! running until then, since on X11 the clipboard is served by the process
+//! that set it, and clears it only if it still holds the secret, so anything
+//! copied in the meantime is left alone.
+
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{CliResult, cancel_flag};
+
+/// How often the wait checks for Ctrl-C
+const POLL: Duration = Duration::from_millis(100);
+
+/// Put `text` on the clipboard, then clear it after `clear_after` or on Ctrl-C
+pub fn copy_then_clear(text: &str, clear_after: Duration) -> CliResult<()> {
+    let mut clipboard = backend::Clipboard::open()?;
+    let cancel = cancel_flag();
+    clipboard.set(text)?;
+    eprintln!(
+        "Secret copied to the clipboard; it will be cleared in {}s (Ctrl-C clears it now).",
+        clear_after.as_secs()
+    );
+
+    let deadline = Instant::now() + clear_after;
+    while Instant::now() < deadline && !cancel.load(Ordering::Relaxed) {
+        thread::sleep(POLL.min(deadline.saturating_duration_since(Instant::now())));
+    }
+
+    if clipboard.holds(text)? {
+        clipboard.clear()?;
+        eprintln!("Clipboard cleared.");
+    } else {
+        eprintln!("Clipboard changed since the copy; left as it is.");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+mod backend {
+    use zeroize::Zeroizing;
+
+    use super::CliResult;
+
+    pub struct Clipboard(arboard::Clipboard);
+
+    impl Clipboard {
+        pub fn open() -> CliResult<Self> {
+            arboard::Clipboard::new()
+                .map(Self)
+                .map_err(|e| format!("clipboard unavailable: {e}").into())
+        }
+
+        pub fn set(&mut self, text: &str) -> CliResult<()> {
+            self.0
+                .set_text(text)
+                .map_err(|e| format!("Failed to copy to the clipboard: {e}").into())
+        }
+
+        pub fn holds(&mut self, text: &str) -> CliResult<bool> {
+            match self.0.get_text() {
+                Ok(current) => Ok(*Zeroizing::new(current) == text),
+                // Emptied or replaced by something that isn't text
+                Err(arboard::Error::ContentNotAvailable) => Ok(false),
+                Err(e) => Err(format!("Failed to read the clipboard: {e}").into()),
+            }
+        }
+
+        pub fn clear(&mut self) -> CliResult<()> {
+            self.0
+                .clear()
+                .map_err(|e| format!("Failed to clear the clipboard: {e}").into())
+        }
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+mod backend {
+    use super::CliResult;
+
+    const UNAVAILABLE: &str =
+        "xplit was built without clipboard support; rebuild with `--features clipboard`";
+
+    pub enum Clipboard {}
+
+    impl Clipboard {
+        pub fn open() -> CliResult<Self> {
+            Err(UNAVAILABLE.into())
+        }
+
+        pub fn set(&mut self, _: &str) -> CliResult<()> {
+            match *self {}
+        }
+
+        pub fn holds(&mut self, _: &str) -> CliResult<bool> {
+            match *self {}
+        }
+
+        pub fn clear(&mut self) -> CliResult<()> {
+            match *self {}
+        }
+    }
+}
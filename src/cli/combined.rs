@@ -0,0 +1,188 @@
+//! `recover --combined`: find both shares inside one text file
+//!
+//! Exports and notes sometimes end up holding both shares amid other text.
+//! Every standalone base64 run is decoded and kept if it is a share, then the
+//! candidates are paired up by kind and length. A single plausible pair is
+//! used as-is; several have to be chosen between with `--select`.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::fmt::Write as _;
+use xplit::chunked::is_chunked;
+use xplit::is_valid_share;
+use xplit::pad::is_padded;
+use xplit::protect::is_protected;
+use xplit::sign::is_signed;
+
+use super::CliResult;
+
+/// Shortest base64 run worth decoding: a 1-byte secret plus its CRC32
+const MIN_TOKEN_LEN: usize = 8;
+
+/// A share found in the text
+#[derive(Debug)]
+pub struct Candidate {
+    /// 1-based line number
+    pub line: usize,
+    /// Byte offset of the first character
+    pub offset: usize,
+    /// The share as found, base64-encoded
+    pub token: String,
+    kind: &'static str,
+    len: usize,
+}
+
+impl Candidate {
+    fn location(&self) -> String {
+        format!("line {} (byte {})", self.line, self.offset)
+    }
+}
+
+/// Every base64 run in `text` that decodes to a share, in order
+pub fn find_candidates(text: &str) -> Vec<Candidate> {
+    let is_base64 = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=');
+    let mut candidates = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(is_base64) {
+        let token_len = rest[start..]
+            .find(|c| !is_base64(c))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + token_len];
+        let offset = text.len() - rest.len() + start;
+        rest = &rest[start + token_len..];
+
+        if token.len() < MIN_TOKEN_LEN {
+            continue;
+        }
+        let Ok(bytes) = BASE64.decode(token) else {
+            continue;
+        };
+        if let Some(kind) = share_kind(&bytes) {
+            candidates.push(Candidate {
+                line: text[..offset].matches('\n').count() + 1,
+                offset,
+                token: token.to_owned(),
+                kind,
+                len: bytes.len(),
+            });
+        }
+    }
+    candidates
+}
+
+/// What kind of share `bytes` is, if it is one at all
+pub fn share_kind(bytes: &[u8]) -> Option<&'static str> {
+    if is_signed(bytes) {
+        Some("signed")
+    } else if is_protected(bytes) {
+        Some("protected")
+    } else if is_chunked(bytes) {
+        Some("chunked")
+    } else if is_padded(bytes) {
+        Some("padded")
+    } else if is_valid_share(bytes) {
+        Some("plain")
+    } else {
+        None
+    }
+}
+
+/// Index pairs of candidates that could have been split together: same kind,
+/// same length, and not the same share twice
+pub fn plausible_pairs(candidates: &[Candidate]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, first) in candidates.iter().enumerate() {
+        for (j, second) in candidates.iter().enumerate().skip(i + 1) {
+            if first.kind == second.kind && first.len == second.len && first.token != second.token {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Pick the pair of shares to recover from in `text`, reporting where they
+/// were found on stderr
+///
+/// `select` is the 1-based number of a pair from the listing shown when more
+/// than one is plausible.
+pub fn pick_pair(text: &str, select: Option<usize>) -> CliResult<[String; 2]> {
+    let candidates = find_candidates(text);
+    let pairs = plausible_pairs(&candidates);
+    let (i, j) = match (pairs.as_slice(), select) {
+        ([], _) => {
+            return Err(format!(
+                "Found {} share(s) but no pair that could have been split together",
+                candidates.len()
+            )
+            .into());
+        }
+        ([pair], None | Some(1)) => *pair,
+        (_, Some(number)) => *number
+            .checked_sub(1)
+            .and_then(|index| pairs.get(index))
+            .ok_or_else(|| format!("--select must be between 1 and {}", pairs.len()))?,
+        (_, None) => {
+            let mut message = format!(
+                "Found {} plausible pairs of shares; choose one with --select N:",
+                pairs.len()
+            );
+            for (number, &(i, j)) in (1..).zip(&pairs) {
+                let (first, second) = (&candidates[i], &candidates[j]);
+                write!(
+                    message,
+                    "\n  {number}) {} + {}, {} {} bytes each",
+                    first.location(),
+                    second.location(),
+                    first.kind,
+                    first.len
+                )?;
+            }
+            return Err(message.into());
+        }
+    };
+
+    let (first, second) = (&candidates[i], &candidates[j]);
+    eprintln!(
+        "Using share1 from {} and share2 from {}",
+        first.location(),
+        second.location()
+    );
+    Ok([first.token.clone(), second.token.clone()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARE1: &str = "ZiTjk3OD6puSVM/JV3CYopI=";
+    const SHARE2: &str = "LkGP/xyvysz9JqOtdpOmJ8A=";
+
+    #[test]
+    fn test_finds_shares_amid_text() {
+        let text = format!("Vault backup notes\nalice: {SHARE1}\n\nbob has {SHARE2}.\n");
+        let candidates = find_candidates(&text);
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!((candidates[0].line, candidates[1].line), (2, 4));
+        assert_eq!(&text[candidates[1].offset..][..SHARE2.len()], SHARE2);
+        assert_eq!(pick_pair(&text, None).unwrap(), [SHARE1, SHARE2]);
+    }
+
+    #[test]
+    fn test_several_pairs_need_select() {
+        let other = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+        let text = format!("{SHARE1}\n{SHARE2}\n{SHARE1}\n{other}\n");
+        // The repeated share1 pairs with share2 again, but never with itself
+        assert_eq!(plausible_pairs(&find_candidates(&text)).len(), 2);
+
+        let error = pick_pair(&text, None).unwrap_err().to_string();
+        assert!(error.contains("choose one with --select"), "{error}");
+        assert!(
+            error.contains("2) line 2 (byte 25) + line 3 (byte 50)"),
+            "{error}"
+        );
+        assert_eq!(pick_pair(&text, Some(2)).unwrap(), [SHARE2, SHARE1]);
+        assert!(pick_pair(&text, Some(3)).is_err());
+        assert!(pick_pair("no shares here", None).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! `xplit compare`: check that two shares still recover a known secret
+//!
+//! For custody attestations: the secret is recovered in memory, hashed, and
+//! wiped, and only MATCH or MISMATCH is printed with the secret's length. The
+//! expected digest comes from the command line or a `sha256sum`-style file.
+
+use clap::Args;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use zeroize::Zeroizing;
+
+use super::CliResult;
+use super::io::ensure_single_stdin;
+use super::shares::{ShareArg, load_share, recover_shares};
+
+/// Exit code when the shares recover a secret with a different digest
+pub const EXIT_MISMATCH: u8 = 3;
+/// Exit code when the shares don't recover at all
+pub const EXIT_RECOVERY_FAILED: u8 = 4;
+
+#[derive(Args)]
+#[command(group = clap::ArgGroup::new("expected").required(true))]
+pub struct CompareArgs {
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    share1: ShareArg,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    share2: ShareArg,
+    /// Expected SHA-256 of the secret, in hex
+    #[arg(long, value_name = "HEX", group = "expected")]
+    sha256: Option<String>,
+    /// Read the expected SHA-256 from a checksum file, as written by `sha256sum`
+    #[arg(long, value_name = "PATH", group = "expected")]
+    sha256_file: Option<PathBuf>,
+    /// Expected BLAKE3 hash of the secret, in hex
+    #[arg(long, value_name = "HEX", group = "expected")]
+    blake3: Option<String>,
+    /// Read passphrases for protected shares from a file (give twice for per-share passphrases)
+    #[arg(long, value_name = "PATH")]
+    passphrase_file: Vec<PathBuf>,
+}
+
+pub fn run(args: &CompareArgs) -> CliResult {
+    let (algorithm, expected) = expected_digest(args)?;
+    ensure_single_stdin(
+        [&args.share1, &args.share2]
+            .into_iter()
+            .filter_map(ShareArg::input),
+    )?;
+
+    let recovered = load_share(&args.share1, 1, args.passphrase_file.first(), None)
+        .and_then(|share1| {
+            let share2 = load_share(&args.share2, 2, args.passphrase_file.last(), None)?;
+            recover_shares(&share1.bytes, &share2.bytes)
+        })
+        .map(Zeroizing::new);
+    let secret = match recovered {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!("Error: Failed to recover secret: {e}");
+            return Ok(ExitCode::from(EXIT_RECOVERY_FAILED));
+        }
+    };
+
+    let digest = Zeroizing::new(match algorithm {
+        "sha256" => Sha256::digest(&*secret).to_vec(),
+        _ => blake3::hash(&secret).as_bytes().to_vec(),
+    });
+    let mut out = io::stdout().lock();
+    if *digest == expected {
+        writeln!(out, "MATCH ({} bytes, {algorithm})", secret.len())?;
+        Ok(ExitCode::SUCCESS)
+    } else {
+        writeln!(out, "MISMATCH ({} bytes, {algorithm})", secret.len())?;
+        Ok(ExitCode::from(EXIT_MISMATCH))
+    }
+}
+
+/// The hash algorithm and the digest the secret should have
+fn expected_digest(args: &CompareArgs) -> CliResult<(&'static str, Vec<u8>)> {
+    let (algorithm, text) = if let Some(hex) = &args.sha256 {
+        ("sha256", hex.clone())
+    } else if let Some(path) = &args.sha256_file {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        ("sha256", checksum_file_digest(&contents)?.to_owned())
+    } else {
+        ("blake3", args.blake3.clone().unwrap_or_default())
+    };
+
+    let digest = hex::decode(text.trim())
+        .map_err(|e| format!("Expected {algorithm} digest is not valid hex: {e}"))?;
+    if digest.len() != 32 {
+        return Err(format!(
+            "Expected {algorithm} digest must be 64 hex digits, got {}",
+            text.trim().len()
+        )
+        .into());
+    }
+    Ok((algorithm, digest))
+}
+
+/// The digest on the first entry of a `sha256sum`-style file (`HEX  NAME`, or
+/// just `HEX`)
+fn checksum_file_digest(contents: &str) -> CliResult<&str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_whitespace().next())
+        .ok_or_else(|| "Checksum file holds no digest".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_file_digest() {
+        let hex = "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f";
+        assert_eq!(
+            checksum_file_digest(&format!("# vault\n{hex}  secret.txt\n")).unwrap(),
+            hex
+        );
+        assert_eq!(checksum_file_digest(&format!("{hex}\n")).unwrap(), hex);
+        assert!(checksum_file_digest("\n\n").is_err());
+    }
+}
@@ -0,0 +1,475 @@
+//! `xplit doctor`: diagnose why a pair of shares won't recover
+//!
+//! Tries every encoding the CLI knows, undoes common copy/paste mangling, and
+//! reports what it found plus the most likely fix. The recovered plaintext is
+//! never produced: a clean recovery path is established from the checksums and
+//! lengths alone. Share order doesn't matter for XOR, so a swapped pair is never
+//! the problem and isn't reported as one.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use clap::Args;
+use std::io::Write;
+use std::process::ExitCode;
+use xplit::chunked::{check_chunks, is_chunked};
+use xplit::is_valid_share;
+use xplit::pad::{is_padded, open_padded};
+use xplit::protect::is_protected;
+use xplit::sign::{is_signed, open_signed_share};
+
+use super::CliResult;
+use super::io::{IoTarget, ensure_single_stdin, open_output};
+use super::shares::{ShareArg, describe_bad_chunks};
+
+const CRC_LEN: usize = 4;
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in1", required_unless_present = "in1")]
+    share1: Option<ShareArg>,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in2", required_unless_present = "in2")]
+    share2: Option<ShareArg>,
+    /// First share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in1: Option<IoTarget>,
+    /// Second share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in2: Option<IoTarget>,
+    /// Write the report to a file, or `-` for stdout (the default)
+    #[arg(long, value_name = "PATH|-")]
+    out: Option<IoTarget>,
+}
+
+/// Print the diagnosis; exits non-zero unless a clean recovery path was found
+pub fn run(args: &DoctorArgs) -> CliResult {
+    let source1 = ShareArg::pick(args.share1.as_ref(), args.in1.as_ref())?;
+    let source2 = ShareArg::pick(args.share2.as_ref(), args.in2.as_ref())?;
+    ensure_single_stdin([&source1, &source2].into_iter().filter_map(ShareArg::input))?;
+    let raw1 = source1.read_raw()?;
+    let raw2 = source2.read_raw()?;
+
+    let report = diagnose(&raw1, &raw2);
+    let mut out = open_output(args.out.as_ref())?;
+    for line in &report.lines {
+        writeln!(out, "{line}")?;
+    }
+    out.flush()?;
+
+    Ok(if report.recoverable {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Outcome of diagnosing a pair of shares
+pub struct Report {
+    pub lines: Vec<String>,
+    pub recoverable: bool,
+}
+
+/// What could be made of one share file
+struct Diagnosis {
+    /// Decoded bytes from the first encoding that produced any, with any
+    /// signature envelope removed
+    bytes: Option<Vec<u8>>,
+    /// The same bytes before any envelope was opened
+    decoded: Option<Vec<u8>>,
+    /// Whether the decoded share passed every check we could run
+    valid: bool,
+    /// Checksum can't be checked (passphrase-protected)
+    opaque: bool,
+}
+
+/// Diagnose a pair of raw share files
+pub fn diagnose(raw1: &[u8], raw2: &[u8]) -> Report {
+    let mut lines = Vec::new();
+    let d1 = diagnose_share(1, raw1, &mut lines);
+    let d2 = diagnose_share(2, raw2, &mut lines);
+
+    let (fix, recoverable) = match (&d1.bytes, &d2.bytes) {
+        (None, _) => (undecodable(1), false),
+        (_, None) => (undecodable(2), false),
+        (Some(b1), Some(b2)) => pair_fix(b1, b2, &d1, &d2),
+    };
+
+    lines.push(String::new());
+    lines.push(format!("Most likely fix: {fix}"));
+    Report { lines, recoverable }
+}
+
+/// One share checked on its own, e.g. as it is pasted
+pub struct ShareCheck {
+    pub lines: Vec<String>,
+    /// The share as decoded, envelopes intact, if it passed every check
+    pub accepted: Option<Vec<u8>>,
+    /// Payload length to compare against the other share, unless it is
+    /// passphrase-protected
+    pub payload_len: Option<usize>,
+}
+
+/// Diagnose a single raw share
+pub fn check_share(index: u8, raw: &[u8]) -> ShareCheck {
+    let mut lines = Vec::new();
+    let diagnosis = diagnose_share(index, raw, &mut lines);
+    let payload_len = diagnosis
+        .bytes
+        .as_ref()
+        .filter(|_| !diagnosis.opaque)
+        .map(Vec::len);
+    ShareCheck {
+        lines,
+        accepted: diagnosis.decoded.filter(|_| diagnosis.valid),
+        payload_len,
+    }
+}
+
+fn undecodable(index: u8) -> String {
+    format!(
+        "share{index} could not be decoded in any known encoding; re-copy it from the original medium"
+    )
+}
+
+fn pair_fix(b1: &[u8], b2: &[u8], d1: &Diagnosis, d2: &Diagnosis) -> (String, bool) {
+    if d1.opaque || d2.opaque {
+        return (
+            "a share is passphrase-protected; run `xplit verify` on it with its passphrase"
+                .to_owned(),
+            d1.valid && d2.valid,
+        );
+    }
+
+    if b1.len() != b2.len() {
+        let (short, long, diff) = if b1.len() < b2.len() {
+            (1, 2, b2.len() - b1.len())
+        } else {
+            (2, 1, b1.len() - b2.len())
+        };
+        let short_valid = if short == 1 { d1.valid } else { d2.valid };
+        let fix = if short_valid {
+            format!(
+                "shares have different lengths ({diff} bytes apart) but both checksums pass; they were not split together"
+            )
+        } else {
+            format!(
+                "share{short} appears truncated by {diff} bytes relative to share{long}; re-copy or re-scan share{short}"
+            )
+        };
+        return (fix, false);
+    }
+
+    match (d1.valid, d2.valid) {
+        (true, true) => (
+            "none needed - this pair should recover cleanly".to_owned(),
+            true,
+        ),
+        (false, true) => (
+            "share1 is corrupted; restore it from another copy".to_owned(),
+            false,
+        ),
+        (true, false) => (
+            "share2 is corrupted; restore it from another copy".to_owned(),
+            false,
+        ),
+        (false, false) => (
+            "both shares are corrupted; restore them from other copies".to_owned(),
+            false,
+        ),
+    }
+}
+
+fn diagnose_share(index: u8, raw: &[u8], lines: &mut Vec<String>) -> Diagnosis {
+    lines.push(format!("share{index}: {} bytes read", raw.len()));
+
+    let mut text = String::from_utf8_lossy(raw).into_owned();
+    if let Some(stripped) = text.strip_prefix('\u{feff}') {
+        lines.push(format!("  removed byte-order mark from share{index}"));
+        text = stripped.to_owned();
+    }
+
+    if text.lines().any(|line| line.trim_start().starts_with('>')) {
+        lines.push(format!("  removed email quoting ('>') from share{index}"));
+        text = text
+            .lines()
+            .map(|line| line.trim_start().trim_start_matches('>').trim_start())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    if let Some(label) = ["Share 1:", "Share 2:"]
+        .into_iter()
+        .find(|label| text.trim_start().starts_with(label))
+    {
+        lines.push(format!("  removed '{label}' label from share{index}"));
+        text = text.trim_start()[label.len()..].to_owned();
+    }
+
+    let trimmed = text.trim();
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.len() != trimmed.len() {
+        lines.push(format!(
+            "  removed embedded whitespace/line breaks from share{index}"
+        ));
+    }
+
+    let Some((encoding, bytes)) = decode_any(&compact) else {
+        if !compact.len().is_multiple_of(4) && STANDARD_NO_PAD.decode(&compact).is_err() {
+            lines.push(format!(
+                "  share{index} is not valid base64 or hex ({} characters; base64 length should be a multiple of 4)",
+                compact.len()
+            ));
+        } else {
+            lines.push(format!("  share{index} is not valid base64 or hex"));
+        }
+        return Diagnosis {
+            bytes: None,
+            decoded: None,
+            valid: false,
+            opaque: false,
+        };
+    };
+    lines.push(format!("  decoded as {encoding} ({} bytes)", bytes.len()));
+    let decoded = Some(bytes.clone());
+
+    let mut share = bytes;
+    if is_signed(&share) {
+        match open_signed_share(&share, None) {
+            Ok((inner, _)) => {
+                lines.push("  signature: valid (embedded key)".to_owned());
+                share = inner;
+            }
+            Err(e) => {
+                lines.push(format!("  signature: {e}"));
+                return Diagnosis {
+                    bytes: Some(share),
+                    decoded,
+                    valid: false,
+                    opaque: false,
+                };
+            }
+        }
+    }
+
+    if is_protected(&share) {
+        lines.push(
+            "  passphrase-protected: checksum can't be checked without the passphrase".to_owned(),
+        );
+        return Diagnosis {
+            bytes: Some(share),
+            decoded,
+            valid: true,
+            opaque: true,
+        };
+    }
+
+    let valid = check_checksums(&share, lines);
+    Diagnosis {
+        bytes: Some(share),
+        decoded,
+        valid,
+        opaque: false,
+    }
+}
+
+/// Check a share's checksum, or every chunk's for a chunked share, looking
+/// inside the padding envelope
+fn check_checksums(share: &[u8], lines: &mut Vec<String>) -> bool {
+    if is_padded(share) {
+        return match open_padded(share) {
+            Ok((padding, inner)) => {
+                lines.push(format!("  padded: yes ({padding})"));
+                check_checksums(inner, lines)
+            }
+            Err(e) => {
+                lines.push(format!("  padded: {e}"));
+                false
+            }
+        };
+    }
+    if is_chunked(share) {
+        return match check_chunks(share) {
+            Ok(report) if report.bad.is_empty() => {
+                lines.push(format!(
+                    "  checksum: OK ({} byte payload in {} chunks)",
+                    report.payload_len, report.chunks
+                ));
+                true
+            }
+            Ok(report) => {
+                lines.push(format!(
+                    "  checksum: FAIL ({})",
+                    describe_bad_chunks(&report)
+                ));
+                false
+            }
+            Err(e) => {
+                lines.push(format!("  checksum: FAIL ({e})"));
+                false
+            }
+        };
+    }
+
+    let valid = is_valid_share(share);
+    if share.len() <= CRC_LEN {
+        lines.push(format!(
+            "  checksum: FAIL (only {} bytes, too short to hold a payload)",
+            share.len()
+        ));
+    } else if valid {
+        lines.push(format!(
+            "  checksum: OK ({} byte payload)",
+            share.len() - CRC_LEN
+        ));
+    } else {
+        lines.push("  checksum: FAIL".to_owned());
+    }
+    valid
+}
+
+/// Try each known encoding, preferring one whose result passes its checksum
+fn decode_any(text: &str) -> Option<(&'static str, Vec<u8>)> {
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut candidates: Vec<(&'static str, Vec<u8>)> = Vec::new();
+    for (name, engine) in [
+        ("base64", &STANDARD),
+        ("base64 (unpadded)", &STANDARD_NO_PAD),
+        ("base64url", &URL_SAFE),
+        ("base64url (unpadded)", &URL_SAFE_NO_PAD),
+    ] {
+        if let Ok(bytes) = engine.decode(text) {
+            candidates.push((name, bytes));
+        }
+    }
+    if let Ok(bytes) = hex::decode(text) {
+        candidates.push(("hex", bytes));
+    }
+
+    let best = candidates
+        .iter()
+        .position(|(_, bytes)| {
+            is_valid_share(bytes)
+                || is_signed(bytes)
+                || is_protected(bytes)
+                || is_chunked(bytes)
+                || is_padded(bytes)
+        })
+        .unwrap_or(0);
+    (!candidates.is_empty()).then(|| candidates.swap_remove(best))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use quickcheck::TestResult;
+    use xplit::{recover_secret, split_secret};
+
+    #[test]
+    fn test_clean_pair_is_recoverable() {
+        let report = diagnose(b"ZiTjk3OD6puSVM/JV3CYopI=\n", b"LkGP/xyvysz9JqOtdpOmJ8A=\n");
+        assert!(report.recoverable);
+    }
+
+    #[test]
+    fn test_mangling_is_undone() {
+        let report = diagnose(
+            "\u{feff}> ZiTjk3OD6puSVM/J\n> V3CYopI=\n".as_bytes(),
+            b"Share 2: LkGP/xyvysz9JqOtdpOmJ8A=",
+        );
+        assert!(report.recoverable);
+        assert!(report.lines.iter().any(|l| l.contains("email quoting")));
+        assert!(report.lines.iter().any(|l| l.contains("byte-order mark")));
+    }
+
+    #[test]
+    fn test_truncation_is_reported() {
+        let shares = split_secret([0xAB; 32]).unwrap();
+        let truncated = BASE64.encode(&shares.share1[..shares.share1.len() - 6]);
+        let intact = BASE64.encode(&shares.share2);
+
+        let report = diagnose(truncated.as_bytes(), intact.as_bytes());
+        assert!(!report.recoverable);
+        assert!(
+            report
+                .lines
+                .last()
+                .unwrap()
+                .contains("share1 appears truncated by 6 bytes")
+        );
+    }
+
+    #[test]
+    fn test_hex_share_is_accepted() {
+        let shares = split_secret(b"hex please").unwrap();
+        let report = diagnose(
+            hex::encode(&shares.share1).as_bytes(),
+            BASE64.encode(&shares.share2).as_bytes(),
+        );
+        assert!(report.recoverable);
+        assert!(report.lines.iter().any(|l| l.contains("decoded as hex")));
+    }
+
+    #[test]
+    fn test_check_share_accepts_mangled_paste() {
+        let check = check_share(1, b"Share 1: ZiTjk3OD6puSVM/J V3CYopI=");
+        assert_eq!(
+            check.accepted,
+            BASE64.decode("ZiTjk3OD6puSVM/JV3CYopI=").ok()
+        );
+        assert_eq!(check.payload_len, Some(17));
+
+        let check = check_share(1, b"ZiTjk3OD6puSVM/JV3CYopA=");
+        assert!(check.accepted.is_none());
+        assert!(check.lines.iter().any(|l| l.contains("checksum: FAIL")));
+    }
+
+    #[test]
+    fn test_report_never_contains_plaintext() {
+        let shares = split_secret(b"top secret plaintext").unwrap();
+        let report = diagnose(
+            BASE64.encode(&shares.share1).as_bytes(),
+            BASE64.encode(&shares.share2).as_bytes(),
+        );
+        assert!(report.lines.iter().all(|l| !l.contains("top secret")));
+    }
+
+    /// An encoder for every encoding `decode_any` detects
+    const ENCODERS: [fn(&[u8]) -> String; 6] = [
+        |b| STANDARD.encode(b),
+        |b| STANDARD_NO_PAD.encode(b),
+        |b| URL_SAFE.encode(b),
+        |b| URL_SAFE_NO_PAD.encode(b),
+        |b| hex::encode(b),
+        |b| hex::encode_upper(b),
+    ];
+
+    quickcheck::quickcheck! {
+        fn prop_any_encoding_pair_recovers(secret: Vec<u8>, pick1: usize, pick2: usize) -> TestResult {
+            if secret.is_empty() {
+                return TestResult::discard();
+            }
+            let shares = split_secret(&secret).unwrap();
+            let text1 = ENCODERS[pick1 % ENCODERS.len()](&shares.share1);
+            let text2 = ENCODERS[pick2 % ENCODERS.len()](&shares.share2);
+
+            // Auto-detection must give back the exact share bytes...
+            let (Some((_, share1)), Some((_, share2))) = (decode_any(&text1), decode_any(&text2))
+            else {
+                return TestResult::failed();
+            };
+            if share1 != shares.share1 || share2 != shares.share2 {
+                return TestResult::failed();
+            }
+            // ...so recovery works whichever encodings the two shares used
+            TestResult::from_bool(
+                recover_secret(&share1, &share2).ok() == Some(secret)
+                    && diagnose(text1.as_bytes(), text2.as_bytes()).recoverable,
+            )
+        }
+    }
+}
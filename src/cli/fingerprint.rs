@@ -0,0 +1,40 @@
+//! `xplit fingerprint`: label a pair of shares for an inventory
+//!
+//! Prints [`xplit::share_set_fingerprint`] as colon-separated hex. Shares are
+//! fingerprinted as stored, so protected shares need no passphrase and the
+//! secret is never recovered.
+
+use clap::Args;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use xplit::share_set_fingerprint;
+
+use super::CliResult;
+use super::io::ensure_single_stdin;
+use super::shares::ShareArg;
+
+#[derive(Args)]
+pub struct FingerprintArgs {
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    share1: ShareArg,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    share2: ShareArg,
+}
+
+pub fn run(args: &FingerprintArgs) -> CliResult {
+    ensure_single_stdin(
+        [&args.share1, &args.share2]
+            .into_iter()
+            .filter_map(ShareArg::input),
+    )?;
+    let share1 = args.share1.decode(1)?;
+    let share2 = args.share2.decode(2)?;
+
+    let fingerprint = share_set_fingerprint(&share1, &share2)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":");
+    writeln!(io::stdout().lock(), "{fingerprint}")?;
+    Ok(ExitCode::SUCCESS)
+}
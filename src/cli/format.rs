@@ -0,0 +1,168 @@
+//! `--format` output templates
+//!
+//! A format is literal text with `{placeholder}`s, `{{`/`}}` for literal braces
+//! and `\n`, `\t`, `\\` escapes. Each subcommand passes the placeholders it can
+//! fill, so an unknown one is rejected before any work is done instead of being
+//! printed literally.
+
+use std::fmt;
+
+/// One parsed piece of a format
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Placeholder(&'static str),
+}
+
+/// A validated `--format` string
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputFormat(Vec<Piece>);
+
+/// Why a format was rejected
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+    UnknownPlaceholder {
+        name: String,
+        allowed: &'static [&'static str],
+    },
+    UnclosedBrace,
+    UnopenedBrace,
+    UnknownEscape(char),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPlaceholder { name, allowed } => write!(
+                f,
+                "unknown placeholder {{{name}}} (expected one of: {})",
+                allowed
+                    .iter()
+                    .map(|p| format!("{{{p}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::UnclosedBrace => write!(
+                f,
+                "'{{' without a matching '}}' (use '{{{{' for a literal brace)"
+            ),
+            Self::UnopenedBrace => write!(
+                f,
+                "'}}' without a matching '{{' (use '}}}}' for a literal brace)"
+            ),
+            Self::UnknownEscape(c) => {
+                write!(f, "unknown escape '\\{c}' (expected \\n, \\t or \\\\)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl OutputFormat {
+    /// Parse `format`, accepting only the placeholders in `allowed`
+    pub fn parse(format: &str, allowed: &'static [&'static str]) -> Result<Self, FormatError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => literal.push('\n'),
+                    Some('t') => literal.push('\t'),
+                    Some('\\') | None => literal.push('\\'),
+                    Some(other) => return Err(FormatError::UnknownEscape(other)),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(FormatError::UnopenedBrace),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(FormatError::UnclosedBrace),
+                        }
+                    }
+                    let placeholder = allowed
+                        .iter()
+                        .find(|p| **p == name)
+                        .ok_or(FormatError::UnknownPlaceholder { name, allowed })?;
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Piece::Placeholder(placeholder));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Self(pieces))
+    }
+
+    /// Whether the format references `{name}`
+    pub fn uses(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|piece| matches!(piece, Piece::Placeholder(p) if *p == name))
+    }
+
+    /// Render with `value` supplying each placeholder
+    pub fn render(&self, mut value: impl FnMut(&str) -> String) -> String {
+        let mut out = String::new();
+        for piece in &self.0 {
+            match piece {
+                Piece::Literal(text) => out.push_str(text),
+                Piece::Placeholder(name) => out.push_str(&value(name)),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALLOWED: &[&str] = &["share1", "share2", "len"];
+
+    #[test]
+    fn test_render_with_escapes() {
+        let format =
+            OutputFormat::parse(r"S1={share1}\nS2={share2}\t{{{len}}}\\", ALLOWED).unwrap();
+        let rendered = format.render(str::to_uppercase);
+        assert_eq!(rendered, "S1=SHARE1\nS2=SHARE2\t{LEN}\\");
+        assert!(format.uses("len"));
+        assert!(!format.uses("secret"));
+    }
+
+    #[test]
+    fn test_unknown_placeholder_is_rejected() {
+        assert!(matches!(
+            OutputFormat::parse("{secret}", ALLOWED),
+            Err(FormatError::UnknownPlaceholder { name, .. }) if name == "secret"
+        ));
+        assert_eq!(
+            OutputFormat::parse("{share1", ALLOWED),
+            Err(FormatError::UnclosedBrace)
+        );
+        assert_eq!(
+            OutputFormat::parse("share1}", ALLOWED),
+            Err(FormatError::UnopenedBrace)
+        );
+        assert_eq!(
+            OutputFormat::parse(r"\r", ALLOWED),
+            Err(FormatError::UnknownEscape('r'))
+        );
+    }
+}
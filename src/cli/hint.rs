@@ -0,0 +1,27 @@
+//! `xplit hint`: show the recovery hint written by `split --hint`
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::Args;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use xplit::open_hint;
+
+use super::CliResult;
+use super::io::IoTarget;
+
+#[derive(Args)]
+pub struct HintArgs {
+    /// Hint file, or `-` for stdin
+    #[arg(value_name = "PATH|-")]
+    input: IoTarget,
+}
+
+pub fn run(args: &HintArgs) -> CliResult {
+    let encoded = args.input.read_all()?;
+    let sealed = BASE64
+        .decode(encoded.trim_ascii())
+        .map_err(|e| format!("Failed to decode hint from base64: {e}"))?;
+    let hint = open_hint(&sealed).map_err(|e| format!("Hint is damaged: {e}"))?;
+    writeln!(io::stdout().lock(), "{hint}")?;
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,139 @@
+//! `split --holder`: one instruction sheet per share holder
+//!
+//! Each sheet carries only its holder's share, so it can be handed over as-is.
+//! The text comes from [`DEFAULT_SHEET`] or from a `--holder-template` file,
+//! which uses the same `{placeholder}` syntax as `--format`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::CliResult;
+use super::format::OutputFormat;
+use super::template::validate_label;
+
+/// Placeholders accepted in a sheet template
+pub const SHEET_PLACEHOLDERS: &[&str] = &[
+    "holder", "other", "index", "share", "fp", "label", "id", "len", "file",
+];
+
+/// Sheet used when no `--holder-template` is given
+pub const DEFAULT_SHEET: &str = "\
+xplit share {index} of 2 for {holder}
+Secret: {label} (split {id}, {len} bytes)
+
+Your share:
+
+{share}
+
+Fingerprint: {fp}
+The share is also saved as {file}.
+
+The other share is held by {other}. Either share alone reveals nothing about
+the secret; both are needed to recover it. Keep yours somewhere safe and never
+store it together with {other}'s.
+
+To check your share is intact:
+
+    xplit verify @{file}
+
+To recover the secret together with {other}, each of you pastes your share
+when prompted (the fingerprint above is shown once yours is accepted):
+
+    xplit recover --paste
+";
+
+/// Values for one holder's sheet
+pub struct SheetVars<'a> {
+    pub holder: &'a str,
+    pub other: &'a str,
+    pub index: u8,
+    /// Share as written to the share file, base64-encoded
+    pub share: &'a str,
+    pub fingerprint: &'a str,
+    pub label: Option<&'a str>,
+    pub id: &'a str,
+    pub len: usize,
+    pub file: &'a Path,
+}
+
+/// Check a `--holder` name, which also ends up in the sheet's file name
+pub fn parse_holder(name: &str) -> Result<String, String> {
+    if name.trim().is_empty() {
+        return Err("holder name must not be empty".to_owned());
+    }
+    validate_label(name).map_err(|e| e.to_string().replacen("label", "holder name", 1))?;
+    Ok(name.to_owned())
+}
+
+/// Load and validate the sheet template, before any share is produced
+pub fn load_template(path: Option<&Path>) -> CliResult<OutputFormat> {
+    let text = match path {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        None => DEFAULT_SHEET.to_owned(),
+    };
+    OutputFormat::parse(&text, SHEET_PLACEHOLDERS)
+        .map_err(|e| format!("Invalid --holder-template: {e}").into())
+}
+
+/// Where the sheet for `holder` goes, next to their share file
+pub fn sheet_path(share_path: &Path, holder: &str) -> PathBuf {
+    let mut name = share_path.file_name().unwrap_or_default().to_owned();
+    name.push(format!(".{holder}.txt"));
+    share_path.with_file_name(name)
+}
+
+/// Render one holder's sheet
+pub fn render(template: &OutputFormat, vars: &SheetVars) -> String {
+    template.render(|name| match name {
+        "holder" => vars.holder.to_owned(),
+        "other" => vars.other.to_owned(),
+        "index" => vars.index.to_string(),
+        "share" => vars.share.to_owned(),
+        "fp" => vars.fingerprint.to_owned(),
+        "label" => vars.label.unwrap_or("unlabeled").to_owned(),
+        "id" => vars.id.to_owned(),
+        "len" => vars.len.to_string(),
+        _ => vars.file.display().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_sheet_names_both_holders() {
+        let template = load_template(None).unwrap();
+        let sheet = render(
+            &template,
+            &SheetVars {
+                holder: "alice",
+                other: "bob",
+                index: 1,
+                share: "ZiTjk3OD6puSVM/JV3CYopI=",
+                fingerprint: "01:02",
+                label: Some("vault"),
+                id: "1a2b3c4d",
+                len: 13,
+                file: Path::new("vault.share1"),
+            },
+        );
+
+        assert!(sheet.starts_with("xplit share 1 of 2 for alice\nSecret: vault"));
+        assert!(sheet.contains("\nZiTjk3OD6puSVM/JV3CYopI=\n"));
+        assert!(sheet.contains("held by bob"));
+        assert!(sheet.contains("xplit verify @vault.share1"));
+        assert_eq!(
+            sheet_path(Path::new("out/vault.share1"), "alice"),
+            PathBuf::from("out/vault.share1.alice.txt")
+        );
+    }
+
+    #[test]
+    fn test_holder_names_are_checked() {
+        assert!(parse_holder("alice").is_ok());
+        assert!(parse_holder(" ").is_err());
+        assert!(parse_holder("../bob").is_err());
+    }
+}
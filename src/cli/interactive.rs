@@ -0,0 +1,311 @@
+//! `xplit interactive`: a guided dialog for people who'd rather not learn the flags
+//!
+//! Every answer is turned into the arguments of a regular `split` or `recover`
+//! run, so the wizard never behaves differently from the commands it wraps.
+//! Secrets and shares are typed without echo.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::Parser;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use xplit::share_fingerprint;
+
+use super::CliResult;
+use super::io::IoTarget;
+use super::paste;
+use super::recover::RecoverArgs;
+use super::shares::{ShareArg, share_path};
+use super::split::SplitArgs;
+
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct SplitCommand {
+    #[command(flatten)]
+    args: SplitArgs,
+}
+
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct RecoverCommand {
+    #[command(flatten)]
+    args: RecoverArgs,
+}
+
+pub fn run() -> CliResult {
+    if !io::stdin().is_terminal() {
+        return Err(
+            "xplit interactive needs a terminal; use `xplit split` or `xplit recover` in scripts"
+                .into(),
+        );
+    }
+    let mut dialog = Dialog {
+        input: io::stdin().lock(),
+        output: io::stderr(),
+    };
+
+    let action = dialog.choose(
+        "What would you like to do?",
+        &[
+            "Split a secret into two shares",
+            "Recover a secret from two shares",
+        ],
+    )?;
+    if action == 0 {
+        split(&mut dialog)
+    } else {
+        recover(&mut dialog)
+    }
+}
+
+fn split<R: BufRead, W: Write>(dialog: &mut Dialog<R, W>) -> CliResult {
+    let mut argv = Vec::new();
+    let mut secret = None;
+    let source = dialog.choose(
+        "Where is the secret?",
+        &["I'll type or paste it (it won't be shown)", "In a file"],
+    )?;
+    if source == 0 {
+        let typed = rpassword::prompt_password("Secret: ")?;
+        if typed.is_empty() {
+            return Err("The secret cannot be empty".into());
+        }
+        if rpassword::prompt_password("Type it again to confirm: ")? != typed {
+            return Err("The two entries don't match; nothing was split".into());
+        }
+        secret = Some(typed);
+    } else {
+        argv.extend([
+            "--in".to_owned(),
+            dialog.ask_existing_file("Path to the file: ")?,
+        ]);
+    }
+
+    let destination = dialog.choose(
+        "Where should the shares go?",
+        &["Save them as two files", "Show them on screen"],
+    )?;
+    let mut prefix = None;
+    if destination == 0 {
+        let name = dialog.ask_default("Name for the share files", "share")?;
+        let paths = [1, 2].map(|index| share_path(Path::new(&name), index));
+        if let Some(existing) = paths.iter().find(|path| path.exists()) {
+            return Err(format!("{} already exists; pick another name", existing.display()).into());
+        }
+        argv.extend(["--output".to_owned(), name.clone()]);
+        if dialog.confirm("Lock each share file with its own passphrase?")? {
+            argv.push("--protect".to_owned());
+        }
+        prefix = Some(paths);
+    }
+
+    // After `--`, a secret starting with `-` can't be mistaken for a flag
+    argv.extend(
+        secret
+            .map(|secret| ["--".to_owned(), secret])
+            .into_iter()
+            .flatten(),
+    );
+    let command = SplitCommand::try_parse_from(argv)?;
+    let code = super::split::run(command.args)?;
+
+    let out = &mut dialog.output;
+    if let Some(paths) = prefix {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "Write down these fingerprints to check the shares later:"
+        )?;
+        for (index, path) in (1..).zip(&paths) {
+            writeln!(out, "  Share {index}: {}", file_fingerprint(path)?)?;
+        }
+    }
+    writeln!(out)?;
+    writeln!(out, "Next steps:")?;
+    writeln!(out, "  1. Give each share to a different person or place.")?;
+    writeln!(
+        out,
+        "  2. Never keep both shares together; either one alone reveals nothing."
+    )?;
+    writeln!(
+        out,
+        "  3. To get the secret back, run `xplit interactive` and choose recover."
+    )?;
+    Ok(code)
+}
+
+fn recover<R: BufRead, W: Write>(dialog: &mut Dialog<R, W>) -> CliResult {
+    let mut shares = Vec::new();
+    for index in 1..=2 {
+        let source = dialog.choose(
+            &format!("Where is share {index}?"),
+            &["I'll paste it (it won't be shown)", "In a file"],
+        )?;
+        if source == 0 {
+            // Checked as it is pasted, which also prints its fingerprint
+            let (share, _) = paste::prompt_share(index)?;
+            shares.push(BASE64.encode(share));
+            continue;
+        }
+
+        let path = dialog.ask_existing_file(&format!("Path to share {index}: "))?;
+        let raw = ShareArg::Input(IoTarget::Path(path.clone().into())).read_raw()?;
+        let fingerprint = BASE64
+            .decode(raw.trim_ascii())
+            .map(|bytes| share_fingerprint(&bytes))
+            .map_err(|_| format!("{path} does not hold a valid share"))?;
+        writeln!(dialog.output, "Share {index} fingerprint: {fingerprint}")?;
+        shares.push(format!("@{path}"));
+    }
+    if !dialog.confirm("Do these fingerprints match the ones you wrote down?")? {
+        return Err("Stopped before recovering; find the matching shares and try again".into());
+    }
+
+    let destination = dialog.choose(
+        "Where should the secret go?",
+        &["Show it on screen", "Save it to a file"],
+    )?;
+    let mut argv = Vec::new();
+    let mut saved = None;
+    if destination == 1 {
+        let path = dialog.ask("Save as: ")?;
+        if Path::new(&path).exists() {
+            return Err(format!("{path} already exists; pick another name").into());
+        }
+        argv.extend(["--out".to_owned(), path.clone()]);
+        saved = Some(path);
+    }
+
+    argv.push("--".to_owned());
+    argv.extend(shares);
+    let command = RecoverCommand::try_parse_from(argv)?;
+    let code = super::recover::run(&command.args)?;
+
+    let out = &mut dialog.output;
+    writeln!(out)?;
+    writeln!(out, "Next steps:")?;
+    if let Some(path) = saved {
+        writeln!(out, "  1. Use the secret in {path}, then delete the file.")?;
+    } else {
+        writeln!(
+            out,
+            "  1. Clear your screen and terminal history once you're done."
+        )?;
+    }
+    writeln!(out, "  2. Put both shares back in their separate places.")?;
+    Ok(code)
+}
+
+/// Fingerprint of the share in a file just written by split
+fn file_fingerprint(path: &Path) -> CliResult<String> {
+    let encoded = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read back {}: {e}", path.display()))?;
+    Ok(share_fingerprint(&BASE64.decode(encoded.trim())?))
+}
+
+/// Questions asked on `output`, answered on `input`
+struct Dialog<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Dialog<R, W> {
+    fn ask(&mut self, prompt: &str) -> CliResult<String> {
+        write!(self.output, "{prompt}")?;
+        self.output.flush()?;
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            return Err("Cancelled".into());
+        }
+        Ok(answer.trim().to_owned())
+    }
+
+    fn ask_default(&mut self, prompt: &str, default: &str) -> CliResult<String> {
+        let answer = self.ask(&format!("{prompt} [{default}]: "))?;
+        Ok(if answer.is_empty() {
+            default.to_owned()
+        } else {
+            answer
+        })
+    }
+
+    fn ask_existing_file(&mut self, prompt: &str) -> CliResult<String> {
+        loop {
+            let answer = self.ask(prompt)?;
+            if PathBuf::from(&answer).is_file() {
+                return Ok(answer);
+            }
+            writeln!(self.output, "No file at '{answer}', try again.")?;
+        }
+    }
+
+    /// Ask until one of `options` is picked by number; returns its index
+    fn choose(&mut self, question: &str, options: &[&str]) -> CliResult<usize> {
+        writeln!(self.output, "{question}")?;
+        for (number, option) in (1..).zip(options) {
+            writeln!(self.output, "  {number}) {option}")?;
+        }
+        loop {
+            let answer = self.ask("> ")?;
+            if let Some(index) = answer
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .filter(|index| *index < options.len())
+            {
+                return Ok(index);
+            }
+            writeln!(
+                self.output,
+                "Please enter a number from 1 to {}.",
+                options.len()
+            )?;
+        }
+    }
+
+    fn confirm(&mut self, question: &str) -> CliResult<bool> {
+        let answer = self.ask(&format!("{question} [y/N] "))?;
+        Ok(matches!(answer.to_ascii_lowercase().as_str(), "y" | "yes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialog(answers: &str) -> Dialog<&[u8], Vec<u8>> {
+        Dialog {
+            input: answers.as_bytes(),
+            output: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_choose_retries_until_valid() {
+        let mut d = dialog("0\nthree\n2\n");
+        assert_eq!(d.choose("Pick", &["a", "b"]).unwrap(), 1);
+        let shown = String::from_utf8(d.output).unwrap();
+        assert_eq!(shown.matches("Please enter a number").count(), 2);
+
+        assert!(dialog("").choose("Pick", &["a"]).is_err());
+    }
+
+    #[test]
+    fn test_confirm_and_defaults() {
+        assert!(dialog("Y\n").confirm("Sure?").unwrap());
+        assert!(!dialog("\n").confirm("Sure?").unwrap());
+        assert_eq!(dialog("\n").ask_default("Name", "share").unwrap(), "share");
+        assert_eq!(
+            dialog("vault\n").ask_default("Name", "share").unwrap(),
+            "vault"
+        );
+    }
+
+    #[test]
+    fn test_answers_map_to_real_arguments() {
+        let split = ["--output", "share", "--protect", "--", "-starts-with-dash"];
+        assert!(SplitCommand::try_parse_from(split).is_ok());
+        let recover = ["--out", "s", "--", "@a.share1", "@a.share2"];
+        assert!(RecoverCommand::try_parse_from(recover).is_ok());
+    }
+}
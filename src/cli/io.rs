@@ -0,0 +1,162 @@
+//! `--in`/`--out` handling shared by every subcommand
+//!
+//! A target is either a path or `-`, meaning stdin for inputs and stdout for
+//! outputs. Positional arguments remain as sugar; clap rejects a positional
+//! given together with its `--in` flag.
+//!
+//! Rust's stdin and stdout are binary on every platform (no CRLF translation),
+//! but a Windows console can only display UTF-8, so raw binary is refused there
+//! rather than garbled. A reader exiting early (`xplit split | head`) shows up as
+//! a broken pipe, or `ERROR_NO_DATA` on Windows, which is not worth reporting.
+
+use std::convert::Infallible;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::CliResult;
+
+/// A path, or `-` for stdin/stdout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IoTarget {
+    Std,
+    Path(PathBuf),
+}
+
+impl FromStr for IoTarget {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s == "-" {
+            Self::Std
+        } else {
+            Self::Path(PathBuf::from(s))
+        })
+    }
+}
+
+impl IoTarget {
+    /// Read the whole input
+    pub fn read_all(&self) -> CliResult<Vec<u8>> {
+        match self {
+            Self::Std => {
+                let mut buffer = Vec::new();
+                io::stdin().read_to_end(&mut buffer)?;
+                Ok(buffer)
+            }
+            Self::Path(path) => {
+                fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()).into())
+            }
+        }
+    }
+
+    /// Open the output for writing; files are created readable only by the owner
+    pub fn create(&self) -> CliResult<Box<dyn Write>> {
+        match self {
+            Self::Std => Ok(Box::new(io::stdout().lock())),
+            Self::Path(path) => Ok(Box::new(create_private_file(path)?)),
+        }
+    }
+}
+
+/// Open `--out` (or stdout when absent)
+pub fn open_output(out: Option<&IoTarget>) -> CliResult<Box<dyn Write>> {
+    out.unwrap_or(&IoTarget::Std).create()
+}
+
+/// Whether `error` only means the reader of our output went away
+pub fn is_broken_pipe(error: &(dyn Error + 'static)) -> bool {
+    /// `ERROR_NO_DATA`: "The pipe is being closed"
+    const WINDOWS_NO_DATA: i32 = 232;
+
+    error.downcast_ref::<io::Error>().is_some_and(|e| {
+        e.kind() == io::ErrorKind::BrokenPipe
+            || (cfg!(windows) && e.raw_os_error() == Some(WINDOWS_NO_DATA))
+    })
+}
+
+/// Refuse to send non-UTF-8 bytes to a Windows console, which can't display them
+pub fn check_console_bytes(bytes: &[u8], windows_console: bool) -> CliResult<()> {
+    if windows_console && std::str::from_utf8(bytes).is_err() {
+        return Err(
+            "Refusing to write binary data to the Windows console; use --out PATH instead".into(),
+        );
+    }
+    Ok(())
+}
+
+/// Reject more than one input reading from stdin
+pub fn ensure_single_stdin<'a>(inputs: impl IntoIterator<Item = &'a IoTarget>) -> CliResult<()> {
+    if inputs
+        .into_iter()
+        .filter(|input| **input == IoTarget::Std)
+        .count()
+        > 1
+    {
+        return Err("Only one input can be read from stdin ('-')".into());
+    }
+    Ok(())
+}
+
+/// Create (or truncate) a file with owner-only permissions
+pub fn create_private_file(path: &Path) -> CliResult<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dash_means_std() {
+        assert_eq!("-".parse::<IoTarget>().unwrap(), IoTarget::Std);
+        assert_eq!(
+            "share.txt".parse::<IoTarget>().unwrap(),
+            IoTarget::Path(PathBuf::from("share.txt"))
+        );
+        assert_eq!(
+            "./-".parse::<IoTarget>().unwrap(),
+            IoTarget::Path(PathBuf::from("./-"))
+        );
+    }
+
+    #[test]
+    fn test_broken_pipe_detection() {
+        let broken: Box<dyn Error> = Box::new(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(is_broken_pipe(&*broken));
+
+        let other: Box<dyn Error> = Box::new(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_broken_pipe(&*other));
+        assert!(!is_broken_pipe(&*Box::<dyn Error>::from("Broken pipe")));
+
+        let no_data: Box<dyn Error> = Box::new(io::Error::from_raw_os_error(232));
+        assert_eq!(is_broken_pipe(&*no_data), cfg!(windows));
+    }
+
+    #[test]
+    fn test_console_refuses_binary() {
+        assert!(check_console_bytes(&[0xff, 0x00], true).is_err());
+        assert!(check_console_bytes("caf\u{e9}".as_bytes(), true).is_ok());
+        assert!(check_console_bytes(&[0xff, 0x00], false).is_ok());
+    }
+
+    #[test]
+    fn test_single_stdin() {
+        let path = IoTarget::Path(PathBuf::from("a"));
+        assert!(ensure_single_stdin([&IoTarget::Std, &path]).is_ok());
+        assert!(ensure_single_stdin([&path, &path]).is_ok());
+        assert!(ensure_single_stdin([&IoTarget::Std, &IoTarget::Std]).is_err());
+    }
+}
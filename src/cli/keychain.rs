@@ -0,0 +1,286 @@
+//! OS keychain storage for shares (`keyring` feature)
+//!
+//! Shares are stored base64 encoded under the `xplit-share` service, one entry
+//! per name. Platform keychains can't be enumerated portably, so the names are
+//! also recorded in an index entry that `xplit keychain list` reads.
+
+use clap::{Args, Subcommand};
+use std::fmt;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use super::CliResult;
+
+/// Prefix marking a share argument as a keychain entry
+pub const SCHEME: &str = "keychain:";
+
+#[derive(Args)]
+pub struct KeychainArgs {
+    #[command(subcommand)]
+    action: KeychainAction,
+}
+
+#[derive(Subcommand)]
+enum KeychainAction {
+    /// List shares stored in the keychain
+    List,
+    /// Delete a stored share
+    Delete {
+        /// Entry name (with or without the `keychain:` prefix)
+        name: String,
+    },
+}
+
+/// A `keychain:NAME` reference given on the command line
+#[derive(Clone, Debug)]
+pub struct KeychainRef(String);
+
+impl KeychainRef {
+    /// Entry holding share `index` of a `--keyring NAME` pair: `NAME.share<index>`
+    pub fn for_share(name: &str, index: u8) -> Self {
+        Self(format!("{name}.share{index}"))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Parse a `--keyring` name, with or without the `keychain:` prefix
+pub fn parse_name(s: &str) -> Result<String, String> {
+    let name = s.strip_prefix(SCHEME).unwrap_or(s);
+    validate_name(name)?;
+    Ok(name.to_owned())
+}
+
+impl FromStr for KeychainRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| format!("expected {SCHEME}NAME"))?;
+        validate_name(name)?;
+        Ok(Self(name.to_owned()))
+    }
+}
+
+impl fmt::Display for KeychainRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SCHEME}{}", self.0)
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['\n', '\r']) {
+        return Err("keychain entry names must be non-empty single-line strings".to_owned());
+    }
+    Ok(())
+}
+
+pub fn run(args: &KeychainArgs) -> CliResult {
+    let mut out = io::stdout().lock();
+    match &args.action {
+        KeychainAction::List => {
+            for name in backend::list()? {
+                writeln!(out, "{SCHEME}{name}")?;
+            }
+        }
+        KeychainAction::Delete { name } => {
+            let name = name.strip_prefix(SCHEME).unwrap_or(name);
+            backend::delete(name)?;
+            writeln!(out, "Deleted {SCHEME}{name}")?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Fail early if `target` already holds a share
+pub fn ensure_absent(target: &KeychainRef) -> CliResult<()> {
+    if backend::exists(target.name())? {
+        return Err(format!(
+            "{target} already exists; delete it first with `xplit keychain delete {}`",
+            target.name()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Store a base64 encoded share
+pub fn store(target: &KeychainRef, encoded: &str) -> CliResult<()> {
+    backend::store(target.name(), encoded)
+}
+
+/// Delete a stored share
+pub fn delete(target: &KeychainRef) -> CliResult<()> {
+    backend::delete(target.name())
+}
+
+/// Fetch a stored share as base64
+pub fn fetch(name: &str) -> CliResult<String> {
+    backend::fetch(name)
+}
+
+/// Add `name` to a newline-separated index, keeping it sorted and unique
+#[cfg(feature = "keyring")]
+fn index_insert(index: &str, name: &str) -> String {
+    let mut names: Vec<&str> = index.lines().filter(|n| !n.is_empty()).collect();
+    names.push(name);
+    names.sort_unstable();
+    names.dedup();
+    names.join("\n")
+}
+
+/// Remove `name` from a newline-separated index
+#[cfg(feature = "keyring")]
+fn index_remove(index: &str, name: &str) -> String {
+    index
+        .lines()
+        .filter(|n| !n.is_empty() && *n != name)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "keyring")]
+mod backend {
+    use keyring::{Entry, Error};
+
+    use super::{CliResult, SCHEME, index_insert, index_remove};
+
+    const SHARE_SERVICE: &str = "xplit-share";
+    const INDEX_SERVICE: &str = "xplit-index";
+    const INDEX_USER: &str = "entries";
+
+    pub fn exists(name: &str) -> CliResult<bool> {
+        match entry(name)?.get_password() {
+            Ok(_) => Ok(true),
+            Err(Error::NoEntry) => Ok(false),
+            Err(e) => Err(explain(e, name)),
+        }
+    }
+
+    pub fn store(name: &str, encoded: &str) -> CliResult<()> {
+        entry(name)?
+            .set_password(encoded)
+            .map_err(|e| explain(e, name))?;
+        let index = read_index()?;
+        write_index(&index_insert(&index, name))
+    }
+
+    pub fn fetch(name: &str) -> CliResult<String> {
+        entry(name)?.get_password().map_err(|e| explain(e, name))
+    }
+
+    pub fn delete(name: &str) -> CliResult<()> {
+        entry(name)?
+            .delete_credential()
+            .map_err(|e| explain(e, name))?;
+        let index = read_index()?;
+        write_index(&index_remove(&index, name))
+    }
+
+    pub fn list() -> CliResult<Vec<String>> {
+        Ok(read_index()?.lines().map(str::to_owned).collect())
+    }
+
+    fn entry(name: &str) -> CliResult<Entry> {
+        Entry::new(SHARE_SERVICE, name).map_err(|e| explain(e, name))
+    }
+
+    fn read_index() -> CliResult<String> {
+        match Entry::new(INDEX_SERVICE, INDEX_USER).and_then(|e| e.get_password()) {
+            Ok(index) => Ok(index),
+            Err(Error::NoEntry) => Ok(String::new()),
+            Err(e) => Err(explain(e, "index")),
+        }
+    }
+
+    fn write_index(index: &str) -> CliResult<()> {
+        let entry = Entry::new(INDEX_SERVICE, INDEX_USER).map_err(|e| explain(e, "index"))?;
+        let result = if index.is_empty() {
+            match entry.delete_credential() {
+                Err(Error::NoEntry) => Ok(()),
+                other => other,
+            }
+        } else {
+            entry.set_password(index)
+        };
+        result.map_err(|e| explain(e, "index"))
+    }
+
+    fn explain(error: Error, name: &str) -> Box<dyn std::error::Error> {
+        match error {
+            Error::NoEntry => format!("{SCHEME}{name} not found").into(),
+            Error::NoStorageAccess(_) | Error::PlatformFailure(_) => format!(
+                "keychain unavailable: {error}\n\
+                 hint: on headless Linux a Secret Service provider (e.g. gnome-keyring) must be \
+                 running and unlocked; otherwise write the share to a file with --output"
+            )
+            .into(),
+            other => format!("keychain error for {SCHEME}{name}: {other}").into(),
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod backend {
+    use super::CliResult;
+
+    const UNAVAILABLE: &str =
+        "xplit was built without keychain support; rebuild with `--features keyring`";
+
+    pub fn exists(_: &str) -> CliResult<bool> {
+        Err(UNAVAILABLE.into())
+    }
+
+    pub fn store(_: &str, _: &str) -> CliResult<()> {
+        Err(UNAVAILABLE.into())
+    }
+
+    pub fn fetch(_: &str) -> CliResult<String> {
+        Err(UNAVAILABLE.into())
+    }
+
+    pub fn delete(_: &str) -> CliResult<()> {
+        Err(UNAVAILABLE.into())
+    }
+
+    pub fn list() -> CliResult<Vec<String>> {
+        Err(UNAVAILABLE.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ref() {
+        let target: KeychainRef = "keychain:myvault".parse().unwrap();
+        assert_eq!(target.name(), "myvault");
+        assert_eq!(target.to_string(), "keychain:myvault");
+
+        assert!("myvault".parse::<KeychainRef>().is_err());
+        assert!("keychain:".parse::<KeychainRef>().is_err());
+
+        let name = parse_name("keychain:myvault").unwrap();
+        assert_eq!(parse_name("myvault").unwrap(), name);
+        assert_eq!(
+            KeychainRef::for_share(&name, 2).to_string(),
+            "keychain:myvault.share2"
+        );
+        assert!(parse_name("").is_err());
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn test_index_edits() {
+        let index = index_insert("", "b");
+        let index = index_insert(&index, "a");
+        assert_eq!(index_insert(&index, "a"), "a\nb");
+        assert_eq!(index_remove(&index, "a"), "b");
+        assert_eq!(index_remove("b", "b"), "");
+    }
+}
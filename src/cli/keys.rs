@@ -0,0 +1,100 @@
+//! `xplit keygen` and Ed25519 key files
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::Args;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use xplit::sign::{SigningKey, VerifyingKey, generate_signing_key, key_fingerprint};
+use zeroize::Zeroizing;
+
+use super::CliResult;
+use super::io::IoTarget;
+use super::shares::path_with_suffix;
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    /// Private key file to create; the public key is written to `<PATH>.pub`
+    #[arg(conflicts_with = "out", required_unless_present = "out")]
+    path: Option<PathBuf>,
+    /// Same as the positional path (key files can't be written to stdout)
+    #[arg(long, value_name = "PATH")]
+    out: Option<IoTarget>,
+}
+
+pub fn run_keygen(args: &KeygenArgs) -> CliResult {
+    let path = match (&args.path, &args.out) {
+        (Some(path), _) | (None, Some(IoTarget::Path(path))) => path,
+        (None, Some(IoTarget::Std)) => {
+            return Err("Key files must be written to a path, not stdout".into());
+        }
+        (None, None) => return Err("Missing key file path".into()),
+    };
+
+    let key = generate_signing_key();
+    let public_path = path_with_suffix(path, ".pub");
+
+    write_key_file(path, &BASE64.encode(key.to_bytes()))?;
+    write_key_file(&public_path, &BASE64.encode(key.verifying_key().as_bytes()))?;
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "Private key written to {}", path.display())?;
+    writeln!(out, "Public key written to {}", public_path.display())?;
+    writeln!(
+        out,
+        "Fingerprint: {}",
+        key_fingerprint(&key.verifying_key())
+    )?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Read a base64 private key file written by `xplit keygen`
+pub fn read_signing_key(path: &Path) -> CliResult<SigningKey> {
+    let bytes = read_key_file(path)?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| format!("{} is not an Ed25519 private key", path.display()))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// Read a base64 public key file written by `xplit keygen`
+pub fn read_verifying_key(path: &Path) -> CliResult<VerifyingKey> {
+    let bytes = read_key_file(path)?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| format!("{} is not an Ed25519 public key", path.display()))?;
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| format!("{} is not an Ed25519 public key", path.display()).into())
+}
+
+fn read_key_file(path: &Path) -> CliResult<Zeroizing<Vec<u8>>> {
+    let contents = Zeroizing::new(
+        fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read key file {}: {e}", path.display()))?,
+    );
+    let bytes = BASE64
+        .decode(contents.trim())
+        .map_err(|_| format!("{} is not a valid key file", path.display()))?;
+    Ok(Zeroizing::new(bytes))
+}
+
+/// Create a new key file readable only by the owner
+fn write_key_file(path: &Path, contents: &str) -> CliResult<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    writeln!(file, "{contents}")?;
+    Ok(())
+}
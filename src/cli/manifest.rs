@@ -0,0 +1,359 @@
+//! `xplit manifest` and `xplit verify-manifest`: pin a pair of shares for archival
+//!
+//! A manifest records the SHA-256 and length of each share as stored, and
+//! [`xplit::share_set_fingerprint`] of the pair, so years later the shares can
+//! be checked against it: a share that was swapped for one from another
+//! split, or rewritten with a fresh but self-consistent CRC, no longer
+//! matches. Nothing is recovered and no passphrase is needed.
+//!
+//! The manifest is plain text and ends in a SHA-256 of its own lines, which
+//! catches damage to the manifest itself. With `--sign-key` that digest is
+//! also signed, which catches a manifest rewritten to match other shares.
+//!
+//! ```text
+//! xplit-manifest 1
+//! set 3f:1c:0b:9e:54:a2:77:d0
+//! share <sha256 hex> <length>
+//! share <sha256 hex> <length>
+//! sha256 <hex of the lines above>
+//! signature <base64 signed envelope over that digest>
+//! ```
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::Args;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use xplit::share_set_fingerprint;
+use xplit::sign::{key_fingerprint, open_signed_share, sign_share};
+
+use super::CliResult;
+use super::compare::EXIT_MISMATCH;
+use super::io::{IoTarget, ensure_single_stdin, open_output};
+use super::keys::{read_signing_key, read_verifying_key};
+use super::shares::ShareArg;
+
+const HEADER: &str = "xplit-manifest 1";
+
+#[derive(Args)]
+pub struct ManifestArgs {
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in1", required_unless_present = "in1")]
+    share1: Option<ShareArg>,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in2", required_unless_present = "in2")]
+    share2: Option<ShareArg>,
+    /// First share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in1: Option<IoTarget>,
+    /// Second share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in2: Option<IoTarget>,
+    /// Write the manifest to a file, or `-` for stdout (the default)
+    #[arg(long, value_name = "PATH|-")]
+    out: Option<IoTarget>,
+    /// Sign the manifest with an Ed25519 private key from `xplit keygen`
+    #[arg(long, value_name = "PATH")]
+    sign_key: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VerifyManifestArgs {
+    /// Manifest written by `xplit manifest`
+    manifest: PathBuf,
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in1", required_unless_present = "in1")]
+    share1: Option<ShareArg>,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in2", required_unless_present = "in2")]
+    share2: Option<ShareArg>,
+    /// First share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in1: Option<IoTarget>,
+    /// Second share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in2: Option<IoTarget>,
+    /// Require the manifest to be signed by this public key (`<PATH>.pub` from `xplit keygen`)
+    #[arg(long, value_name = "PATH")]
+    verify_key: Option<PathBuf>,
+}
+
+/// A share as the manifest records it
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    digest: [u8; 32],
+    len: usize,
+}
+
+impl Entry {
+    fn of(share: &[u8]) -> Self {
+        Self {
+            digest: Sha256::digest(share).into(),
+            len: share.len(),
+        }
+    }
+}
+
+/// The checked contents of a manifest
+#[derive(Debug)]
+struct Manifest {
+    set: String,
+    shares: [Entry; 2],
+    /// Fingerprint of the key that signed the manifest, if it was signed
+    signer: Option<String>,
+}
+
+pub fn run(args: &ManifestArgs) -> CliResult {
+    let [share1, share2] = read_pair(
+        [args.share1.as_ref(), args.share2.as_ref()],
+        [args.in1.as_ref(), args.in2.as_ref()],
+    )?;
+    let signing_key = args.sign_key.as_deref().map(read_signing_key).transpose()?;
+
+    let mut text = format!("{HEADER}\nset {}\n", set_fingerprint(&share1, &share2));
+    for share in [&share1, &share2] {
+        let entry = Entry::of(share);
+        writeln!(text, "share {} {}", hex::encode(entry.digest), entry.len)?;
+    }
+    let digest = Sha256::digest(&text);
+    writeln!(text, "sha256 {}", hex::encode(digest))?;
+    if let Some(key) = &signing_key {
+        writeln!(
+            text,
+            "signature {}",
+            BASE64.encode(sign_share(&digest, key))
+        )?;
+    }
+
+    let mut out = open_output(args.out.as_ref())?;
+    out.write_all(text.as_bytes())?;
+    out.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Check both shares against the manifest; exits with [`EXIT_MISMATCH`] if
+/// either one isn't the share the manifest recorded
+pub fn run_verify(args: &VerifyManifestArgs) -> CliResult {
+    let text = fs::read_to_string(&args.manifest)
+        .map_err(|e| format!("Failed to read {}: {e}", args.manifest.display()))?;
+    let expected_signer = args
+        .verify_key
+        .as_deref()
+        .map(read_verifying_key)
+        .transpose()?;
+    let manifest = parse_manifest(&text, expected_signer.as_ref())
+        .map_err(|e| format!("{}: {e}", args.manifest.display()))?;
+    let [share1, share2] = read_pair(
+        [args.share1.as_ref(), args.share2.as_ref()],
+        [args.in1.as_ref(), args.in2.as_ref()],
+    )?;
+
+    let mut out = io::stdout().lock();
+    match &manifest.signer {
+        Some(signer) if expected_signer.is_some() => {
+            writeln!(out, "Manifest: intact, signed by {signer}")?;
+        }
+        Some(signer) => writeln!(
+            out,
+            "Manifest: intact, signed by {signer} (signer not checked)"
+        )?,
+        None => writeln!(out, "Manifest: intact, unsigned")?,
+    }
+
+    let matched = match_shares(&manifest.shares, [&share1, &share2]);
+    for (index, found) in (1..).zip(matched) {
+        if found {
+            writeln!(out, "Share {index}: OK")?;
+        } else {
+            writeln!(
+                out,
+                "Share {index}: MISMATCH (not the share this manifest recorded)"
+            )?;
+        }
+    }
+    // Matching both entries already pins the pair; the set fingerprint is
+    // compared too so a hand-edited manifest can't disagree with itself
+    let set = set_fingerprint(&share1, &share2);
+    if set == manifest.set {
+        writeln!(out, "Pair: OK ({set})")?;
+    } else {
+        writeln!(
+            out,
+            "Pair: MISMATCH (manifest has {}, shares give {set})",
+            manifest.set
+        )?;
+    }
+
+    Ok(if matched == [true, true] && set == manifest.set {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_MISMATCH)
+    })
+}
+
+/// Read the two shares as stored, from their positionals or `--in` flags
+fn read_pair(
+    positional: [Option<&ShareArg>; 2],
+    flags: [Option<&IoTarget>; 2],
+) -> CliResult<[Vec<u8>; 2]> {
+    let source1 = ShareArg::pick(positional[0], flags[0])?;
+    let source2 = ShareArg::pick(positional[1], flags[1])?;
+    ensure_single_stdin([&source1, &source2].into_iter().filter_map(ShareArg::input))?;
+    Ok([source1.decode(1)?, source2.decode(2)?])
+}
+
+fn set_fingerprint(share1: &[u8], share2: &[u8]) -> String {
+    share_set_fingerprint(share1, share2)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Which of `shares` match an entry, each entry matching at most one share
+///
+/// Share order doesn't matter for recovery, so neither does it here.
+fn match_shares(entries: &[Entry; 2], shares: [&[u8]; 2]) -> [bool; 2] {
+    let mut unused = [true, true];
+    shares.map(|share| {
+        let entry = Entry::of(share);
+        let found = entries
+            .iter()
+            .zip(&mut unused)
+            .find(|(expected, unused)| **unused && **expected == entry);
+        found.map(|(_, unused)| *unused = false).is_some()
+    })
+}
+
+/// Parse a manifest, checking its digest and, if present, its signature
+fn parse_manifest(
+    text: &str,
+    expected_signer: Option<&xplit::sign::VerifyingKey>,
+) -> Result<Manifest, String> {
+    let damaged = |what: &str| format!("manifest is damaged: {what}");
+    let body_len = text
+        .find("\nsha256 ")
+        .map(|at| at + 1)
+        .ok_or_else(|| damaged("no sha256 line"))?;
+    let (body, trailer) = text.split_at(body_len);
+    let mut trailer = trailer.lines();
+
+    let digest = trailer
+        .next()
+        .and_then(|line| line.strip_prefix("sha256 "))
+        .and_then(|hex| hex::decode(hex.trim()).ok())
+        .ok_or_else(|| damaged("unreadable sha256 line"))?;
+    if digest != Sha256::digest(body).as_slice() {
+        return Err(damaged("its contents don't match its sha256"));
+    }
+
+    let signer = match trailer.next().map(|line| line.strip_prefix("signature ")) {
+        Some(Some(encoded)) => {
+            let envelope = BASE64
+                .decode(encoded.trim())
+                .map_err(|_| damaged("unreadable signature"))?;
+            let (signed, issuer) = open_signed_share(&envelope, None)
+                .map_err(|e| format!("manifest signature rejected: {e}"))?;
+            if expected_signer.is_some_and(|expected| expected != &issuer) {
+                return Err(format!(
+                    "manifest was signed by {}, not the --verify-key key",
+                    key_fingerprint(&issuer)
+                ));
+            }
+            if signed != digest {
+                return Err("manifest signature rejected: it signs a different manifest".into());
+            }
+            Some(key_fingerprint(&issuer))
+        }
+        Some(None) => return Err(damaged("unexpected line after sha256")),
+        None if expected_signer.is_some() => {
+            return Err("manifest is not signed, but --verify-key was given".into());
+        }
+        None => None,
+    };
+    if trailer.next().is_some() {
+        return Err(damaged("unexpected line after the signature"));
+    }
+
+    let mut lines = body.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(format!("not an xplit manifest (expected {HEADER:?})"));
+    }
+    let set = lines
+        .next()
+        .and_then(|line| line.strip_prefix("set "))
+        .ok_or_else(|| damaged("no set line"))?
+        .to_owned();
+    let mut entry = || {
+        let line = lines.next().and_then(|line| line.strip_prefix("share "))?;
+        let (digest, len) = line.split_once(' ')?;
+        Some(Entry {
+            digest: hex::decode(digest).ok()?.try_into().ok()?,
+            len: len.parse().ok()?,
+        })
+    };
+    let shares = [entry(), entry()];
+    if lines.next().is_some() {
+        return Err(damaged("unexpected line before sha256"));
+    }
+    let [Some(first), Some(second)] = shares else {
+        return Err(damaged("bad share line"));
+    };
+
+    Ok(Manifest {
+        set,
+        shares: [first, second],
+        signer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_for(shares: [&[u8]; 2]) -> String {
+        let mut text = format!("{HEADER}\nset {}\n", set_fingerprint(shares[0], shares[1]));
+        for share in shares {
+            let entry = Entry::of(share);
+            writeln!(text, "share {} {}", hex::encode(entry.digest), entry.len).unwrap();
+        }
+        let digest = hex::encode(Sha256::digest(&text));
+        text + &format!("sha256 {digest}\n")
+    }
+
+    #[test]
+    fn test_manifest_matches_shares_in_either_order() {
+        let manifest = parse_manifest(&manifest_for([b"share one", b"share two"]), None).unwrap();
+        assert_eq!(manifest.signer, None);
+        assert_eq!(
+            match_shares(&manifest.shares, [b"share two", b"share one"]),
+            [true, true]
+        );
+        assert_eq!(
+            match_shares(&manifest.shares, [b"share one", b"share one"]),
+            [true, false]
+        );
+        assert_eq!(
+            match_shares(&manifest.shares, [b"share one", b"share 2"]),
+            [true, false]
+        );
+    }
+
+    #[test]
+    fn test_damaged_manifest_is_rejected() {
+        let text = manifest_for([b"share one", b"share two"]);
+        let edited = text.replacen(" 9\n", " 8\n", 1);
+        assert_ne!(edited, text);
+        assert!(
+            parse_manifest(&edited, None)
+                .unwrap_err()
+                .contains("sha256")
+        );
+        assert!(parse_manifest(&text.replace("sha256 ", "sha512 "), None).is_err());
+        assert!(parse_manifest(&format!("{text}extra\n"), None).is_err());
+        assert!(parse_manifest(&text.replace(HEADER, "xplit-manifest 2"), None).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! Subcommand implementations for the `xplit` binary
+
+use std::error::Error;
+use std::process::ExitCode;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub mod clipboard;
+pub mod combined;
+pub mod compare;
+pub mod doctor;
+pub mod fingerprint;
+pub mod format;
+pub mod hint;
+pub mod holder;
+pub mod interactive;
+pub mod io;
+pub mod keychain;
+pub mod keys;
+pub mod manifest;
+pub mod passphrase;
+pub mod paste;
+pub mod recover;
+pub mod rotate;
+pub mod secret_cmd;
+pub mod shares;
+pub mod shred;
+pub mod split;
+pub mod template;
+pub mod verify;
+
+/// Result of running a subcommand
+pub type CliResult<T = ExitCode> = Result<T, Box<dyn Error>>;
+
+/// Flag set by Ctrl-C, for streaming operations to check between chunks
+///
+/// The handler is installed on first use; until then Ctrl-C keeps its default
+/// behavior of terminating the process.
+pub fn cancel_flag() -> &'static AtomicBool {
+    static CANCEL: AtomicBool = AtomicBool::new(false);
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        // Without a handler Ctrl-C still terminates the process, just less cleanly
+        let _ = ctrlc::set_handler(|| CANCEL.store(true, Ordering::Relaxed));
+    });
+    &CANCEL
+}
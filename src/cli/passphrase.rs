@@ -0,0 +1,29 @@
+//! Passphrase prompts and passphrase files
+
+use std::fs;
+use std::path::Path;
+
+use super::CliResult;
+
+/// Read a passphrase file, dropping a single trailing newline
+pub fn read_passphrase_file(path: &Path) -> CliResult<String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read passphrase file {}: {e}", path.display()))?;
+    let passphrase = contents
+        .strip_suffix('\n')
+        .map_or(contents.as_str(), |s| s.strip_suffix('\r').unwrap_or(s));
+    Ok(passphrase.to_owned())
+}
+
+/// Prompt twice for a new passphrase for share `index`
+pub fn prompt_new_passphrase(index: u8) -> CliResult<String> {
+    let passphrase = rpassword::prompt_password(format!("Passphrase for share {index}: "))?;
+    if passphrase.is_empty() {
+        return Err(format!("Passphrase for share {index} cannot be empty").into());
+    }
+    let confirm = rpassword::prompt_password(format!("Confirm passphrase for share {index}: "))?;
+    if passphrase != confirm {
+        return Err(format!("Passphrases for share {index} do not match").into());
+    }
+    Ok(passphrase)
+}
@@ -0,0 +1,112 @@
+//! `recover --paste`: prompted share entry with immediate validation
+//!
+//! During a phone-assisted recovery a mangled share should be caught while the
+//! custodian is still on the line, not minutes later. Each pasted share is run
+//! through the same checks as `xplit doctor` as soon as it is entered, and the
+//! prompt repeats with the specific problem until it passes or the user aborts.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use xplit::share_fingerprint;
+
+use super::CliResult;
+use super::doctor::{ShareCheck, check_share};
+
+/// ANSI sequence clearing the screen and the scrollback
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[3J\x1b[H";
+
+/// Prompt for both shares, re-prompting until each one validates
+///
+/// Returns the shares as decoded, with any signature or passphrase envelope
+/// still in place.
+pub fn prompt_pair() -> CliResult<[Vec<u8>; 2]> {
+    ensure_terminal()?;
+    let (share1, check1) = prompt_share(1)?;
+    loop {
+        let (share2, check2) = prompt_share(2)?;
+        if let Some(problem) = pair_problem(&check1, &check2) {
+            eprintln!("{problem}");
+            continue;
+        }
+        return Ok([share1, share2]);
+    }
+}
+
+/// Prompt for one share until it validates; an empty entry aborts
+pub fn prompt_share(index: u8) -> CliResult<(Vec<u8>, ShareCheck)> {
+    loop {
+        let pasted = rpassword::prompt_password(format!(
+            "Paste share {index} (it won't be shown; empty to abort): "
+        ))?;
+        if pasted.trim().is_empty() {
+            return Err("Aborted".into());
+        }
+
+        let check = check_share(index, pasted.as_bytes());
+        for line in &check.lines {
+            eprintln!("{line}");
+        }
+        if let Some(share) = check.accepted.clone() {
+            eprintln!("  fingerprint: {}", share_fingerprint(&share));
+            return Ok((share, check));
+        }
+        eprintln!("Share {index} was not accepted; paste it again.");
+    }
+}
+
+/// Why two individually valid shares can't belong together, if they can't
+fn pair_problem(check1: &ShareCheck, check2: &ShareCheck) -> Option<String> {
+    match (check1.payload_len, check2.payload_len) {
+        (Some(len1), Some(len2)) if len1 != len2 => Some(format!(
+            "Shares 1 and 2 have different lengths ({len1} and {len2} bytes), so they weren't \
+             split together; paste the share 2 that matches share 1."
+        )),
+        _ => None,
+    }
+}
+
+/// Wait for Enter before continuing
+pub fn wait_for_enter(prompt: &str) -> CliResult<()> {
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+    io::stdin().lock().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// Show `secret` only after a keypress, and clear it after another
+pub fn reveal(secret: &[u8], out: &mut dyn Write) -> CliResult<()> {
+    wait_for_enter("Press Enter to show the secret...")?;
+    out.write_all(secret)?;
+    if !secret.ends_with(b"\n") {
+        // Keep the next prompt off the secret's line
+        writeln!(out)?;
+    }
+    out.flush()?;
+    wait_for_enter("Press Enter to clear it from the screen...")?;
+    write!(out, "{CLEAR_SCREEN}")?;
+    out.flush()?;
+    Ok(())
+}
+
+fn ensure_terminal() -> CliResult<()> {
+    if !io::stdin().is_terminal() {
+        return Err(
+            "--paste needs a terminal; pass shares as arguments or files in scripts".into(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_problem_flags_different_lengths() {
+        let check = |raw: &str| check_share(1, raw.as_bytes());
+        let short = check("ZiTjk3OD6puSVM/JV3CYopI=");
+        let long = check("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+
+        assert!(pair_problem(&short, &check("LkGP/xyvysz9JqOtdpOmJ8A=")).is_none());
+        assert!(pair_problem(&short, &long).is_some());
+    }
+}
@@ -0,0 +1,515 @@
+//! `xplit recover`
+//!
+//! With `--raw` and both shares in files, plain shares are recovered as a
+//! stream: the output is written as the files are read, at whatever pace its
+//! reader takes it, and the secret is never held in memory whole. Checksums
+//! can only be checked at the end, so a failure leaves unverified output
+//! behind; `--verify-first` reads the files twice to avoid that.
+
+use base64::read::DecoderReader;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::Args;
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use xplit::chunked::is_chunked;
+use xplit::grid::is_grid;
+use xplit::pad::is_padded;
+use xplit::stream::{StreamError, recover_stream};
+use xplit::{
+    CrcOrder, RecoveredSecret, is_valid_share, recover_secret_crc_order, recover_secret_force,
+};
+use zeroize::Zeroizing;
+
+use super::CliResult;
+use super::cancel_flag;
+use super::clipboard;
+use super::combined;
+use super::format::OutputFormat;
+use super::io::{IoTarget, check_console_bytes, ensure_single_stdin, is_broken_pipe, open_output};
+use super::keychain::{self, KeychainRef};
+use super::keys::read_verifying_key;
+use super::paste;
+use super::shares::{
+    ShareArg, check_shares, load_share, recover_raw_shares, recover_shares, split_joined,
+};
+
+/// Placeholders accepted by `recover --format`
+const FORMAT_PLACEHOLDERS: &[&str] = &["secret", "secret_hex", "len"];
+
+/// Exit code when `--strict-utf8` rejects a recovered secret
+pub const EXIT_NOT_UTF8: u8 = 3;
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RecoverArgs {
+    /// First share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    ///
+    /// Both shares pasted as one argument, joined by `:` or whitespace, are
+    /// accepted too.
+    #[arg(conflicts_with = "in1", required_unless_present_any = ["in1", "keyring", "paste", "combined"])]
+    share1: Option<ShareArg>,
+    /// Second share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "in2", required_unless_present_any = ["share1", "in2", "keyring", "paste", "combined"])]
+    share2: Option<ShareArg>,
+    /// Read the first share from a file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in1: Option<IoTarget>,
+    /// Read the second share from a file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in2: Option<IoTarget>,
+    /// Read both shares from the OS keychain, as stored by `split --keyring NAME`
+    ///
+    /// Requires the `keyring` feature. When only one share is in the keychain,
+    /// pass it as `keychain:NAME` alongside the other share instead.
+    #[arg(
+        long,
+        value_name = "NAME",
+        value_parser = keychain::parse_name,
+        conflicts_with_all = ["share1", "share2", "in1", "in2"]
+    )]
+    keyring: Option<String>,
+    /// Paste each share at a prompt, checking it as soon as it is entered
+    ///
+    /// A share that fails to decode or verify is reported with the reason and
+    /// asked for again. On a terminal, the secret is only shown after pressing
+    /// Enter and is cleared from the screen after another.
+    #[arg(long, conflicts_with_all = ["share1", "share2", "in1", "in2", "keyring"])]
+    paste: bool,
+    /// Find both shares in one text file, or `-` for stdin
+    ///
+    /// Every base64 run that decodes to a share is a candidate. The pair used is
+    /// reported by line and byte offset; when several could belong together
+    /// they are listed and one has to be picked with --select.
+    #[arg(
+        long,
+        value_name = "PATH|-",
+        conflicts_with_all = ["share1", "share2", "in1", "in2", "keyring", "paste"]
+    )]
+    combined: Option<IoTarget>,
+    /// Pair to recover from when --combined finds several, numbered as listed
+    #[arg(long, value_name = "N", requires = "combined")]
+    select: Option<usize>,
+    /// Write the recovered secret to a file, or `-` for stdout (the default)
+    #[arg(long, value_name = "PATH|-")]
+    out: Option<IoTarget>,
+    /// Copy the secret to the clipboard instead of printing it, then clear it
+    ///
+    /// Requires the `clipboard` feature. xplit waits for --clear-after seconds
+    /// before clearing (Ctrl-C clears it early), and leaves the clipboard alone
+    /// if something else was copied meanwhile. Binary secrets need --format
+    /// with `{secret_hex}`.
+    #[arg(long, conflicts_with_all = ["out", "raw", "check", "no_newline"])]
+    to_clip: bool,
+    /// Seconds before --to-clip clears the clipboard
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "to_clip"
+    )]
+    clear_after: u64,
+    /// Never print a trailing newline, even on a terminal
+    ///
+    /// By default a newline is only added when writing to a terminal; piped or
+    /// `--out` output is always exactly the secret.
+    #[arg(long)]
+    no_newline: bool,
+    /// Write the recovered bytes verbatim, even when they aren't UTF-8 (implies --no-newline)
+    ///
+    /// When both shares are plain share files, the output is streamed as they
+    /// are read instead of being recovered in memory first.
+    #[arg(long)]
+    raw: bool,
+    /// When streaming, check both shares' checksums before writing anything
+    ///
+    /// Each share file is read twice. Without it a damaged share is only
+    /// noticed at the end: recover then fails, removes an --out file, and
+    /// whatever reached stdout is unverified and must be discarded.
+    #[arg(long)]
+    verify_first: bool,
+    /// Fail with exit code 3, printing nothing, unless the secret is valid UTF-8
+    ///
+    /// Catches corrupted shares or mismatched pairs when a text secret is expected,
+    /// instead of falling back to a hex dump.
+    #[arg(long, conflicts_with_all = ["raw", "lossy_utf8"])]
+    strict_utf8: bool,
+    /// Decode invalid UTF-8 with replacement characters instead of printing hex
+    #[arg(long, conflicts_with = "raw")]
+    lossy_utf8: bool,
+    /// Print the secret using a template, e.g. `SECRET={secret}\n`
+    ///
+    /// Placeholders: `{secret}` (UTF-8 text), `{secret_hex}` and `{len}` (bytes).
+    /// Use `\n` and `\t` for newlines and tabs, and `{{` `}}` for literal braces. No
+    /// trailing newline is added.
+    #[arg(long, value_name = "FORMAT", conflicts_with = "raw")]
+    format: Option<String>,
+    /// Read passphrases for protected shares from a file (give twice for per-share passphrases)
+    #[arg(long, value_name = "PATH")]
+    passphrase_file: Vec<PathBuf>,
+    /// Check that recovery would succeed, without computing the secret
+    ///
+    /// Decodes, unlocks and verifies both shares and checks that they pair up,
+    /// then reports the secret's length. The shares are never XOR'd, so no part
+    /// of the secret is produced in memory, on screen or on disk. Fails like
+    /// the real recovery would.
+    #[arg(long, conflicts_with_all = ["out", "raw", "format", "strict_utf8", "lossy_utf8", "crc_le"])]
+    check: bool,
+    /// Combine bare shares from `split --no-crc`, without any verification
+    ///
+    /// A corrupted share can't be detected and recovers to a wrong secret.
+    #[arg(long, conflicts_with_all = ["crc_le", "paste", "check"])]
+    no_crc: bool,
+    /// Read the shares' CRC32 trailers as little-endian
+    ///
+    /// For shares written by variant implementations that store the checksum
+    /// little-endian. Shares written by xplit are always big-endian.
+    #[arg(long, conflicts_with = "paste")]
+    crc_le: bool,
+    /// Last resort: ignore the shares' CRC32 checksums and XOR them anyway
+    ///
+    /// For a share whose checksum trailer is damaged but whose data may be
+    /// intact. Nothing is verified, so the output may be garbage; only use this
+    /// when a normal recovery fails and there is nothing to lose.
+    #[arg(long, conflicts_with_all = ["no_crc", "crc_le", "check"])]
+    force: bool,
+    /// Refuse to recover unless both shares carry valid signatures
+    #[arg(long)]
+    require_signed: bool,
+    /// Require both shares to be signed by this public key
+    #[arg(long, value_name = "PUBKEY")]
+    signer: Option<PathBuf>,
+}
+
+pub fn run(args: &RecoverArgs) -> CliResult {
+    let format = args
+        .format
+        .as_deref()
+        .map(|format| OutputFormat::parse(format, FORMAT_PLACEHOLDERS))
+        .transpose()
+        .map_err(|e| format!("Invalid --format: {e}"))?;
+
+    let (source1, source2) = if args.paste {
+        let [share1, share2] = paste::prompt_pair()?.map(|share| BASE64.encode(share));
+        (ShareArg::Literal(share1), ShareArg::Literal(share2))
+    } else if let Some(input) = &args.combined {
+        let text =
+            String::from_utf8(input.read_all()?).map_err(|_| "--combined input is not text")?;
+        let [share1, share2] = combined::pick_pair(&text, args.select)?;
+        (ShareArg::Literal(share1), ShareArg::Literal(share2))
+    } else if let (Some(ShareArg::Literal(joined)), None, None) =
+        (&args.share1, &args.share2, &args.in2)
+    {
+        let [share1, share2] = split_joined(joined)?;
+        (share1, share2)
+    } else if let Some(name) = &args.keyring {
+        let entry =
+            |index| ShareArg::Keychain(KeychainRef::for_share(name, index).name().to_owned());
+        (entry(1), entry(2))
+    } else {
+        (
+            ShareArg::pick(args.share1.as_ref(), args.in1.as_ref())?,
+            ShareArg::pick(args.share2.as_ref(), args.in2.as_ref())?,
+        )
+    };
+    ensure_single_stdin([&source1, &source2].into_iter().filter_map(ShareArg::input))?;
+    if let Some(paths) = streamable(args, [&source1, &source2])? {
+        return stream_recover(&paths, args);
+    }
+
+    let signer = args.signer.as_deref().map(read_verifying_key).transpose()?;
+    let share1 = load_share(&source1, 1, args.passphrase_file.first(), signer.as_ref())?;
+    let share2 = load_share(&source2, 2, args.passphrase_file.last(), signer.as_ref())?;
+
+    if args.require_signed || signer.is_some() {
+        for (index, share) in [(1, &share1), (2, &share2)] {
+            if share.signer.is_none() {
+                return Err(format!("share{index} is not signed").into());
+            }
+        }
+    }
+
+    if args.check {
+        let (summary, warnings) = check_shares(&share1.bytes, &share2.bytes)
+            .map_err(|e| format!("Recovery would fail: {e}"))?;
+        for warning in warnings {
+            eprintln!("Warning: {warning}");
+        }
+        let mut out = io::stdout().lock();
+        writeln!(out, "Recovery would succeed: {summary}")?;
+        writeln!(
+            out,
+            "Shares embed no digest of the secret, so its content is not checked."
+        )?;
+        writeln!(out, "No XOR was performed; the secret was not computed.")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let recovered = if args.no_crc {
+        eprintln!("Warning: --no-crc shares are not verified; a corrupted share recovers wrongly");
+        recover_raw_shares(&share1.bytes, &share2.bytes)
+    } else if args.force {
+        force_recover(&share1.bytes, &share2.bytes)
+    } else if args.crc_le {
+        recover_secret_crc_order(&share1.bytes, &share2.bytes, CrcOrder::LittleEndian)
+            .map_err(Into::into)
+    } else {
+        recover_shares(&share1.bytes, &share2.bytes)
+    };
+    let recovered =
+        RecoveredSecret::from(recovered.map_err(|e| format!("Failed to recover secret: {e}"))?);
+
+    if args.strict_utf8 && !recovered.is_text() {
+        eprintln!("Error: Recovered secret is not valid UTF-8 (corrupted shares or wrong pair?)");
+        return Ok(ExitCode::from(EXIT_NOT_UTF8));
+    }
+
+    if args.to_clip {
+        return copy_to_clipboard(&recovered, format.as_ref(), args);
+    }
+
+    let to_terminal = matches!(args.out, None | Some(IoTarget::Std)) && io::stdout().is_terminal();
+
+    let mut out = open_output(args.out.as_ref())?;
+    if args.paste && to_terminal {
+        // Held back until the user asks to see it
+        let mut held = Zeroizing::new(Vec::new());
+        write_secret(&mut *held, &recovered, format.as_ref(), args, true)?;
+        paste::reveal(&held, &mut out)?;
+    } else {
+        write_secret(&mut out, &recovered, format.as_ref(), args, to_terminal)?;
+    }
+    out.flush()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `recover --to-clip`: render the secret like it would be printed, without
+/// the newline, and hand it to the clipboard until --clear-after runs out
+fn copy_to_clipboard(
+    recovered: &RecoveredSecret,
+    format: Option<&OutputFormat>,
+    args: &RecoverArgs,
+) -> CliResult {
+    if !recovered.is_text() && format.is_none() && !args.lossy_utf8 {
+        return Err(
+            "Recovered secret is binary; use --format '{secret_hex}' to copy it as hex".into(),
+        );
+    }
+    let mut held = Zeroizing::new(Vec::new());
+    write_secret(&mut *held, recovered, format, args, false)?;
+    let text = std::str::from_utf8(&held)
+        .map_err(|_| "Formatted secret is not UTF-8 text; it can't be copied")?;
+    clipboard::copy_then_clear(text, Duration::from_secs(args.clear_after))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `recover --force`: XOR plain shares whatever their checksums say, warning
+/// loudly about each one that fails
+fn force_recover(share1: &[u8], share2: &[u8]) -> CliResult<Vec<u8>> {
+    if [share1, share2]
+        .iter()
+        .any(|share| is_chunked(share) || is_padded(share))
+    {
+        return Err("--force only works on plain shares".into());
+    }
+    eprintln!(
+        "WARNING: --force skips checksum verification. The output may be garbage; \
+         do not trust it without checking it another way."
+    );
+    for (index, share) in [(1, share1), (2, share2)] {
+        if !is_valid_share(share) {
+            eprintln!("WARNING: share{index} fails its checksum; using its data anyway");
+        }
+    }
+    Ok(recover_secret_force(share1, share2)?)
+}
+
+/// Write the recovered secret per `--format`, `--raw` or `--lossy-utf8`, or as
+/// text by default (hex for binary secrets)
+///
+/// A newline is only added for a terminal, unless --no-newline or --raw is given.
+fn write_secret(
+    out: &mut dyn Write,
+    secret: &RecoveredSecret,
+    format: Option<&OutputFormat>,
+    args: &RecoverArgs,
+    to_terminal: bool,
+) -> CliResult<()> {
+    let recovered = secret.as_bytes();
+    if let Some(format) = format {
+        let text = if !format.uses("secret") {
+            Cow::Borrowed("")
+        } else if args.lossy_utf8 {
+            String::from_utf8_lossy(recovered)
+        } else {
+            Cow::Borrowed(
+                std::str::from_utf8(recovered)
+                    .map_err(|_| "Recovered secret is not UTF-8; use {secret_hex} in --format")?,
+            )
+        };
+        let rendered = format.render(|name| match name {
+            "secret" => text.clone().into_owned(),
+            "secret_hex" => hex::encode(recovered),
+            _ => recovered.len().to_string(),
+        });
+        out.write_all(rendered.as_bytes())?;
+        return Ok(());
+    }
+    if args.raw {
+        check_console_bytes(recovered, cfg!(windows) && to_terminal)?;
+        out.write_all(recovered)?;
+    } else if args.lossy_utf8 {
+        write!(out, "{}", String::from_utf8_lossy(recovered))?;
+    } else {
+        match secret {
+            RecoveredSecret::Text(text) => write!(out, "{text}")?,
+            RecoveredSecret::Binary(bytes) => {
+                write!(out, "Binary data (hex): {}", hex::encode(bytes))?;
+            }
+        }
+    }
+    if to_terminal && !args.no_newline && !args.raw {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// The two share files to stream from, when recovery can be streamed
+///
+/// Only `--raw` output of plain shares, read from files, with no option that
+/// needs the whole secret: anything else is recovered in memory as usual.
+/// Files that look like a grid or an envelope (labeled, signed, protected,
+/// chunked, padded, v1) are left to that path too; all of their magics start
+/// with `XP`, which a plain share only does by chance, and then it simply
+/// isn't streamed.
+fn streamable(args: &RecoverArgs, sources: [&ShareArg; 2]) -> CliResult<Option<[PathBuf; 2]>> {
+    let to_terminal = matches!(args.out, None | Some(IoTarget::Std)) && io::stdout().is_terminal();
+    if !args.raw
+        || to_terminal
+        || args.no_crc
+        || args.crc_le
+        || args.force
+        || args.require_signed
+        || args.signer.is_some()
+        || !args.passphrase_file.is_empty()
+    {
+        return Ok(None);
+    }
+    let [
+        ShareArg::Input(IoTarget::Path(path1)),
+        ShareArg::Input(IoTarget::Path(path2)),
+    ] = sources
+    else {
+        return Ok(None);
+    };
+    for path in [path1, path2] {
+        if !looks_plain(path)? {
+            return Ok(None);
+        }
+    }
+    Ok(Some([path1.clone(), path2.clone()]))
+}
+
+/// Whether a share file starts like a plain base64 share
+fn looks_plain(path: &Path) -> CliResult<bool> {
+    let mut head = Vec::with_capacity(64);
+    open_share_file(path)?.take(64).read_to_end(&mut head)?;
+    if is_grid(&head) {
+        return Ok(false);
+    }
+    let start: Vec<u8> = head
+        .into_iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .take(4)
+        .collect();
+    Ok(BASE64
+        .decode(start)
+        .is_ok_and(|bytes| bytes.len() == 3 && !bytes.starts_with(b"XP")))
+}
+
+/// `recover --raw` from two plain share files, streamed to the output
+fn stream_recover(paths: &[PathBuf; 2], args: &RecoverArgs) -> CliResult {
+    let open = |index: usize| -> CliResult<_> {
+        let file = open_share_file(&paths[index])?;
+        Ok(DecoderReader::new(SkipWhitespace(file), &BASE64))
+    };
+
+    if args.verify_first {
+        recover_stream(open(0)?, open(1)?, io::sink(), Some(cancel_flag()))
+            .map_err(|e| format!("Failed to recover secret: {e}"))?;
+    }
+    let mut out = open_output(args.out.as_ref())?;
+    let result = recover_stream(open(0)?, open(1)?, &mut out, Some(cancel_flag()));
+    drop(out);
+
+    let error: Box<dyn std::error::Error> = match result {
+        Ok(_) => return Ok(ExitCode::SUCCESS),
+        // The reader went away; main treats that as a clean exit
+        Err(StreamError::Io(e)) if is_broken_pipe(&e) => return Err(e.into()),
+        Err(e) => format!("Failed to recover secret: {e}").into(),
+    };
+    Err(match &args.out {
+        // Never a device such as /dev/null, only a file we wrote
+        Some(IoTarget::Path(path)) if fs::metadata(path).is_ok_and(|m| m.is_file()) => {
+            // Best effort: whatever was written is unverified
+            let _ = fs::remove_file(path);
+            format!("{error}; removed the unverified {}", path.display()).into()
+        }
+        _ => {
+            format!("{error}; the output written so far is unverified and must be discarded").into()
+        }
+    })
+}
+
+fn open_share_file(path: &Path) -> CliResult<BufReader<File>> {
+    let file = File::open(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+/// A base64 share file without its line breaks, which the decoder won't skip
+struct SkipWhitespace<R>(R);
+
+impl<R: BufRead> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let available = self.0.fill_buf()?;
+            if available.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            let mut written = 0;
+            let mut consumed = 0;
+            for &byte in available {
+                if written == buf.len() {
+                    break;
+                }
+                consumed += 1;
+                if !byte.is_ascii_whitespace() {
+                    buf[written] = byte;
+                    written += 1;
+                }
+            }
+            self.0.consume(consumed);
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_whitespace() {
+        let mut text = String::new();
+        let mut reader = SkipWhitespace(BufReader::with_capacity(3, &b"Zi Tj\n\nk3\r\n"[..]));
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "ZiTjk3");
+    }
+}
@@ -0,0 +1,119 @@
+//! `xplit rotate`: re-randomize a pair of shares
+//!
+//! Custodians can rotate shares periodically without anyone reconstructing the
+//! secret: both shares get the same fresh pad XOR'd in (see
+//! [`xplit::refresh_shares`]). By default the old and new pairs are each
+//! recovered in memory and compared before anything is written. Both new
+//! shares are written in full next to their destinations before either is
+//! renamed into place, so a full disk doesn't leave a mismatched pair behind.
+
+use clap::Args;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use xplit::{recover_secret, refresh_shares};
+use zeroize::Zeroizing;
+
+use super::CliResult;
+use super::combined::share_kind;
+use super::io::{IoTarget, ensure_single_stdin};
+use super::shares::{ShareArg, path_with_suffix};
+use super::split::write_share_file;
+
+#[derive(Args)]
+pub struct RotateArgs {
+    /// Current first share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in1: IoTarget,
+    /// Current second share file, or `-` for stdin
+    #[arg(long, value_name = "PATH|-")]
+    in2: IoTarget,
+    /// Where to write the new first share
+    #[arg(long, value_name = "PATH")]
+    out1: PathBuf,
+    /// Where to write the new second share
+    #[arg(long, value_name = "PATH")]
+    out2: PathBuf,
+    /// Overwrite existing output files, including the input shares themselves
+    #[arg(long)]
+    force: bool,
+    /// Skip recovering the old and new pairs to check they hold the same secret
+    ///
+    /// The check is the only step that computes the secret, in memory; without
+    /// it the rotation never does.
+    #[arg(long)]
+    no_verify: bool,
+}
+
+pub fn run(args: &RotateArgs) -> CliResult {
+    ensure_single_stdin([&args.in1, &args.in2])?;
+    if args.out1 == args.out2 {
+        return Err("--out1 and --out2 must be different files".into());
+    }
+    if !args.force
+        && let Some(existing) = [&args.out1, &args.out2].into_iter().find(|p| p.exists())
+    {
+        return Err(format!(
+            "{} already exists; use --force to overwrite it",
+            existing.display()
+        )
+        .into());
+    }
+
+    let share1 = read_plain_share(&args.in1, 1)?;
+    let share2 = read_plain_share(&args.in2, 2)?;
+    let rotated =
+        refresh_shares(&share1, &share2).map_err(|e| format!("Failed to rotate shares: {e}"))?;
+
+    if !args.no_verify {
+        let before = Zeroizing::new(recover_secret(&share1, &share2)?);
+        let after = Zeroizing::new(recover_secret(&rotated.share1, &rotated.share2)?);
+        if before != after {
+            return Err("Rotated shares don't recover the original secret; nothing written".into());
+        }
+    }
+
+    let staged = [&args.out1, &args.out2].map(|path| path_with_suffix(path, ".tmp"));
+    let written = write_share_file(&staged[0], &rotated.share1)
+        .and_then(|()| write_share_file(&staged[1], &rotated.share2))
+        .and_then(|()| rename(&staged[0], &args.out1))
+        .and_then(|()| rename(&staged[1], &args.out2));
+    if let Err(e) = written {
+        for path in &staged {
+            // Best effort: a staged file may not exist at all
+            let _ = fs::remove_file(path);
+        }
+        return Err(e);
+    }
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "Share 1 written to {}", args.out1.display())?;
+    writeln!(out, "Share 2 written to {}", args.out2.display())?;
+    if args.no_verify {
+        writeln!(out, "Rotated without verification.")?;
+    } else {
+        writeln!(out, "Verified: the new shares recover the same secret.")?;
+    }
+    writeln!(
+        out,
+        "The old shares still recover the secret together; destroy every copy of them."
+    )?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Read a share, refusing protected, signed, chunked and padded ones, whose
+/// envelopes rotating would silently strip
+fn read_plain_share(input: &IoTarget, index: u8) -> CliResult<Vec<u8>> {
+    let share = ShareArg::Input(input.clone()).decode(index)?;
+    match share_kind(&share) {
+        Some("plain") | None => Ok(share),
+        Some(kind) => {
+            Err(format!("share{index} is a {kind} share; only plain shares can be rotated").into())
+        }
+    }
+}
+
+fn rename(from: &Path, to: &Path) -> CliResult<()> {
+    fs::rename(from, to).map_err(|e| format!("Failed to write {}: {e}", to.display()).into())
+}
@@ -0,0 +1,180 @@
+//! `split --secret-cmd`: read the secret from another program's stdout
+//!
+//! The secret never passes through a shell pipeline or a temp file. The child's
+//! stdout is read into a buffer allocated up front at its maximum size, so it is
+//! never reallocated and the single copy is wiped on drop.
+
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use super::CliResult;
+
+/// Largest secret accepted from a command
+pub const MAX_OUTPUT: usize = 1024 * 1024;
+
+/// How much of the child's stderr is kept for error messages
+const MAX_STDERR: u64 = 64 * 1024;
+
+/// How a `--secret-cmd` is run
+pub struct SecretCommand<'a> {
+    /// Command line, split shell-style into arguments unless `shell` is set
+    pub command: &'a str,
+    /// Run through `sh -c` (`cmd /C` on Windows) instead
+    pub shell: bool,
+    /// Kill the child and fail after this long
+    pub timeout: Duration,
+    /// Keep a trailing newline instead of trimming exactly one
+    pub keep_newline: bool,
+}
+
+impl SecretCommand<'_> {
+    /// Run the command and capture its stdout as the secret
+    pub fn run(&self) -> CliResult<Zeroizing<Vec<u8>>> {
+        let mut child = self
+            .command()?
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run --secret-cmd: {e}"))?;
+
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let stdout_reader = thread::spawn(move || -> std::io::Result<Zeroizing<Vec<u8>>> {
+            let mut buffer = Zeroizing::new(Vec::with_capacity(MAX_OUTPUT + 1));
+            (&mut stdout)
+                .take(MAX_OUTPUT as u64 + 1)
+                .read_to_end(&mut buffer)?;
+            Ok(buffer)
+        });
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let stderr_reader = thread::spawn(move || {
+            let mut buffer = Vec::new();
+            // Best effort: stderr only feeds the error message
+            let _ = (&mut stderr).take(MAX_STDERR).read_to_end(&mut buffer);
+            String::from_utf8_lossy(&buffer).trim().to_owned()
+        });
+
+        let status = wait_with_timeout(&mut child, self.timeout)?;
+        let mut secret = stdout_reader
+            .join()
+            .map_err(|_| "--secret-cmd output reader panicked")?
+            .map_err(|e| format!("Failed to read --secret-cmd output: {e}"))?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+
+        if !status.success() {
+            let reason = status.code().map_or_else(
+                || "was killed by a signal".to_owned(),
+                |code| format!("exited with code {code}"),
+            );
+            return Err(if stderr.is_empty() {
+                format!("--secret-cmd {reason}").into()
+            } else {
+                format!("--secret-cmd {reason}: {stderr}").into()
+            });
+        }
+        if secret.len() > MAX_OUTPUT {
+            return Err(format!("--secret-cmd output exceeds {MAX_OUTPUT} bytes").into());
+        }
+
+        if !self.keep_newline {
+            trim_one_newline(&mut secret);
+        }
+        Ok(secret)
+    }
+
+    fn command(&self) -> CliResult<Command> {
+        if self.shell {
+            let (shell, flag) = if cfg!(windows) {
+                ("cmd", "/C")
+            } else {
+                ("sh", "-c")
+            };
+            let mut command = Command::new(shell);
+            command.args([flag, self.command]);
+            return Ok(command);
+        }
+
+        let argv = shlex::split(self.command).ok_or("--secret-cmd has unbalanced quotes")?;
+        let (program, rest) = argv.split_first().ok_or("--secret-cmd is empty")?;
+        let mut command = Command::new(program);
+        command.args(rest);
+        Ok(command)
+    }
+}
+
+/// Wait for `child`, killing it once `timeout` has passed
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> CliResult<std::process::ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            // Already exiting if kill fails; reap it either way
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("--secret-cmd timed out after {timeout:?}").into());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Remove one trailing `\n` (or `\r\n`)
+fn trim_one_newline(secret: &mut Vec<u8>) {
+    if secret.last() == Some(&b'\n') {
+        secret.pop();
+        if secret.last() == Some(&b'\r') {
+            secret.pop();
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn run(command: &str, shell: bool) -> CliResult<Zeroizing<Vec<u8>>> {
+        SecretCommand {
+            command,
+            shell,
+            timeout: Duration::from_secs(5),
+            keep_newline: false,
+        }
+        .run()
+    }
+
+    #[test]
+    fn test_captures_stdout_and_trims_one_newline() {
+        assert_eq!(
+            run("printf 'hunter2\\n\\n'", false).unwrap().as_slice(),
+            b"hunter2\n"
+        );
+        assert_eq!(
+            run("printf secret | cat", true).unwrap().as_slice(),
+            b"secret"
+        );
+    }
+
+    #[test]
+    fn test_failure_reports_stderr_and_code() {
+        let err = run("echo nope >&2; exit 3", true).unwrap_err().to_string();
+        assert!(err.contains("code 3"), "{err}");
+        assert!(err.contains("nope"), "{err}");
+    }
+
+    #[test]
+    fn test_timeout_kills_child() {
+        let err = SecretCommand {
+            command: "sleep 10",
+            shell: false,
+            timeout: Duration::from_millis(100),
+            keep_newline: false,
+        }
+        .run()
+        .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
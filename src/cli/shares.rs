@@ -0,0 +1,377 @@
+//! Reading shares from the command line and naming share files
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use xplit::chunked::{ChunkReport, check_chunks, is_chunked, recover_chunked};
+use xplit::grid::{is_grid, parse_grid};
+use xplit::label::{is_labeled, open_labeled};
+use xplit::pad::{is_padded, open_padded, recover_padded};
+use xplit::protect::{is_protected, unprotect_share};
+use xplit::sign::{VerifyingKey, is_signed, open_signed_share};
+use xplit::{ShareError, is_valid_share, recover_secret, recover_secret_raw, recovered_len};
+
+use super::CliResult;
+use super::io::IoTarget;
+use super::keychain;
+use super::passphrase::read_passphrase_file;
+
+/// Path of the share file for `index` under the `--output` prefix
+pub fn share_path(prefix: &Path, index: u8) -> PathBuf {
+    path_with_suffix(prefix, &format!(".share{index}"))
+}
+
+/// Append `suffix` to the final component of `path`
+pub fn path_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// A share given on the command line
+///
+/// Plain arguments are always literal shares. `@PATH` reads the share from a
+/// file and `@-` from stdin; `@@` escapes a literal leading `@`. `keychain:NAME`
+/// fetches a share stored with `split --store-share1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShareArg {
+    Literal(String),
+    Input(IoTarget),
+    Keychain(String),
+}
+
+impl FromStr for ShareArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rest) = s.strip_prefix("@@") {
+            return Ok(Self::Literal(format!("@{rest}")));
+        }
+        if let Some(path) = s.strip_prefix('@') {
+            if path.is_empty() {
+                return Err("'@' must be followed by a path, or '-' for stdin".to_owned());
+            }
+            return Ok(Self::Input(path.parse().unwrap_or_else(|e| match e {})));
+        }
+        if let Some(name) = s.strip_prefix(keychain::SCHEME) {
+            return Ok(Self::Keychain(name.to_owned()));
+        }
+        Ok(Self::Literal(s.to_owned()))
+    }
+}
+
+impl ShareArg {
+    /// Pick the positional argument or its `--in` flag (clap guarantees at most one)
+    pub fn pick(positional: Option<&Self>, flag: Option<&IoTarget>) -> CliResult<Self> {
+        match (positional, flag) {
+            (Some(arg), None) => Ok(arg.clone()),
+            (None, Some(target)) => Ok(Self::Input(target.clone())),
+            (Some(_), Some(_)) => Err("A share was given both positionally and with --in".into()),
+            (None, None) => Err("Missing share argument".into()),
+        }
+    }
+
+    /// The file or stdin this share is read from, if any
+    pub fn input(&self) -> Option<&IoTarget> {
+        match self {
+            Self::Input(target) => Some(target),
+            Self::Literal(_) | Self::Keychain(_) => None,
+        }
+    }
+
+    /// Read the share exactly as given, before any decoding
+    pub fn read_raw(&self) -> CliResult<Vec<u8>> {
+        match self {
+            Self::Literal(text) => Ok(text.clone().into_bytes()),
+            Self::Input(target) => target.read_all(),
+            Self::Keychain(name) => keychain::fetch(name).map(String::into_bytes),
+        }
+    }
+
+    fn read_text(&self, index: u8) -> CliResult<String> {
+        String::from_utf8(self.read_raw()?)
+            .map_err(|_| format!("share{index} input is not text").into())
+    }
+
+    /// Read and base64 decode the share
+    ///
+    /// A share copied out as a grid (`split --grid`) is read back as well.
+    pub fn decode(&self, index: u8) -> CliResult<Vec<u8>> {
+        let decode = |encoded: &[u8]| {
+            if is_grid(encoded) {
+                let text = std::str::from_utf8(encoded)
+                    .map_err(|_| format!("share{index} grid is not text"))?;
+                return parse_grid(text)
+                    .map_err(|e| format!("Failed to read share{index} grid: {e}"));
+            }
+            BASE64
+                .decode(encoded.trim_ascii())
+                .map_err(|e| format!("Failed to decode share{index} from base64: {e}"))
+        };
+        Ok(decode(self.read_text(index)?.as_bytes())?)
+    }
+}
+
+/// Split both shares pasted as one argument, `SHARE1:SHARE2` or separated by
+/// whitespace
+///
+/// Neither character occurs in base64, so the split is unambiguous.
+pub fn split_joined(joined: &str) -> CliResult<[ShareArg; 2]> {
+    let parts: Vec<&str> = joined
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [share1, share2] => {
+            Ok([share1, share2].map(|share| ShareArg::Literal((*share).to_owned())))
+        }
+        [_] => Err("Missing share2: give two shares, or both joined as SHARE1:SHARE2".into()),
+        _ => Err(format!(
+            "Expected 2 shares joined by ':' or whitespace, found {} parts",
+            parts.len()
+        )
+        .into()),
+    }
+}
+
+/// A share read from the command line, with its envelopes removed
+pub struct LoadedShare {
+    pub bytes: Vec<u8>,
+    pub signer: Option<VerifyingKey>,
+    /// The share's label, e.g. a custodian role from `split --roles`
+    pub label: Option<String>,
+}
+
+/// Read a share given on the command line, checking its signature and
+/// unwrapping it if it is protected
+pub fn load_share(
+    source: &ShareArg,
+    index: u8,
+    passphrase_file: Option<&PathBuf>,
+    expected_signer: Option<&VerifyingKey>,
+) -> CliResult<LoadedShare> {
+    let mut share = source.decode(index)?;
+    // A label only names the share; nothing below needs it
+    let mut label = None;
+    if is_labeled(&share) {
+        let (text, inner) =
+            open_labeled(&share).map_err(|e| format!("Failed to read share{index}: {e}"))?;
+        label = Some(text.to_owned());
+        share = inner.to_vec();
+    }
+
+    let mut signer = None;
+    if is_signed(&share) {
+        let (inner, key) = open_signed_share(&share, expected_signer)
+            .map_err(|e| format!("Failed to verify share{index}: {e}"))?;
+        share = inner;
+        signer = Some(key);
+    }
+
+    if is_protected(&share) {
+        let passphrase = match passphrase_file {
+            Some(path) => read_passphrase_file(path)?,
+            None => rpassword::prompt_password(format!("Passphrase for share {index}: "))?,
+        };
+        share = unprotect_share(&share, &passphrase)
+            .map_err(|e| format!("Failed to unlock share{index}: {e}"))?;
+    }
+
+    Ok(LoadedShare {
+        bytes: share,
+        signer,
+        label,
+    })
+}
+
+/// Recover the secret from two loaded shares, plain, chunked or padded
+///
+/// For chunked shares every damaged chunk of either share is reported, not
+/// just the first.
+pub fn recover_shares(share1: &[u8], share2: &[u8]) -> CliResult<Vec<u8>> {
+    if both_or_neither(share1, share2, is_chunked, "chunked")? {
+        check_chunked_pair(share1, share2)?;
+        Ok(recover_chunked(share1, share2)?)
+    } else if both_or_neither(share1, share2, is_padded, "padded")? {
+        Ok(recover_padded(share1, share2)?)
+    } else {
+        recover_secret(share1, share2).map_err(|e| checksum_error(share1, share2, e))
+    }
+}
+
+/// Recover the secret from two bare `split --no-crc` shares
+///
+/// A share that carries a checksum is refused rather than XOR'd into garbage.
+/// One bare share in about 4 billion happens to end in a matching CRC32 and
+/// is refused too.
+pub fn recover_raw_shares(share1: &[u8], share2: &[u8]) -> CliResult<Vec<u8>> {
+    let checksummed = |share: &[u8]| is_valid_share(share) || is_chunked(share) || is_padded(share);
+    match (checksummed(share1), checksummed(share2)) {
+        (false, false) => Ok(recover_secret_raw(share1, share2)?),
+        (true, true) => Err(
+            "Both shares carry checksums, so they weren't split with --no-crc; \
+                 recover them without --no-crc"
+                .into(),
+        ),
+        (checked1, _) => Err(format!(
+            "Only share{} carries a checksum, so the shares weren't split together \
+             (one was split with --no-crc and one without)",
+            if checked1 { 1 } else { 2 }
+        )
+        .into()),
+    }
+}
+
+/// Explain a checksum failure caused by pairing a bare `--no-crc` share with a
+/// checksummed one, which would otherwise look like corruption
+fn checksum_error(share1: &[u8], share2: &[u8], error: ShareError) -> Box<dyn Error> {
+    let mixed = error == ShareError::InvalidChecksum
+        && is_valid_share(share1) != is_valid_share(share2)
+        && share1.len().abs_diff(share2.len()) == 4;
+    if mixed {
+        let bare = if is_valid_share(share1) { 2 } else { 1 };
+        return format!(
+            "share{bare} has no checksum; it looks like a bare --no-crc share, so the shares \
+             weren't split together"
+        )
+        .into();
+    }
+    if error == ShareError::InvalidChecksum
+        && !is_valid_share(share1)
+        && !is_valid_share(share2)
+        && share1.len() == share2.len()
+    {
+        return format!("{error} (if both were split with --no-crc, recover with --no-crc)").into();
+    }
+    error.into()
+}
+
+/// Run every check [`recover_shares`] does short of combining the shares
+///
+/// Nothing is XOR'd, so the secret is never computed. Returns a summary of
+/// what recovery would produce, plus any warnings to show first.
+pub fn check_shares(share1: &[u8], share2: &[u8]) -> CliResult<(String, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let summary = if both_or_neither(share1, share2, is_chunked, "chunked")? {
+        let len = check_chunked_pair(share1, share2)?;
+        format!("{len} bytes")
+    } else if both_or_neither(share1, share2, is_padded, "padded")? {
+        let (padding1, inner1) = open_padded(share1)?;
+        let (padding2, inner2) = open_padded(share2)?;
+        if padding1 != padding2 {
+            return Err(ShareError::InvalidPadding.into());
+        }
+        let len = recovered_len(inner1, inner2)?;
+        warnings.push(
+            "shares are padded; the padding itself can only be checked by recovering".to_owned(),
+        );
+        format!(
+            "at most {} bytes (padded to {len}, {padding1})",
+            len.saturating_sub(1)
+        )
+    } else {
+        let len = recovered_len(share1, share2)?;
+        let (len1, len2) = (share1.len() - 4, share2.len() - 4);
+        if len1 != len2 {
+            warnings.push(format!(
+                "shares have different lengths ({len1} and {len2} bytes); recovery uses the \
+                 shorter, so they may not belong together"
+            ));
+        }
+        format!("{len} bytes")
+    };
+    Ok((summary, warnings))
+}
+
+/// Whether both shares pass `check`, or an error if only one does
+fn both_or_neither(
+    share1: &[u8],
+    share2: &[u8],
+    check: fn(&[u8]) -> bool,
+    kind: &str,
+) -> CliResult<bool> {
+    match (check(share1), check(share2)) {
+        (true, true) => Ok(true),
+        (false, false) => Ok(false),
+        _ => Err(format!("Only one share is {kind}, so they weren't split together").into()),
+    }
+}
+
+/// Check every chunk of both chunked shares and that their layouts match,
+/// returning the payload length
+fn check_chunked_pair(share1: &[u8], share2: &[u8]) -> CliResult<usize> {
+    let mut reports = Vec::with_capacity(2);
+    for (index, share) in [(1, share1), (2, share2)] {
+        let report = check_chunks(share)?;
+        if !report.bad.is_empty() {
+            return Err(format!(
+                "share{index} is corrupted: {}",
+                describe_bad_chunks(&report)
+            )
+            .into());
+        }
+        reports.push(report);
+    }
+    if (reports[0].chunk_size, reports[0].payload_len)
+        != (reports[1].chunk_size, reports[1].payload_len)
+    {
+        return Err(ShareError::LengthMismatch.into());
+    }
+    Ok(reports[0].payload_len)
+}
+
+/// Damaged chunks as `chunk 3 (bytes 131072..196608)`, comma separated
+pub fn describe_bad_chunks(report: &ChunkReport) -> String {
+    report
+        .bad
+        .iter()
+        .map(|range| {
+            format!(
+                "chunk {} (bytes {}..{}) failed its checksum",
+                range.start / report.chunk_size + 1,
+                range.start,
+                range.end
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_arg_syntax() {
+        let parse = |s: &str| s.parse::<ShareArg>().unwrap();
+
+        assert_eq!(parse("ZiTj"), ShareArg::Literal("ZiTj".to_owned()));
+        assert_eq!(
+            parse("share1.txt"),
+            ShareArg::Literal("share1.txt".to_owned())
+        );
+        assert_eq!(
+            parse("@share1.txt"),
+            ShareArg::Input(IoTarget::Path(PathBuf::from("share1.txt")))
+        );
+        assert_eq!(parse("@-"), ShareArg::Input(IoTarget::Std));
+        assert_eq!(parse("@@abc"), ShareArg::Literal("@abc".to_owned()));
+        assert_eq!(parse("keychain:v"), ShareArg::Keychain("v".to_owned()));
+        assert!("@".parse::<ShareArg>().is_err());
+    }
+
+    #[test]
+    fn test_split_joined() {
+        let literal = |s: &str| ShareArg::Literal(s.to_owned());
+        for joined in ["abc=:def=", "abc=  def=", " abc=\ndef=\n"] {
+            assert_eq!(
+                split_joined(joined).unwrap(),
+                [literal("abc="), literal("def=")]
+            );
+        }
+        assert!(split_joined("abc=").is_err());
+        let error = split_joined("a:b:c").unwrap_err().to_string();
+        assert!(error.contains("found 3 parts"), "{error}");
+    }
+}
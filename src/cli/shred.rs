@@ -0,0 +1,112 @@
+//! `split --shred`: destroy the plaintext input once its shares are safe
+//!
+//! This is best effort. Overwriting a file in place does not reach copies kept
+//! by SSD wear leveling, copy-on-write or journaling filesystems, snapshots or
+//! backups; full-disk encryption is the only real protection there.
+
+use rand::RngCore;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use super::CliResult;
+use super::io::IoTarget;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Check up front that `--in` names a file that can be shredded
+///
+/// Symlinks are rejected rather than followed, so only the file that was
+/// actually split is ever destroyed.
+pub fn check_target(input: Option<&IoTarget>) -> CliResult<PathBuf> {
+    let Some(IoTarget::Path(path)) = input else {
+        return Err("--shred requires the secret to be read from a file with --in PATH".into());
+    };
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if metadata.file_type().is_symlink() {
+        return Err(format!("Refusing to shred symlink {}", path.display()).into());
+    }
+    if !metadata.is_file() {
+        return Err(format!("Refusing to shred {}: not a regular file", path.display()).into());
+    }
+    Ok(path.clone())
+}
+
+/// Ask on the terminal before shredding an input whose shares were only printed
+pub fn confirm(path: &Path) -> CliResult<()> {
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        return Err(format!(
+            "Not shredding {}: the shares were only printed; write them with --output, or \
+             run interactively to confirm",
+            path.display()
+        )
+        .into());
+    }
+    eprint!(
+        "The shares were only printed, not saved. Shred {} anyway? [y/N] ",
+        path.display()
+    );
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        return Err(format!("Not shredding {}", path.display()).into());
+    }
+    Ok(())
+}
+
+/// Overwrite `path` with random data, truncate it and unlink it
+pub fn shred(path: &Path) -> CliResult<()> {
+    // Checked again in case the path was swapped since `check_target`
+    check_target(Some(&IoTarget::Path(path.to_owned())))?;
+    let fail = |e: io::Error| format!("Failed to shred {}: {e}", path.display());
+
+    let mut file = OpenOptions::new().write(true).open(path).map_err(fail)?;
+    let mut remaining = file.metadata().map_err(fail)?.len();
+    let mut rng = rand::thread_rng();
+    let mut noise = vec![0u8; CHUNK_SIZE];
+    while remaining > 0 {
+        let n = usize::try_from(remaining).map_or(CHUNK_SIZE, |r| r.min(CHUNK_SIZE));
+        rng.fill_bytes(&mut noise[..n]);
+        file.write_all(&noise[..n]).map_err(fail)?;
+        remaining -= n as u64;
+    }
+    file.sync_all().map_err(fail)?;
+    file.set_len(0).map_err(fail)?;
+    file.sync_all().map_err(fail)?;
+    drop(file);
+
+    fs::remove_file(path).map_err(fail)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shred_removes_file_and_rejects_symlinks() {
+        let dir = std::env::temp_dir().join(format!("xplit-shred-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("key.bin");
+        fs::write(&path, vec![7u8; CHUNK_SIZE + 3]).unwrap();
+
+        #[cfg(unix)]
+        {
+            let link = dir.join("link");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            let err = check_target(Some(&IoTarget::Path(link))).unwrap_err();
+            assert!(err.to_string().contains("symlink"));
+        }
+        assert!(check_target(Some(&IoTarget::Std)).is_err());
+        assert!(check_target(Some(&IoTarget::Path(dir.clone()))).is_err());
+
+        let target = check_target(Some(&IoTarget::Path(path.clone()))).unwrap();
+        shred(&target).unwrap();
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1040 @@
+//! `xplit split`
+
+use base64::write::EncoderWriter;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use clap::{ArgGroup, Args};
+use rand::RngCore;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime};
+use xplit::chunked::{MAX_CHUNK_SIZE, MIN_CHUNK_SIZE, chunked_len, split_chunked};
+use xplit::grid::format_grid;
+use xplit::label::{label_share, labeled_len};
+use xplit::pad::{MAX_PAD_BLOCK, Padding, padded_share_len, split_padded};
+use xplit::protect::{protect_share, protected_len};
+use xplit::sign::{SigningKey, sign_share, signed_len};
+use xplit::stream::split_stream;
+use xplit::unicode::Normalization;
+use xplit::{
+    ShareError, TwoShares, is_valid_share, recover_secret_raw, seal, shannon_entropy,
+    share_fingerprint, share_len, split_secret, split_secret_raw,
+};
+use zeroize::Zeroizing;
+
+use super::format::OutputFormat;
+use super::holder::{self, SheetVars};
+use super::io::{IoTarget, create_private_file, open_output};
+use super::keychain::{self, KeychainRef};
+use super::keys::read_signing_key;
+use super::passphrase::prompt_new_passphrase;
+use super::secret_cmd::SecretCommand;
+use super::shares::{path_with_suffix, recover_shares, share_path};
+use super::shred;
+use super::template::{NameTemplate, NameVars, Variable, utc_date, validate_label};
+use super::{CliResult, cancel_flag};
+
+/// Extension for base64 share files
+const SHARE_EXT: &str = "b64";
+
+/// Placeholders accepted by `split --format`
+const FORMAT_PLACEHOLDERS: &[&str] = &["share1", "share2", "fp1", "fp2", "id", "len", "label"];
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(group(ArgGroup::new("files").args(["output", "name_template", "drive1"])))]
+pub struct SplitArgs {
+    /// Secret to split (if neither this nor --in is given, reads from stdin)
+    #[arg(conflicts_with_all = ["input", "secret_cmd"])]
+    secret: Option<String>,
+    /// Read the secret from a file, or `-` for stdin
+    #[arg(long = "in", value_name = "PATH|-", conflicts_with = "secret_cmd")]
+    input: Option<IoTarget>,
+    /// Read the secret from this command's stdout, e.g. `pass show prod/db`
+    ///
+    /// The command is split into arguments shell-style but not run through a
+    /// shell unless --shell is given. One trailing newline is trimmed.
+    #[arg(long, value_name = "COMMAND")]
+    secret_cmd: Option<String>,
+    /// Run --secret-cmd through `sh -c`
+    #[arg(long, requires = "secret_cmd")]
+    shell: bool,
+    /// Keep the trailing newline of --secret-cmd output
+    #[arg(long, requires = "secret_cmd")]
+    keep_newline: bool,
+    /// Kill --secret-cmd if it hasn't finished after this many seconds
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 30,
+        requires = "secret_cmd"
+    )]
+    secret_cmd_timeout: u64,
+    /// Write the shares to a file, or `-` for stdout (the default)
+    #[arg(long, value_name = "PATH|-", conflicts_with = "files")]
+    out: Option<IoTarget>,
+    /// Write shares to `<OUTPUT>.share1` and `<OUTPUT>.share2` instead of printing them
+    #[arg(short, long, value_name = "PREFIX")]
+    output: Option<PathBuf>,
+    /// Name share files from a template, e.g. `{label}-{date}-{index}.{ext}`
+    ///
+    /// Variables: {label} (from --label), {id} (pairing id shared by both files),
+    /// {index} (1 or 2), {date} (UTC, YYYY-MM-DD) and {ext} (b64).
+    #[arg(long, value_name = "TEMPLATE")]
+    name_template: Option<NameTemplate>,
+    /// Write share 1 to this directory, e.g. a mounted USB drive (needs --drive2)
+    ///
+    /// Each share file (`<LABEL>.share1` and `<LABEL>.share2`, or `xplit.shareN`
+    /// without --label) is synced and read back, and the split only succeeds if
+    /// both round-trip; otherwise whatever was written is removed from both drives.
+    #[arg(
+        long,
+        value_name = "DIR",
+        requires = "drive2",
+        conflicts_with = "store_share1"
+    )]
+    drive1: Option<PathBuf>,
+    /// Write share 2 to this directory (needs --drive1)
+    #[arg(long, value_name = "DIR", requires = "drive1")]
+    drive2: Option<PathBuf>,
+    /// Also write a non-secret recovery hint to PREFIX.hint, e.g. where each
+    /// share is kept
+    ///
+    /// The hint is stored in the clear with a checksum and isn't needed to
+    /// recover; read it with `xplit hint PREFIX.hint`.
+    #[arg(
+        long,
+        value_name = "TEXT",
+        requires = "output",
+        value_parser = clap::builder::NonEmptyStringValueParser::new()
+    )]
+    hint: Option<String>,
+    /// Label substituted for {label} in --name-template and --format, and used
+    /// to name the share files on --drive1 and --drive2
+    #[arg(long)]
+    label: Option<String>,
+    /// Store a custodian role in each share, e.g. `CFO,CTO` for shares 1 and 2
+    ///
+    /// The role is kept in the share as its label, in the clear: `verify`
+    /// shows it and `recover` skips over it.
+    #[arg(
+        long,
+        value_name = "ROLE1,ROLE2",
+        value_delimiter = ',',
+        value_parser = parse_role,
+        conflicts_with = "no_crc"
+    )]
+    roles: Vec<String>,
+    /// Print the shares using a template instead of `Share N: ...` lines
+    ///
+    /// Placeholders: `{share1}` `{share2}` (base64), `{fp1}` `{fp2}` (share
+    /// fingerprints), `{id}` (pairing id), `{len}` (secret length in bytes) and
+    /// `{label}`. Use `\n` and `\t` for newlines and tabs, and `{{` `}}` for literal
+    /// braces. No trailing newline is added.
+    #[arg(long, value_name = "FORMAT", conflicts_with = "files")]
+    format: Option<String>,
+    /// Print each share as a grid of base32 for copying onto paper
+    ///
+    /// Rows are numbered and end in a check character, so `xplit recover`
+    /// reports a copying mistake by row when the grid is read back from a file
+    /// or stdin.
+    #[arg(long, conflicts_with_all = ["files", "format"])]
+    grid: bool,
+    /// Unicode-normalize the secret before splitting (nfc, nfd, nfkc or nfkd)
+    ///
+    /// Recovery returns the normalized form, not the bytes that were typed; use
+    /// nfc for passphrases that may be re-typed on another system.
+    #[arg(long, value_name = "FORM")]
+    normalize: Option<Normalization>,
+    /// Encrypt each share file with its own passphrase (prompted separately)
+    #[arg(long, requires = "files")]
+    protect: bool,
+    /// Store share 1 in the OS keychain as `keychain:NAME` instead of a file or stdout
+    ///
+    /// Requires the `keyring` feature. Recover with `xplit recover keychain:NAME ...`.
+    #[arg(long, value_name = "keychain:NAME", conflicts_with = "format")]
+    store_share1: Option<KeychainRef>,
+    /// Store both shares in the OS keychain as `NAME.share1` and `NAME.share2`
+    ///
+    /// Requires the `keyring` feature. Recover with `xplit recover --keyring NAME`.
+    /// To keep only one share in the keychain, use --store-share1 instead.
+    #[arg(
+        long,
+        value_name = "NAME",
+        value_parser = keychain::parse_name,
+        conflicts_with_all = ["out", "files", "format", "store_share1"]
+    )]
+    keyring: Option<String>,
+    /// Name of a share holder; give it twice to also write an instruction sheet
+    /// for each holder next to their share file
+    ///
+    /// A sheet holds only its holder's share, its fingerprint, the label, the
+    /// other holder's name and the commands to verify and recover.
+    #[arg(
+        long,
+        value_name = "NAME",
+        value_parser = holder::parse_holder,
+        requires = "files",
+        conflicts_with = "store_share1"
+    )]
+    holder: Vec<String>,
+    /// Use this file instead of the built-in instruction sheet for --holder
+    ///
+    /// Placeholders: `{holder}`, `{other}` (the other holder's name), `{index}`,
+    /// `{share}` (base64), `{fp}`, `{label}`, `{id}`, `{len}` and `{file}` (the
+    /// share file). Escapes and literal braces work as in --format.
+    #[arg(long, value_name = "PATH", requires = "holder")]
+    holder_template: Option<PathBuf>,
+    /// Sign each share with the private key in this file (see `xplit keygen`)
+    #[arg(long, value_name = "PATH")]
+    sign_key: Option<PathBuf>,
+    /// Checksum every SIZE bytes of each share separately, e.g. `64KiB`
+    ///
+    /// `verify` and `recover` then name the damaged chunks and their byte ranges
+    /// instead of rejecting the whole share. Accepts a byte count with an optional
+    /// K/KiB, M/MiB or G/GiB suffix, from 64 bytes to 1 GiB; a secret no larger
+    /// than SIZE is a single chunk.
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_chunk_size,
+        conflicts_with_all = ["protect", "stats"]
+    )]
+    chunk_size: Option<usize>,
+    /// Pad the secret to a multiple of BYTES before splitting, so the shares
+    /// don't reveal its exact length (1 byte to 1 MiB)
+    ///
+    /// Recovery strips the padding. This hides the exact length, not the size
+    /// class: a 4 KiB key file still looks nothing like a 12-character password.
+    /// At least one byte is always added.
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = parse_pad_block,
+        conflicts_with_all = ["chunk_size", "protect", "stats"]
+    )]
+    pad: Option<usize>,
+    /// Pad the secret to the next power of two before splitting (see --pad)
+    #[arg(long, conflicts_with_all = ["pad", "chunk_size", "protect", "stats"])]
+    pad_pow2: bool,
+    /// Emit bare shares with no checksum, for systems that already authenticate
+    /// what they store
+    ///
+    /// Each share is exactly as long as the secret, and nothing can tell a
+    /// corrupted share from a good one. Recover with `recover --no-crc`.
+    #[arg(long)]
+    no_crc: bool,
+    /// Print timing, throughput and random-share byte distribution to stderr
+    #[arg(long)]
+    stats: bool,
+    /// Destroy the --in file once both shares are written and verified
+    ///
+    /// Best effort: the file is overwritten with random data, truncated and
+    /// unlinked, which can't reach copies kept by SSDs, copy-on-write filesystems
+    /// or backups. Asks for confirmation if the shares were only printed.
+    #[arg(long, requires = "input")]
+    shred: bool,
+    /// Report the share sizes a real run would produce, without generating
+    /// randomness or writing anything
+    #[arg(long, conflicts_with_all = ["shred", "stats"])]
+    dry_run: bool,
+}
+
+pub fn run(mut args: SplitArgs) -> CliResult {
+    let format = output_format(&args)?;
+    let id = pairing_id(args.dry_run);
+    let paths = share_files(&args, &id)?;
+    let keychain_targets = keychain_targets(&args);
+    let sheet = sheet_template(&args)?;
+    check_no_crc(&args)?;
+    check_roles(&args.roles)?;
+    if args.dry_run {
+        return dry_run(&mut args, paths.as_ref(), &keychain_targets);
+    }
+    for (_, target) in &keychain_targets {
+        keychain::ensure_absent(target)?;
+    }
+    let shred_target = args
+        .shred
+        .then(|| shred::check_target(args.input.as_ref()))
+        .transpose()?;
+    if let Some(paths) = paths.as_ref().filter(|_| can_stream(&args)) {
+        return stream_to_files(args.input.as_ref(), paths);
+    }
+    let secret_bytes = read_secret(&mut args)?;
+    if looks_like_share(&secret_bytes) {
+        eprintln!("Warning: input looks like an existing share; did you mean to recover?");
+    }
+
+    let signing_key = args.sign_key.as_deref().map(read_signing_key).transpose()?;
+    let sign = |index, share| wrap_share(signing_key.as_ref(), &args.roles, index, share);
+
+    let started = Instant::now();
+    let shares =
+        split_shares(&args, &secret_bytes).map_err(|e| format!("Failed to split secret: {e}"))?;
+    if args.stats {
+        print_stats(started.elapsed(), &shares.share2[..secret_bytes.len()]);
+    }
+    if args.shred {
+        let recovered = Zeroizing::new(recover_split(&args, &shares)?);
+        if recovered != secret_bytes {
+            return Err("Shares failed verification; nothing was written or shredded".into());
+        }
+    }
+
+    let protect = |index: u8, share: Vec<u8>| -> CliResult<Vec<u8>> {
+        if args.protect {
+            let passphrase = prompt_new_passphrase(index)?;
+            Ok(protect_share(&share, &passphrase)?)
+        } else {
+            Ok(share)
+        }
+    };
+
+    let mut out = open_output(args.out.as_ref())?;
+    // Keychain shares are stored last, so a failure writing the other share
+    // leaves no orphaned keychain entry behind
+    let (keychain_shares, pending): (Vec<_>, Vec<_>) = [(1, shares.share1), (2, shares.share2)]
+        .into_iter()
+        .partition(|(index, _)| keychain_targets.iter().any(|(i, _)| i == index));
+    let printed = paths.is_none() && !pending.is_empty();
+    let vars = FormatVars {
+        id: &id,
+        len: secret_bytes.len(),
+        label: args.label.as_deref(),
+    };
+
+    if let Some(paths) = paths {
+        let mut sealed = Vec::with_capacity(pending.len());
+        for (index, share) in pending {
+            sealed.push((index, sign(index, protect(index, share)?)?));
+        }
+        write_files(&args, &paths, &sealed, &mut out)?;
+        if let Some(template) = &sheet {
+            for (index, share) in &sealed {
+                let path = &paths[usize::from(*index) - 1];
+                write_sheet(
+                    template,
+                    &args.holder,
+                    (*index, share, path),
+                    &vars,
+                    &mut out,
+                )?;
+            }
+        }
+    } else if let Some(format) = format {
+        // --format conflicts with the keychain options, so both shares are pending
+        let signed = pending
+            .into_iter()
+            .map(|(index, share)| sign(index, share))
+            .collect::<CliResult<Vec<_>>>()?;
+        write!(out, "{}", render_format(&format, &signed, &vars))?;
+    } else {
+        for (index, share) in pending {
+            print_share(&mut out, index, &sign(index, share)?, args.grid)?;
+        }
+    }
+
+    let mut encoded = Vec::with_capacity(keychain_shares.len());
+    for ((index, share), (_, target)) in keychain_shares.into_iter().zip(&keychain_targets) {
+        let share = sign(index, protect(index, share)?)?;
+        encoded.push((index, target, BASE64.encode(share)));
+    }
+    store_in_keychain(&encoded, args.shred, &mut out)?;
+    out.flush()?;
+
+    if let Some(path) = shred_target {
+        if printed {
+            shred::confirm(&path)?;
+        }
+        shred::shred(&path)?;
+        eprintln!("Shredded {}", path.display());
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Print a share as a `Share N: ...` line, or under a `Share N:` heading as a grid
+fn print_share(out: &mut dyn Write, index: u8, share: &[u8], grid: bool) -> io::Result<()> {
+    if grid {
+        writeln!(out, "Share {index}:\n{}", format_grid(share))
+    } else {
+        writeln!(out, "Share {index}: {}", BASE64.encode(share))
+    }
+}
+
+/// Write share files, reading each back when it must be verified
+///
+/// On a drive failure the shares already written are removed from both drives,
+/// so a split to drives either fully succeeds or leaves nothing behind.
+fn write_files(
+    args: &SplitArgs,
+    paths: &[PathBuf; 2],
+    shares: &[(u8, Vec<u8>)],
+    out: &mut dyn Write,
+) -> CliResult<()> {
+    let drives = args.drive1.is_some();
+    for (done, (index, share)) in shares.iter().enumerate() {
+        let path = &paths[usize::from(*index) - 1];
+        let result = write_share_file(path, share).and_then(|()| {
+            if drives {
+                fs::File::open(path)
+                    .and_then(|file| file.sync_all())
+                    .map_err(|e| format!("Failed to sync {}: {e}", path.display()))?;
+            }
+            if drives || args.shred {
+                check_share_file(path, share)?;
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) if drives => writeln!(
+                out,
+                "Drive {index}: share written to {} and verified",
+                path.display()
+            )?,
+            Ok(()) => writeln!(out, "Share {index} written to {}", path.display())?,
+            Err(e) if drives => {
+                for (written, _) in &shares[..=done] {
+                    // Best effort: the failed file may not exist at all
+                    let _ = fs::remove_file(&paths[usize::from(*written) - 1]);
+                }
+                return Err(
+                    format!("Drive {index}: {e}; removed the shares written so far").into(),
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if drives {
+        writeln!(out, "Both drives verified")?;
+    }
+    if let (Some(hint), Some(prefix)) = (&args.hint, &args.output) {
+        let path = path_with_suffix(prefix, ".hint");
+        write_share_file(&path, &seal(hint.as_bytes()))?;
+        writeln!(out, "Hint written to {}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Split in the share format the options ask for: chunked, padded or plain
+fn split_shares(args: &SplitArgs, secret: &[u8]) -> Result<TwoShares, ShareError> {
+    if args.no_crc {
+        return split_secret_raw(secret);
+    }
+    if let Some(size) = args.chunk_size {
+        return split_chunked(secret, size);
+    }
+    match padding(args) {
+        Some(padding) => split_padded(secret, padding),
+        None => split_secret(secret),
+    }
+}
+
+/// Refuse `--no-crc` with options that rely on the share header or checksum
+/// it drops, explaining why, and warn about it otherwise
+fn check_no_crc(args: &SplitArgs) -> CliResult<()> {
+    if !args.no_crc {
+        return Ok(());
+    }
+    let conflict = [
+        (
+            args.chunk_size.is_some(),
+            "--chunk-size: chunk checksums are recorded in a share header",
+        ),
+        (
+            args.pad.is_some() || args.pad_pow2,
+            "--pad: the padding is recorded in a share header",
+        ),
+        (
+            args.protect,
+            "--protect: unlocking checks the share's checksum to catch a wrong passphrase",
+        ),
+        (
+            args.drive1.is_some(),
+            "--drive1/--drive2: each share is verified on its drive by its checksum",
+        ),
+    ]
+    .into_iter()
+    .find_map(|(set, reason)| set.then_some(reason));
+    if let Some(reason) = conflict {
+        return Err(format!("--no-crc can't be combined with {reason}").into());
+    }
+    eprintln!("Warning: --no-crc shares carry no checksum; corruption will go undetected");
+    Ok(())
+}
+
+/// Recover the secret from freshly split shares, to check them
+fn recover_split(args: &SplitArgs, shares: &TwoShares) -> CliResult<Vec<u8>> {
+    if args.no_crc {
+        Ok(recover_secret_raw(&shares.share1, &shares.share2)?)
+    } else {
+        recover_shares(&shares.share1, &shares.share2)
+    }
+}
+
+fn padding(args: &SplitArgs) -> Option<Padding> {
+    args.pad
+        .map(Padding::Block)
+        .or(args.pad_pow2.then_some(Padding::PowerOfTwo))
+}
+
+/// Store encoded shares in the keychain, removing the ones already stored if one fails
+///
+/// With `verify`, each entry is read back and compared.
+fn store_in_keychain(
+    shares: &[(u8, &KeychainRef, String)],
+    verify: bool,
+    out: &mut dyn Write,
+) -> CliResult<()> {
+    for (done, (index, target, encoded)) in shares.iter().enumerate() {
+        let result = keychain::store(target, encoded).and_then(|()| {
+            if verify && keychain::fetch(target.name())? != *encoded {
+                return Err(format!("{target} does not hold the share just stored").into());
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            for (_, stored, _) in &shares[..done] {
+                // Best effort: don't leave half a pair in the keychain
+                let _ = keychain::delete(stored);
+            }
+            return Err(e);
+        }
+        writeln!(out, "Share {index} stored in {target}")?;
+    }
+    Ok(())
+}
+
+/// Parse --format, if given
+fn output_format(args: &SplitArgs) -> CliResult<Option<OutputFormat>> {
+    let format = args
+        .format
+        .as_deref()
+        .map(|format| OutputFormat::parse(format, FORMAT_PLACEHOLDERS))
+        .transpose()
+        .map_err(|e| format!("Invalid --format: {e}"))?;
+    if args.label.is_none() && format.as_ref().is_some_and(|f| f.uses("label")) {
+        return Err("--format uses {label} but no --label was given".into());
+    }
+    Ok(format)
+}
+
+/// Check the --holder names and load the sheet template, if sheets were asked for
+fn sheet_template(args: &SplitArgs) -> CliResult<Option<OutputFormat>> {
+    match args.holder.as_slice() {
+        [] => Ok(None),
+        [first, second] if first == second => {
+            Err(format!("--holder {first} was given twice; name two different holders").into())
+        }
+        [_, _] => holder::load_template(args.holder_template.as_deref()).map(Some),
+        _ => Err("--holder must be given exactly twice, once per share".into()),
+    }
+}
+
+/// Write the instruction sheet for the holder of a share just written to `share_path`
+fn write_sheet(
+    template: &OutputFormat,
+    holders: &[String],
+    (index, share, share_path): (u8, &[u8], &Path),
+    vars: &FormatVars,
+    out: &mut dyn Write,
+) -> CliResult<()> {
+    // `sheet_template` checked there are exactly two holders
+    let position = usize::from(index) - 1;
+    let (holder, other) = (&holders[position], &holders[1 - position]);
+    let sheet = holder::render(
+        template,
+        &SheetVars {
+            holder,
+            other,
+            index,
+            share: &BASE64.encode(share),
+            fingerprint: &share_fingerprint(share),
+            label: vars.label,
+            id: vars.id,
+            len: vars.len,
+            file: share_path,
+        },
+    );
+    let path = holder::sheet_path(share_path, holder);
+    create_private_file(&path)?
+        .write_all(sheet.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    writeln!(out, "Sheet for share {index} written to {}", path.display())?;
+    Ok(())
+}
+
+/// Values for the non-share placeholders of `split --format`
+struct FormatVars<'a> {
+    id: &'a str,
+    len: usize,
+    label: Option<&'a str>,
+}
+
+/// Render `--format` for the two (signed) shares
+fn render_format(format: &OutputFormat, shares: &[Vec<u8>], vars: &FormatVars) -> String {
+    let share = |index: usize| shares.get(index).map_or(&[][..], Vec::as_slice);
+    format.render(|name| match name {
+        "share1" => BASE64.encode(share(0)),
+        "share2" => BASE64.encode(share(1)),
+        "fp1" => share_fingerprint(share(0)),
+        "fp2" => share_fingerprint(share(1)),
+        "id" => vars.id.to_owned(),
+        "len" => vars.len.to_string(),
+        _ => vars.label.unwrap_or_default().to_owned(),
+    })
+}
+
+/// Print the share sizes and destinations a real run would produce
+///
+/// The secret is only read when its length can't be taken from the file size
+/// (stdin, --secret-cmd, or --normalize, which can change the length).
+fn dry_run(
+    args: &mut SplitArgs,
+    paths: Option<&[PathBuf; 2]>,
+    keychain_targets: &[(u8, KeychainRef)],
+) -> CliResult {
+    let file_len = match &args.input {
+        Some(IoTarget::Path(path)) if args.normalize.is_none() => Some(
+            fs::metadata(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        )
+        .filter(fs::Metadata::is_file)
+        .map(|metadata| metadata.len()),
+        _ => None,
+    };
+    let secret_len = match file_len {
+        Some(len) => usize::try_from(len)?,
+        None => read_secret(args)?.len(),
+    };
+    if secret_len == 0 {
+        return Err(format!("Failed to split secret: {}", ShareError::EmptyInput).into());
+    }
+
+    let mut len = share_len(secret_len);
+    let mut options = Vec::new();
+    if let Some(size) = args.chunk_size {
+        len = chunked_len(secret_len, size.min(secret_len));
+        options.push(format!("--chunk-size {size}"));
+    }
+    if args.no_crc {
+        len = secret_len;
+        options.push("--no-crc".to_owned());
+    }
+    if let Some(padding) = padding(args) {
+        len = padded_share_len(secret_len, padding);
+        options.push(match padding {
+            Padding::Block(block) => format!("--pad {block}"),
+            Padding::PowerOfTwo => "--pad-pow2".to_owned(),
+        });
+    }
+    if let Some(form) = args.normalize {
+        options.push(format!("--normalize {form}"));
+    }
+    if args.protect {
+        len = protected_len(len);
+        options.push("--protect".to_owned());
+    }
+    if args.sign_key.is_some() {
+        len = signed_len(len);
+        options.push("--sign-key".to_owned());
+    }
+    if !args.roles.is_empty() {
+        options.push(format!("--roles {}", args.roles.join(",")));
+    }
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "Dry run: no randomness generated, nothing written")?;
+    writeln!(out, "Secret: {secret_len} bytes")?;
+    for index in 1..=2 {
+        let len = args
+            .roles
+            .get(usize::from(index) - 1)
+            .map_or(len, |role| labeled_len(len, role));
+        let encoded_len = len.div_ceil(3) * 4;
+        let destination = match (keychain_targets.iter().find(|(i, _)| *i == index), paths) {
+            (Some((_, target)), _) => target.to_string(),
+            (None, Some(paths)) => paths[usize::from(index) - 1].display().to_string(),
+            (None, None) => match &args.out {
+                Some(IoTarget::Path(path)) => path.display().to_string(),
+                Some(IoTarget::Std) | None => "stdout".to_owned(),
+            },
+        };
+        writeln!(
+            out,
+            "Share {index}: {len} bytes ({encoded_len} base64 characters) -> {destination}"
+        )?;
+    }
+    if let Some(paths) = paths.filter(|_| !args.holder.is_empty()) {
+        for (path, name) in paths.iter().zip(&args.holder) {
+            writeln!(
+                out,
+                "Sheet for {name}: {}",
+                holder::sheet_path(path, name).display()
+            )?;
+        }
+    }
+    if !options.is_empty() {
+        writeln!(out, "Options: {}", options.join(", "))?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Keychain entries that receive shares, by share index
+fn keychain_targets(args: &SplitArgs) -> Vec<(u8, KeychainRef)> {
+    if let Some(name) = &args.keyring {
+        vec![
+            (1, KeychainRef::for_share(name, 1)),
+            (2, KeychainRef::for_share(name, 2)),
+        ]
+    } else {
+        args.store_share1
+            .iter()
+            .map(|target| (1, target.clone()))
+            .collect()
+    }
+}
+
+/// Read the secret from --secret-cmd, the argument, --in or stdin, normalizing it if asked
+fn read_secret(args: &mut SplitArgs) -> CliResult<Zeroizing<Vec<u8>>> {
+    let secret_bytes = if let Some(command) = &args.secret_cmd {
+        SecretCommand {
+            command,
+            shell: args.shell,
+            timeout: Duration::from_secs(args.secret_cmd_timeout),
+            keep_newline: args.keep_newline,
+        }
+        .run()?
+    } else {
+        Zeroizing::new(match (args.secret.take(), &args.input) {
+            (Some(s), _) => s.into_bytes(),
+            (None, Some(input)) => input.read_all()?,
+            (None, None) => {
+                let mut buffer = Vec::new();
+                io::stdin().read_to_end(&mut buffer)?;
+                buffer
+            }
+        })
+    };
+
+    Ok(match args.normalize {
+        Some(form) => {
+            let text = std::str::from_utf8(&secret_bytes)
+                .map_err(|_| "--normalize requires the secret to be UTF-8 text")?;
+            Zeroizing::new(form.apply(text).into_bytes())
+        }
+        None => secret_bytes,
+    })
+}
+
+/// Whether `secret` is a base64 share with a valid checksum (a likely mix-up)
+fn looks_like_share(secret: &[u8]) -> bool {
+    std::str::from_utf8(secret)
+        .ok()
+        .and_then(|text| BASE64.decode(text.trim()).ok())
+        .is_some_and(|decoded| is_valid_share(&decoded))
+}
+
+/// Read a share file back and check it holds exactly `share`
+fn check_share_file(path: &Path, share: &[u8]) -> CliResult<()> {
+    let written = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read back {}: {e}", path.display()))?;
+    if written.trim_end() != BASE64.encode(share) {
+        return Err(format!("{} does not hold the share just written", path.display()).into());
+    }
+    Ok(())
+}
+
+/// Write a base64 share file, creating parent directories as needed
+pub fn write_share_file(path: &Path, share: &[u8]) -> CliResult<()> {
+    create_parent_dirs(path)?;
+    writeln!(create_private_file(path)?, "{}", BASE64.encode(share))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()).into())
+}
+
+fn create_parent_dirs(path: &Path) -> CliResult<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Whether the secret can be streamed straight into share files
+///
+/// Everything that needs the whole secret or share in memory (a literal secret,
+/// --secret-cmd, --normalize, --protect, --sign-key, --store-share1, --keyring,
+/// --chunk-size, --pad, --stats, --holder, --roles, and --drive1 and --shred, which verify the
+/// shares after writing them) falls back to the buffered path.
+fn can_stream(args: &SplitArgs) -> bool {
+    args.secret.is_none()
+        && args.secret_cmd.is_none()
+        && args.normalize.is_none()
+        && !args.protect
+        && args.sign_key.is_none()
+        && args.store_share1.is_none()
+        && args.keyring.is_none()
+        && !args.stats
+        && !args.shred
+        && args.holder.is_empty()
+        && args.roles.is_empty()
+        && args.drive1.is_none()
+        && args.chunk_size.is_none()
+        && !args.no_crc
+        && args.hint.is_none()
+        && padding(args).is_none()
+}
+
+/// Stream the secret from `input` (stdin by default) into base64 share files
+///
+/// Ctrl-C cancels cleanly: both partial files are removed before returning.
+fn stream_to_files(input: Option<&IoTarget>, paths: &[PathBuf; 2]) -> CliResult {
+    let reader: Box<dyn Read> = match input {
+        Some(IoTarget::Path(path)) => Box::new(
+            fs::File::open(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        ),
+        Some(IoTarget::Std) | None => Box::new(io::stdin().lock()),
+    };
+
+    let write = || -> CliResult<()> {
+        let open = |path: &PathBuf| -> CliResult<_> {
+            create_parent_dirs(path)?;
+            Ok(EncoderWriter::new(create_private_file(path)?, &BASE64))
+        };
+        let (mut first, mut second) = (open(&paths[0])?, open(&paths[1])?);
+        split_stream(reader, &mut first, &mut second, Some(cancel_flag()))?;
+        for mut encoder in [first, second] {
+            writeln!(encoder.finish()?)?;
+        }
+        Ok(())
+    };
+
+    write().map_err(|e| -> Box<dyn std::error::Error> {
+        for path in paths {
+            // Best effort: the file may never have been created
+            let _ = fs::remove_file(path);
+        }
+        if cancel_flag().load(Ordering::Relaxed) {
+            "Interrupted; partial share files were removed".into()
+        } else {
+            e
+        }
+    })?;
+
+    let mut out = open_output(None)?;
+    for (index, path) in (1..).zip(paths) {
+        writeln!(out, "Share {index} written to {}", path.display())?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Resolve the share file paths from `--output`, `--name-template` or the
+/// drives, if any
+///
+/// Everything that can be rejected is rejected here, before the secret is read.
+fn share_files(args: &SplitArgs, id: &str) -> CliResult<Option<[PathBuf; 2]>> {
+    if let Some(prefix) = &args.output {
+        return Ok(Some([1, 2].map(|index| share_path(prefix, index))));
+    }
+    if let (Some(drive1), Some(drive2)) = (&args.drive1, &args.drive2) {
+        return drive_files(drive1, drive2, args.label.as_deref()).map(Some);
+    }
+    let Some(template) = &args.name_template else {
+        return Ok(None);
+    };
+
+    if let Some(label) = &args.label {
+        validate_label(label).map_err(|e| format!("Invalid --label: {e}"))?;
+    } else if template.uses(Variable::Label) {
+        return Err("--name-template uses {label} but no --label was given".into());
+    }
+
+    let date = utc_date(SystemTime::now());
+    let paths = [1, 2].map(|index| {
+        template.expand(&NameVars {
+            label: args.label.as_deref(),
+            id,
+            index,
+            date: &date,
+            ext: SHARE_EXT,
+        })
+    });
+
+    if paths[0] == paths[1] {
+        return Err(format!(
+            "--name-template expands to {} for both shares; include {{index}}",
+            paths[0].display()
+        )
+        .into());
+    }
+    Ok(Some(paths))
+}
+
+/// Share file paths on two drives, checking both are mounted and distinct
+fn drive_files(drive1: &Path, drive2: &Path, label: Option<&str>) -> CliResult<[PathBuf; 2]> {
+    let name = match label {
+        Some(label) => {
+            validate_label(label).map_err(|e| format!("Invalid --label: {e}"))?;
+            label
+        }
+        None => "xplit",
+    };
+    let mut resolved = Vec::with_capacity(2);
+    for (index, drive) in [(1, drive1), (2, drive2)] {
+        let dir = fs::canonicalize(drive)
+            .ok()
+            .filter(|dir| dir.is_dir())
+            .ok_or_else(|| {
+                format!(
+                    "Drive {index} not found: {} is not a directory",
+                    drive.display()
+                )
+            })?;
+        resolved.push(dir);
+    }
+    if resolved[0] == resolved[1] {
+        return Err(format!(
+            "--drive1 and --drive2 are the same directory ({}); use two different drives",
+            resolved[0].display()
+        )
+        .into());
+    }
+    Ok([1, 2].map(|index| share_path(&resolved[usize::from(index) - 1].join(name), index)))
+}
+
+/// Parse a `--chunk-size` such as `65536`, `64K` or `64KiB`
+fn parse_chunk_size(value: &str) -> Result<usize, String> {
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let multiplier = match &value[digits.len()..] {
+        "" | "B" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        unit => {
+            return Err(format!(
+                "unknown unit '{unit}' (expected K/KiB, M/MiB or G/GiB)"
+            ));
+        }
+    };
+    let size = digits
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("'{value}' is not a size"))?;
+    if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&size) {
+        return Err(format!(
+            "chunk size must be between {MIN_CHUNK_SIZE} bytes and 1 GiB"
+        ));
+    }
+    Ok(size)
+}
+
+/// Parse a `--pad` block size, rejecting 0 and anything above 1 MiB
+fn parse_pad_block(value: &str) -> Result<usize, String> {
+    let block: usize = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number of bytes"))?;
+    if !(1..=MAX_PAD_BLOCK).contains(&block) {
+        return Err(format!("must be between 1 and {MAX_PAD_BLOCK} bytes"));
+    }
+    Ok(block)
+}
+
+fn check_roles(roles: &[String]) -> CliResult<()> {
+    if !roles.is_empty() && roles.len() != 2 {
+        return Err("--roles takes one role per share, e.g. --roles CFO,CTO".into());
+    }
+    Ok(())
+}
+
+/// Sign share `index`, then label it with its custodian's role from `--roles`
+fn wrap_share(
+    key: Option<&SigningKey>,
+    roles: &[String],
+    index: u8,
+    share: Vec<u8>,
+) -> CliResult<Vec<u8>> {
+    let share = match key {
+        Some(key) => sign_share(&share, key),
+        None => share,
+    };
+    // The role goes outermost, readable without a passphrase or key
+    match roles.get(usize::from(index) - 1) {
+        Some(role) => Ok(label_share(&share, role)?),
+        None => Ok(share),
+    }
+}
+
+/// Parse a `--roles` entry, which must be a valid share label
+fn parse_role(value: &str) -> Result<String, String> {
+    xplit::label::validate_label(value).map_err(|e| e.to_string())?;
+    Ok(value.to_owned())
+}
+
+/// Random id linking the outputs of one split, e.g. `1a2b3c4d`
+///
+/// A dry run draws no randomness, so its file names show `{id}` instead.
+fn pairing_id(dry_run: bool) -> String {
+    if dry_run {
+        return "{id}".to_owned();
+    }
+    let mut id = [0u8; 4];
+    rand::thread_rng().fill_bytes(&mut id);
+    hex::encode(id)
+}
+
+/// Report split timing and the byte distribution of the random pad on stderr
+fn print_stats(elapsed: Duration, pad: &[u8]) {
+    let mut counts = [0usize; 256];
+    for &b in pad {
+        counts[b as usize] += 1;
+    }
+    let min = counts.iter().min().copied().unwrap_or(0);
+    let max = counts.iter().max().copied().unwrap_or(0);
+
+    #[allow(clippy::cast_precision_loss)]
+    let bytes = pad.len() as f64;
+    let secs = elapsed.as_secs_f64();
+    let throughput = if secs > 0.0 {
+        bytes / secs / (1024.0 * 1024.0)
+    } else {
+        f64::INFINITY
+    };
+
+    eprintln!("time:         {elapsed:?}");
+    eprintln!("bytes:        {}", pad.len());
+    eprintln!("throughput:   {throughput:.2} MiB/s");
+    eprintln!(
+        "random share: entropy {:.4} bits/byte, byte counts min {min} / max {max} (expected {:.1})",
+        shannon_entropy(pad),
+        bytes / 256.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunk_size() {
+        assert_eq!(parse_chunk_size("64KiB"), Ok(65_536));
+        assert_eq!(parse_chunk_size("64K"), Ok(65_536));
+        assert_eq!(parse_chunk_size("4096"), Ok(4096));
+        assert_eq!(parse_chunk_size("1GiB"), Ok(1 << 30));
+        assert!(parse_chunk_size("8").is_err());
+        assert!(parse_chunk_size("2GiB").is_err());
+        assert!(parse_chunk_size("64KB").is_err());
+        assert!(parse_chunk_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_looks_like_share() {
+        assert!(looks_like_share(b"ZiTjk3OD6puSVM/JV3CYopI=\n"));
+        assert!(!looks_like_share(b"Hello, World!"));
+        // Valid base64, but no matching checksum
+        assert!(!looks_like_share(b"aGVsbG8gd29ybGQ="));
+    }
+}
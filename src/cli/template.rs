@@ -0,0 +1,246 @@
+//! `--name-template` expansion for share file names
+//!
+//! A template is literal text with `{variable}` placeholders. It is parsed and
+//! validated once, before any share is produced, so a typo can't leave half a
+//! ceremony's files on disk.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A `{variable}` a template can reference
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variable {
+    Label,
+    Id,
+    Index,
+    Date,
+    Ext,
+}
+
+impl Variable {
+    const ALL: [Self; 5] = [Self::Label, Self::Id, Self::Index, Self::Date, Self::Ext];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::Id => "id",
+            Self::Index => "index",
+            Self::Date => "date",
+            Self::Ext => "ext",
+        }
+    }
+}
+
+/// One parsed piece of a template
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Variable(Variable),
+}
+
+/// A validated file name template
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameTemplate(Vec<Part>);
+
+/// Values substituted into a template
+pub struct NameVars<'a> {
+    /// `--label`, if given
+    pub label: Option<&'a str>,
+    /// Pairing id shared by every file of one split
+    pub id: &'a str,
+    /// 1-based share index
+    pub index: u8,
+    /// UTC date of the split, `YYYY-MM-DD`
+    pub date: &'a str,
+    /// Extension matching the file's encoding
+    pub ext: &'a str,
+}
+
+/// Why a template was rejected
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    UnknownVariable(String),
+    UnclosedBrace,
+    UnopenedBrace,
+    PathTraversal,
+    AbsolutePath,
+    InvalidLabel,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownVariable(name) => write!(
+                f,
+                "unknown variable {{{name}}} (expected one of: {})",
+                Variable::ALL
+                    .map(|v| format!("{{{}}}", v.name()))
+                    .join(", ")
+            ),
+            Self::UnclosedBrace => write!(f, "'{{' without a matching '}}'"),
+            Self::UnopenedBrace => write!(f, "'}}' without a matching '{{'"),
+            Self::PathTraversal => write!(f, "'..' path components are not allowed"),
+            Self::AbsolutePath => write!(f, "template must be a relative path"),
+            Self::InvalidLabel => write!(f, "label must not contain path separators or '..'"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl FromStr for NameTemplate {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let path = Path::new(s);
+        if path.is_absolute() {
+            return Err(TemplateError::AbsolutePath);
+        }
+        if path.components().any(|c| c == Component::ParentDir) {
+            return Err(TemplateError::PathTraversal);
+        }
+
+        let mut parts = Vec::new();
+        let mut rest = s;
+        while !rest.is_empty() {
+            match rest.find(['{', '}']) {
+                Some(pos) if rest[pos..].starts_with('}') => {
+                    return Err(TemplateError::UnopenedBrace);
+                }
+                Some(pos) => {
+                    if pos > 0 {
+                        parts.push(Part::Literal(rest[..pos].to_owned()));
+                    }
+                    let end = rest[pos..].find('}').ok_or(TemplateError::UnclosedBrace)? + pos;
+                    let name = &rest[pos + 1..end];
+                    let variable = Variable::ALL
+                        .into_iter()
+                        .find(|v| v.name() == name)
+                        .ok_or_else(|| TemplateError::UnknownVariable(name.to_owned()))?;
+                    parts.push(Part::Variable(variable));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    parts.push(Part::Literal(rest.to_owned()));
+                    rest = "";
+                }
+            }
+        }
+        Ok(Self(parts))
+    }
+}
+
+impl NameTemplate {
+    /// Whether the template references `variable`
+    pub fn uses(&self, variable: Variable) -> bool {
+        self.0.contains(&Part::Variable(variable))
+    }
+
+    /// Expand the template into a relative path
+    pub fn expand(&self, vars: &NameVars) -> PathBuf {
+        let mut name = String::new();
+        for part in &self.0 {
+            match part {
+                Part::Literal(text) => name.push_str(text),
+                Part::Variable(Variable::Label) => name.push_str(vars.label.unwrap_or_default()),
+                Part::Variable(Variable::Id) => name.push_str(vars.id),
+                Part::Variable(Variable::Index) => name.push_str(&vars.index.to_string()),
+                Part::Variable(Variable::Date) => name.push_str(vars.date),
+                Part::Variable(Variable::Ext) => name.push_str(vars.ext),
+            }
+        }
+        PathBuf::from(name)
+    }
+}
+
+/// Check a `--label` value can't escape the template's directory
+pub fn validate_label(label: &str) -> Result<(), TemplateError> {
+    if label.contains(['/', '\\']) || label.contains("..") {
+        return Err(TemplateError::InvalidLabel);
+    }
+    Ok(())
+}
+
+/// Today's UTC date as `YYYY-MM-DD`
+pub fn utc_date(now: SystemTime) -> String {
+    let days = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400);
+    let days = i64::try_from(days).unwrap_or(0);
+
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn vars(index: u8) -> NameVars<'static> {
+        NameVars {
+            label: Some("vault"),
+            id: "1a2b3c4d",
+            index,
+            date: "2024-02-29",
+            ext: "b64",
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let template: NameTemplate = "{label}-{date}-{index}.{ext}".parse().unwrap();
+        assert_eq!(
+            template.expand(&vars(2)),
+            PathBuf::from("vault-2024-02-29-2.b64")
+        );
+
+        let template: NameTemplate = "archive/{id}/share{index}".parse().unwrap();
+        assert_eq!(
+            template.expand(&vars(1)),
+            PathBuf::from("archive/1a2b3c4d/share1")
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        assert_eq!(
+            "{name}.txt".parse::<NameTemplate>(),
+            Err(TemplateError::UnknownVariable("name".to_owned()))
+        );
+        assert_eq!(
+            "{index".parse::<NameTemplate>(),
+            Err(TemplateError::UnclosedBrace)
+        );
+        assert_eq!(
+            "../{index}".parse::<NameTemplate>(),
+            Err(TemplateError::PathTraversal)
+        );
+        assert_eq!(
+            "/tmp/{index}".parse::<NameTemplate>(),
+            Err(TemplateError::AbsolutePath)
+        );
+        assert!(validate_label("../etc").is_err());
+    }
+
+    #[test]
+    fn test_utc_date() {
+        assert_eq!(utc_date(UNIX_EPOCH), "1970-01-01");
+        assert_eq!(
+            utc_date(UNIX_EPOCH + Duration::from_hours(474_780)),
+            "2024-02-29"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! `xplit verify`
+
+use clap::Args;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use xplit::chunked::{check_chunks, is_chunked};
+use xplit::pad::{is_padded, open_padded};
+use xplit::sign::{VerifyingKey, key_fingerprint};
+use xplit::{is_valid_share, share_fingerprint, unseal};
+
+use super::CliResult;
+use super::io::{IoTarget, open_output};
+use super::keys::read_verifying_key;
+use super::shares::{ShareArg, describe_bad_chunks, load_share};
+
+/// Exit code when `--pair` finds a share invalid on its own
+pub const EXIT_SHARE_INVALID: u8 = 3;
+/// Exit code when `--pair` finds two valid shares that can't belong together
+pub const EXIT_NOT_A_PAIR: u8 = 4;
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Share: base64, `@PATH` to read a file, `@-` for stdin, or `keychain:NAME`
+    #[arg(conflicts_with = "input", required_unless_present_any = ["input", "pair"])]
+    share: Option<ShareArg>,
+    /// Check that two shares belong together instead of checking one
+    ///
+    /// Prints a PASS/FAIL table and both fingerprints. The shares are never
+    /// XOR'd, so the secret is not computed, which makes this safe to run
+    /// outside the environment where recovery will happen. Exits with 3 if
+    /// either share is invalid and 4 if both are valid but can't be a pair.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["SHARE1", "SHARE2"],
+        conflicts_with_all = ["share", "input"]
+    )]
+    pair: Option<Vec<ShareArg>>,
+    /// Read the share from a file, or `-` for stdin
+    #[arg(long = "in", value_name = "PATH|-")]
+    input: Option<IoTarget>,
+    /// Write the report to a file, or `-` for stdout (the default)
+    #[arg(long, value_name = "PATH|-")]
+    out: Option<IoTarget>,
+    /// Read the passphrase for a protected share from a file
+    #[arg(long, value_name = "PATH")]
+    passphrase_file: Option<PathBuf>,
+    /// Require the share to be signed by this public key
+    #[arg(long, value_name = "PUBKEY")]
+    signer: Option<PathBuf>,
+}
+
+pub fn run(args: &VerifyArgs) -> CliResult {
+    let expected = args.signer.as_deref().map(read_verifying_key).transpose()?;
+    if let Some(pair) = &args.pair {
+        return verify_pair(pair, args, expected.as_ref());
+    }
+    let source = ShareArg::pick(args.share.as_ref(), args.input.as_ref())?;
+    let share = load_share(&source, 1, args.passphrase_file.as_ref(), expected.as_ref())?;
+    let padding = is_padded(&share.bytes)
+        .then(|| open_padded(&share.bytes))
+        .transpose()?;
+    let bytes = padding.map_or(&share.bytes[..], |(_, inner)| inner);
+    let summary = if is_chunked(bytes) {
+        let report = check_chunks(bytes)?;
+        if !report.bad.is_empty() {
+            return Err(format!("Share is corrupted - {}", describe_bad_chunks(&report)).into());
+        }
+        format!(
+            "{} byte payload in {} chunks of up to {} bytes",
+            report.payload_len, report.chunks, report.chunk_size
+        )
+    } else if is_valid_share(bytes) {
+        format!("{} byte payload", bytes.len() - 4)
+    } else {
+        return Err("Share is corrupted - checksum does not match".into());
+    };
+    let mut out = open_output(args.out.as_ref())?;
+    writeln!(out, "Share is valid ({summary})")?;
+    if let Some(label) = &share.label {
+        writeln!(out, "label: {label}")?;
+    }
+    if let Some((padding, _)) = padding {
+        // The payload length above is the padded one
+        writeln!(out, "padded: yes ({padding})")?;
+    }
+
+    match (&share.signer, &expected) {
+        (Some(key), Some(_)) => writeln!(out, "Signed by {}", key_fingerprint(key))?,
+        (Some(key), None) => writeln!(
+            out,
+            "Signed by {} (embedded key, not checked against --signer)",
+            key_fingerprint(key)
+        )?,
+        (None, Some(_)) => return Err("Share is not signed".into()),
+        (None, None) => {}
+    }
+    out.flush()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `verify --pair`: check two shares against each other without recovering
+fn verify_pair(pair: &[ShareArg], args: &VerifyArgs, expected: Option<&VerifyingKey>) -> CliResult {
+    let loaded: Vec<_> = (1..)
+        .zip(pair)
+        .map(|(index, source)| {
+            load_share(source, index, args.passphrase_file.as_ref(), expected)
+                .and_then(|share| {
+                    unseal(&share.bytes)?;
+                    Ok(share.bytes)
+                })
+                .map_err(|e| e.to_string())
+        })
+        .collect();
+
+    let mut out = open_output(args.out.as_ref())?;
+    writeln!(out, "{:<20} RESULT", "CHECK")?;
+    for (index, share) in (1..).zip(&loaded) {
+        let result = match share {
+            Ok(_) => "PASS".to_owned(),
+            Err(e) => format!("FAIL ({e})"),
+        };
+        writeln!(out, "{:<20} {result}", format!("share {index} valid"))?;
+    }
+
+    let (Ok(share1), Ok(share2)) = (&loaded[0], &loaded[1]) else {
+        writeln!(out, "{:<20} SKIP (needs two valid shares)", "equal length")?;
+        out.flush()?;
+        return Ok(ExitCode::from(EXIT_SHARE_INVALID));
+    };
+    let paired = xplit::verify_pair(share1, share2);
+    let result = match &paired {
+        Ok(len) => format!("PASS ({len} bytes)"),
+        Err(_) => format!("FAIL ({} and {} bytes)", share1.len() - 4, share2.len() - 4),
+    };
+    writeln!(out, "{:<20} {result}", "equal length")?;
+    writeln!(
+        out,
+        "{:<20} SKIP (shares carry no pairing id or index)",
+        "pairing id"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "Share 1 fingerprint: {}", share_fingerprint(share1))?;
+    writeln!(out, "Share 2 fingerprint: {}", share_fingerprint(share2))?;
+    writeln!(out, "No XOR was performed; the secret was not computed.")?;
+    out.flush()?;
+
+    Ok(if paired.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NOT_A_PAIR)
+    })
+}
@@ -0,0 +1,93 @@
+//! Deterministic share dealing
+//!
+//! Produces byte-identical N-of-N share sets for the same `(seed, secret, n)`,
+//! so several nodes can regenerate the same shares from shared configuration.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::{ShareError, split_n_with_rng};
+
+/// Deals reproducible N-of-N share sets from a fixed 32-byte seed
+///
+/// **The seed is secret-equivalent.** Anyone holding the seed and any single
+/// share can regenerate every random pad and therefore recover the secret.
+/// Store and transmit the seed with at least the same care as the secret itself,
+/// and never reuse a seed for two different secrets: the first shares of two such
+/// deals XOR together to the XOR of the two secrets.
+pub struct DeterministicDealer {
+    seed: [u8; 32],
+}
+
+impl DeterministicDealer {
+    /// Create a dealer from a 32-byte seed
+    #[must_use]
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self { seed }
+    }
+
+    /// Deal `n` shares of `secret`, all of which are required for recovery
+    ///
+    /// The random pads are drawn from a `ChaCha20Rng` freshly seeded on every call,
+    /// so repeated calls with the same secret and `n` return identical shares.
+    /// Recover with [`crate::recover_secret_n`].
+    ///
+    /// # Errors
+    ///
+    /// Returns:
+    /// - [`ShareError::EmptyInput`] if the secret is empty
+    /// - [`ShareError::TooFewShares`] if `n` is less than 2
+    pub fn deal(&self, secret: &[u8], n: usize) -> Result<Vec<Vec<u8>>, ShareError> {
+        let mut rng = ChaCha20Rng::from_seed(self.seed);
+        split_n_with_rng(secret, n, &mut rng)
+    }
+}
+
+impl std::fmt::Debug for DeterministicDealer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeterministicDealer")
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recover_secret_n;
+
+    #[test]
+    fn test_same_seed_deals_identical_shares() {
+        let secret = b"Hello, World!";
+        let shares_a = DeterministicDealer::new([7; 32]).deal(secret, 4).unwrap();
+        let shares_b = DeterministicDealer::new([7; 32]).deal(secret, 4).unwrap();
+
+        assert_eq!(shares_a.len(), 4);
+        assert_eq!(shares_a, shares_b);
+    }
+
+    #[test]
+    fn test_different_seeds_deal_different_shares() {
+        let secret = b"Hello, World!";
+        let shares_a = DeterministicDealer::new([1; 32]).deal(secret, 3).unwrap();
+        let shares_b = DeterministicDealer::new([2; 32]).deal(secret, 3).unwrap();
+
+        assert_ne!(shares_a, shares_b);
+    }
+
+    #[test]
+    fn test_dealt_shares_recover() {
+        let secret = b"distributed secret";
+        let shares = DeterministicDealer::new([42; 32]).deal(secret, 5).unwrap();
+        let refs: Vec<&[u8]> = shares.iter().map(Vec::as_slice).collect();
+
+        assert_eq!(recover_secret_n(&refs).unwrap(), secret);
+        assert_ne!(recover_secret_n(&refs[..4]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_deal_rejects_too_few_shares() {
+        let dealer = DeterministicDealer::new([0; 32]);
+        assert_eq!(dealer.deal(b"secret", 1), Err(ShareError::TooFewShares));
+        assert_eq!(dealer.deal(b"", 2), Err(ShareError::EmptyInput));
+    }
+}
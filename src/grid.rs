@@ -0,0 +1,284 @@
+//! Printable grids for copying shares by hand
+//!
+//! A long base64 string is easy to miscopy and hard to check: one wrong
+//! character only shows up as a failed checksum for the whole share. A grid
+//! spells the share in base32 (one case, and no `0`, `1` or `8` to mistake for
+//! `O`, `I` or `B`), laid out in numbered rows of four lettered groups, each row ending in its
+//! own check character:
+//!
+//! ```text
+//!     A    B    C    D    #
+//! 01  MYSO HE3T QPVJ XESU  T
+//! 02  Z7EV O4EY UKJA       I
+//! ```
+//!
+//! A copying mistake is then reported by row, so only that row needs
+//! rechecking against the paper. The check character is Luhn mod 32 over the
+//! row number and the row's characters: it catches any single wrong character
+//! and most swaps of neighbouring ones, and a row copied under the wrong
+//! number. The share's own CRC32 still guards the whole.
+
+use crate::ShareError;
+
+/// Base32 characters per group
+pub const GROUP_LEN: usize = 4;
+/// Groups per row
+pub const GROUPS_PER_ROW: usize = 4;
+
+const ROW_LEN: usize = GROUP_LEN * GROUPS_PER_ROW;
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const CHECK_COLUMN: &str = "#";
+
+/// Render a share as a grid of base32, one check character per row
+#[must_use]
+pub fn format_grid(share: &[u8]) -> String {
+    let encoded = base32_encode(share);
+    let rows: Vec<&[u8]> = encoded.as_bytes().chunks(ROW_LEN).collect();
+    let width = row_label_width(rows.len());
+
+    let columns: Vec<String> = (b'A'..)
+        .take(GROUPS_PER_ROW)
+        .map(|c| format!("{:<GROUP_LEN$}", char::from(c)))
+        .collect();
+    let header = format!("{:width$}  {} {CHECK_COLUMN}\n", "", columns.join(" "));
+    let lines = rows.iter().enumerate().map(|(i, row)| {
+        let groups: Vec<&str> = row
+            .chunks(GROUP_LEN)
+            .map(|group| std::str::from_utf8(group).unwrap_or_default())
+            .collect();
+        let cells = format!(
+            "{:<full$}",
+            groups.join(" "),
+            full = ROW_LEN + GROUPS_PER_ROW - 1
+        );
+        let check = char::from(ALPHABET[check_value(i + 1, row)]);
+        format!("{:0width$}  {cells}  {check}\n", i + 1)
+    });
+    header + &lines.collect::<String>()
+}
+
+/// Read a grid written by [`format_grid`] back into the share
+///
+/// The column header is optional, letters may be lower case and spacing is
+/// free, as long as every row keeps its number and check character.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if there are no rows
+/// - [`ShareError::InvalidGrid`] if a line isn't the next row, holds a
+///   character outside base32, or the rows don't add up to a share
+/// - [`ShareError::InvalidGridRow`] if a row fails its check character
+pub fn parse_grid(text: &str) -> Result<Vec<u8>, ShareError> {
+    let mut encoded = Vec::new();
+    let mut last_line = 0;
+    let mut short_row = None;
+    let mut rows = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || (rows == 0 && is_header(&tokens)) {
+            continue;
+        }
+        let invalid = ShareError::InvalidGrid { line: line_number };
+        // A short row is only allowed last
+        if short_row.is_some() {
+            return Err(invalid);
+        }
+        let [label, cells @ .., check] = tokens.as_slice() else {
+            return Err(invalid);
+        };
+        if label.parse::<usize>().ok() != Some(rows + 1) {
+            return Err(invalid);
+        }
+        let row: Vec<u8> = cells.concat().to_ascii_uppercase().into_bytes();
+        let check = check.to_ascii_uppercase().into_bytes();
+        if row.is_empty()
+            || row.len() > ROW_LEN
+            || check.len() != 1
+            || !row.iter().chain(&check).all(|c| ALPHABET.contains(c))
+        {
+            return Err(invalid);
+        }
+
+        rows += 1;
+        if ALPHABET[check_value(rows, &row)] != check[0] {
+            return Err(ShareError::InvalidGridRow { row: rows });
+        }
+        if row.len() < ROW_LEN {
+            short_row = Some(line_number);
+        }
+        encoded.extend_from_slice(&row);
+        last_line = line_number;
+    }
+
+    if encoded.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+    base32_decode(&encoded).ok_or(ShareError::InvalidGrid { line: last_line })
+}
+
+/// Returns `true` if `data` starts like a grid: the column header, or row 1
+#[must_use]
+pub fn is_grid(data: &[u8]) -> bool {
+    let first_line = data
+        .split(|&b| b == b'\n')
+        .map(<[u8]>::trim_ascii)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let tokens: Vec<&str> = std::str::from_utf8(first_line)
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect();
+    is_header(&tokens)
+        || tokens
+            .first()
+            .is_some_and(|label| label.trim_start_matches('0') == "1")
+}
+
+fn is_header(tokens: &[&str]) -> bool {
+    tokens.first().is_some_and(|t| t.eq_ignore_ascii_case("A"))
+        && tokens.last() == Some(&CHECK_COLUMN)
+}
+
+fn row_label_width(rows: usize) -> usize {
+    rows.to_string().len().max(2)
+}
+
+/// Luhn mod 32 check value over the row number and the row's characters
+fn check_value(row: usize, chars: &[u8]) -> usize {
+    let values = std::iter::once(row % 32).chain(chars.iter().map(|c| symbol_value(*c)));
+    let values: Vec<usize> = values.collect();
+    let mut factor = 2;
+    let mut sum = 0;
+    for value in values.iter().rev() {
+        let addend = factor * value;
+        sum += addend / 32 + addend % 32;
+        factor = 3 - factor;
+    }
+    (32 - sum % 32) % 32
+}
+
+fn symbol_value(c: u8) -> usize {
+    ALPHABET.iter().position(|&a| a == c).unwrap_or_default()
+}
+
+/// RFC 4648 base32 without padding
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(ALPHABET[usize::from((buffer >> bits) & 0x1f)]));
+        }
+    }
+    if bits > 0 {
+        out.push(char::from(
+            ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)],
+        ));
+    }
+    out
+}
+
+/// Decode unpadded base32, rejecting lengths and trailing bits no encoding produces
+fn base32_decode(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &c in encoded {
+        let value = ALPHABET.iter().position(|&a| a == c)?;
+        buffer = (buffer << 5) | u16::try_from(value).ok()?;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+        }
+    }
+    // Leftover bits must be a partial symbol's zero fill
+    (bits < 5 && buffer & ((1 << bits) - 1) == 0).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARE1: &[u8] = &[
+        0x66, 0x24, 0xe3, 0x93, 0x73, 0x83, 0xea, 0x9b, 0x92, 0x54, 0xcf, 0xc9, 0x57, 0x70, 0x98,
+        0xa2, 0x92,
+    ];
+
+    #[test]
+    fn test_grid_layout() {
+        let grid = format_grid(SHARE1);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines[0], "    A    B    C    D    #");
+        assert_eq!(lines[1], "01  MYSO HE3T QPVJ XESU  T");
+        assert_eq!(lines[2], "02  Z7EV O4EY UKJA       I");
+        assert_eq!(parse_grid(&grid).unwrap(), SHARE1);
+    }
+
+    #[test]
+    fn test_copying_mistakes_are_located() {
+        let grid = format_grid(&[0xA5; 40]);
+        // A wrong character in row 2, found by the row's check character
+        let lines: Vec<String> = grid.lines().map(str::to_owned).collect();
+        let mut typo = lines.clone();
+        let wrong = if &typo[2][4..5] == "B" { "C" } else { "B" };
+        typo[2].replace_range(4..5, wrong);
+        assert_eq!(
+            parse_grid(&typo.join("\n")),
+            Err(ShareError::InvalidGridRow { row: 2 })
+        );
+
+        // Neighbouring characters swapped
+        let mut swapped = lines.clone();
+        swapped[1] = lines[1].replacen("UWS2", "UW2S", 1);
+        assert_ne!(swapped[1], lines[1]);
+        assert_eq!(
+            parse_grid(&swapped.join("\n")),
+            Err(ShareError::InvalidGridRow { row: 1 })
+        );
+
+        // A skipped row
+        let skipped = [&lines[0], &lines[1], &lines[3]]
+            .map(String::as_str)
+            .join("\n");
+        assert_eq!(
+            parse_grid(&skipped),
+            Err(ShareError::InvalidGrid { line: 3 })
+        );
+
+        assert_eq!(
+            parse_grid("01  MYS! HE3T QPVJ XESU  G"),
+            Err(ShareError::InvalidGrid { line: 1 })
+        );
+        assert_eq!(parse_grid("\n\n"), Err(ShareError::EmptyInput));
+    }
+
+    #[test]
+    fn test_grid_input_is_forgiving_about_case_and_spacing() {
+        let grid = format_grid(SHARE1);
+        let retyped = grid
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            .to_lowercase();
+        assert_eq!(parse_grid(&retyped).unwrap(), SHARE1);
+        assert!(is_grid(retyped.as_bytes()));
+        assert!(is_grid(grid.as_bytes()));
+        assert!(!is_grid(b"ZiTjk3OD6puSVM/JV3CYopI="));
+    }
+
+    quickcheck::quickcheck! {
+        fn prop_grid_roundtrip(share: Vec<u8>) -> bool {
+            share.is_empty() || parse_grid(&format_grid(&share)).as_deref() == Ok(&share[..])
+        }
+    }
+}
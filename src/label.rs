@@ -0,0 +1,214 @@
+//! Share labels
+//!
+//! A label names the secret a share belongs to, e.g. "prod DB password", so a
+//! stored share can be told apart from others without recovering anything.
+//! It wraps a share of any format and is removed before recovery.
+//!
+//! The label is not covered by the inner share's checksum and is not
+//! authenticated: like a file name, anyone holding the share can change it.
+//!
+//! Layout:
+//!
+//! ```text
+//! magic "XPL1" | label length u8 | label (UTF-8) | share
+//! ```
+
+use crate::{ShareError, split_secret_n};
+
+/// Magic prefix identifying a labeled share
+pub const LABELED_MAGIC: &[u8; 4] = b"XPL1";
+
+/// Longest accepted label, in bytes of UTF-8
+pub const MAX_LABEL_LEN: usize = 64;
+
+const HEADER_LEN: usize = LABELED_MAGIC.len() + 1;
+
+/// Returns `true` if `data` starts with the labeled-share magic
+#[must_use]
+pub fn is_labeled(data: &[u8]) -> bool {
+    data.starts_with(LABELED_MAGIC)
+}
+
+/// Check that `label` can be stored: 1 to [`MAX_LABEL_LEN`] bytes, with no
+/// control characters
+///
+/// # Errors
+///
+/// Returns [`ShareError::InvalidLabel`] otherwise.
+pub fn validate_label(label: &str) -> Result<(), ShareError> {
+    if label.is_empty() || label.len() > MAX_LABEL_LEN || label.chars().any(char::is_control) {
+        return Err(ShareError::InvalidLabel);
+    }
+    Ok(())
+}
+
+/// Wrap `share` with a label
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the share is empty
+/// - [`ShareError::InvalidLabel`] if the label fails [`validate_label`]
+pub fn label_share(share: &[u8], label: &str) -> Result<Vec<u8>, ShareError> {
+    validate_label(label)?;
+    if share.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+
+    let mut labeled = Vec::with_capacity(HEADER_LEN + label.len() + share.len());
+    labeled.extend_from_slice(LABELED_MAGIC);
+    // validate_label keeps the length within MAX_LABEL_LEN
+    labeled.push(u8::try_from(label.len()).unwrap_or(u8::MAX));
+    labeled.extend_from_slice(label.as_bytes());
+    labeled.extend_from_slice(share);
+    Ok(labeled)
+}
+
+/// Length of `share` once labeled with `label`
+#[must_use]
+pub const fn labeled_len(share_len: usize, label: &str) -> usize {
+    HEADER_LEN + label.len() + share_len
+}
+
+/// Split secret into one share per custodian role, each labeled with its role
+///
+/// An N-of-N split (see [`split_secret_n`]): every role's share is needed to
+/// recover, in any order. The role is stored as the share's label, so
+/// recovery ignores it but anyone handed a share can see whose it is.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the secret is empty
+/// - [`ShareError::TooFewShares`] if fewer than 2 roles are given
+/// - [`ShareError::InvalidLabel`] if a role fails [`validate_label`]
+pub fn split_for_custodians(
+    secret: &[u8],
+    roles: &[&str],
+) -> Result<Vec<(String, Vec<u8>)>, ShareError> {
+    for role in roles {
+        validate_label(role)?;
+    }
+    let shares = split_secret_n(secret, roles.len())?;
+    roles
+        .iter()
+        .zip(shares)
+        .map(|(role, share)| Ok(((*role).to_owned(), label_share(&share, role)?)))
+        .collect()
+}
+
+/// Read the label, returning it with the inner share
+///
+/// The inner share is not checked here.
+///
+/// # Errors
+///
+/// Returns [`ShareError::InvalidLabel`] if the header is missing or malformed,
+/// or the label fails [`validate_label`].
+pub fn open_labeled(share: &[u8]) -> Result<(&str, &[u8]), ShareError> {
+    if !is_labeled(share) {
+        return Err(ShareError::InvalidLabel);
+    }
+    let len = usize::from(
+        *share
+            .get(LABELED_MAGIC.len())
+            .ok_or(ShareError::InvalidLabel)?,
+    );
+    let label = share
+        .get(HEADER_LEN..HEADER_LEN + len)
+        .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        .ok_or(ShareError::InvalidLabel)?;
+    validate_label(label)?;
+    Ok((label, &share[HEADER_LEN + len..]))
+}
+
+/// The share with its label removed, if it has one
+///
+/// # Errors
+///
+/// Returns the errors of [`open_labeled`] for a share with a labeled-share magic.
+pub fn strip_label(share: &[u8]) -> Result<(Option<&str>, &[u8]), ShareError> {
+    if is_labeled(share) {
+        open_labeled(share).map(|(label, inner)| (Some(label), inner))
+    } else {
+        Ok((None, share))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recover_secret, recover_secret_n, split_secret};
+
+    #[test]
+    fn test_labeled_roundtrip() {
+        let split = split_secret(b"Hello, World!").unwrap();
+        let share1 = label_share(&split.share1, "prod DB password").unwrap();
+        let share2 = label_share(&split.share2, "prod DB password").unwrap();
+
+        let (label, inner1) = open_labeled(&share1).unwrap();
+        assert_eq!(label, "prod DB password");
+        assert_eq!(inner1, split.share1);
+        let (_, inner2) = strip_label(&share2).unwrap();
+        assert_eq!(recover_secret(inner1, inner2).unwrap(), b"Hello, World!");
+        assert_eq!(
+            strip_label(&split.share1).unwrap(),
+            (None, &split.share1[..])
+        );
+    }
+
+    #[test]
+    fn test_split_for_custodians() {
+        let shares = split_for_custodians(b"Hello, World!", &["CFO", "CTO", "CEO"]).unwrap();
+        let roles: Vec<&str> = shares.iter().map(|(role, _)| role.as_str()).collect();
+        assert_eq!(roles, ["CFO", "CTO", "CEO"]);
+
+        let mut inner = Vec::new();
+        for (role, share) in &shares {
+            assert_eq!(share.len(), labeled_len(17, role));
+            let (label, share) = open_labeled(share).unwrap();
+            assert_eq!(label, role);
+            inner.push(share);
+        }
+        inner.reverse();
+        assert_eq!(recover_secret_n(&inner).unwrap(), b"Hello, World!");
+
+        assert_eq!(
+            split_for_custodians(b"secret", &["CFO"]),
+            Err(ShareError::TooFewShares)
+        );
+        assert_eq!(
+            split_for_custodians(b"secret", &["CFO", ""]),
+            Err(ShareError::InvalidLabel)
+        );
+        assert_eq!(
+            split_for_custodians(b"", &["CFO", "CTO"]),
+            Err(ShareError::EmptyInput)
+        );
+    }
+
+    #[test]
+    fn test_invalid_labels() {
+        for label in [
+            "",
+            "tab\there",
+            "line\nbreak",
+            &"x".repeat(MAX_LABEL_LEN + 1),
+        ] {
+            assert_eq!(label_share(b"share", label), Err(ShareError::InvalidLabel));
+        }
+        assert!(label_share(b"share", &"é".repeat(MAX_LABEL_LEN / 2)).is_ok());
+        assert_eq!(label_share(b"", "vault"), Err(ShareError::EmptyInput));
+
+        assert_eq!(open_labeled(b"XPL1"), Err(ShareError::InvalidLabel));
+        assert_eq!(
+            open_labeled(b"XPL1\x09short"),
+            Err(ShareError::InvalidLabel)
+        );
+        assert_eq!(
+            open_labeled(b"XPL1\x02\xff\xfeshare"),
+            Err(ShareError::InvalidLabel)
+        );
+        assert_eq!(open_labeled(b"plain"), Err(ShareError::InvalidLabel));
+    }
+}
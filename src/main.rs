@@ -1,7 +1,10 @@
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+// Unsafe code is denied except where it is allowed, and justified, item by item
+#![deny(unsafe_code)]
+
 use clap::{Parser, Subcommand};
-use std::io::{self, Read};
-use xplit::{recover_secret, split_secret};
+use std::process::ExitCode;
+
+mod cli;
 
 #[derive(Parser)]
 #[command(name = "xplit")]
@@ -14,57 +17,140 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Split a secret into two shares
-    Split {
-        /// Secret to split (if not provided, reads from stdin)
-        secret: Option<String>,
-    },
+    Split(cli::split::SplitArgs),
     /// Recover a secret from two shares
-    Recover {
-        /// First share (base64 encoded)
-        share1: String,
-        /// Second share (base64 encoded)
-        share2: String,
-    },
+    Recover(cli::recover::RecoverArgs),
+    /// Check a single share's integrity without recovering anything
+    Verify(cli::verify::VerifyArgs),
+    /// Check that two shares recover a secret with a known hash, without printing it
+    Compare(cli::compare::CompareArgs),
+    /// Diagnose a pair of shares that won't recover (never prints the secret)
+    Doctor(cli::doctor::DoctorArgs),
+    /// Print a short fingerprint labelling a pair of shares, without recovering anything
+    Fingerprint(cli::fingerprint::FingerprintArgs),
+    /// Record a pair of shares' digests in a manifest, to check them against later
+    Manifest(cli::manifest::ManifestArgs),
+    /// Check a pair of shares against a manifest written by `manifest`
+    VerifyManifest(cli::manifest::VerifyManifestArgs),
+    /// Re-randomize a pair of shares without reconstructing the secret
+    Rotate(cli::rotate::RotateArgs),
+    /// Show the recovery hint written by `split --hint`
+    Hint(cli::hint::HintArgs),
+    /// Generate an Ed25519 keypair for signing shares
+    Keygen(cli::keys::KeygenArgs),
+    /// Manage shares stored in the OS keychain (requires the `keyring` feature)
+    Keychain(cli::keychain::KeychainArgs),
+    /// Guided step-by-step split or recover, no flags needed
+    Interactive,
 }
 
-fn main() -> io::Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Commands::Split(args) => cli::split::run(args),
+        Commands::Recover(args) => cli::recover::run(&args),
+        Commands::Verify(args) => cli::verify::run(&args),
+        Commands::Compare(args) => cli::compare::run(&args),
+        Commands::Doctor(args) => cli::doctor::run(&args),
+        Commands::Fingerprint(args) => cli::fingerprint::run(&args),
+        Commands::Manifest(args) => cli::manifest::run(&args),
+        Commands::VerifyManifest(args) => cli::manifest::run_verify(&args),
+        Commands::Rotate(args) => cli::rotate::run(&args),
+        Commands::Hint(args) => cli::hint::run(&args),
+        Commands::Keygen(args) => cli::keys::run_keygen(&args),
+        Commands::Keychain(args) => cli::keychain::run(&args),
+        Commands::Interactive => cli::interactive::run(),
+    };
 
-    match cli.command {
-        Commands::Split { secret } => {
-            let secret_bytes = if let Some(s) = secret {
-                s.into_bytes()
-            } else {
-                let mut buffer = Vec::new();
-                io::stdin().read_to_end(&mut buffer)?;
-                buffer
-            };
+    match result {
+        Ok(code) => code,
+        // The reader of our output exited early, e.g. `xplit split | head`
+        Err(e) if cli::io::is_broken_pipe(&*e) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-            let shares = split_secret(&secret_bytes).expect("Failed to split secret");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            println!("Share 1: {}", BASE64.encode(&shares.share1));
-            println!("Share 2: {}", BASE64.encode(&shares.share2));
-        }
-        Commands::Recover { share1, share2 } => {
-            let share1_bytes = BASE64
-                .decode(share1)
-                .expect("Failed to decode share1 from base64");
-            let share2_bytes = BASE64
-                .decode(share2)
-                .expect("Failed to decode share2 from base64");
+    fn parses(args: &[&str]) -> bool {
+        Cli::try_parse_from(std::iter::once("xplit").chain(args.iter().copied())).is_ok()
+    }
 
-            let recovered =
-                recover_secret(&share1_bytes, &share2_bytes).expect("Failed to recover secret");
+    #[test]
+    fn test_positional_and_in_flag_conflict() {
+        assert!(!parses(&["split", "secret", "--in", "-"]));
+        assert!(!parses(&["recover", "a", "b", "--in1", "-"]));
+        assert!(!parses(&["verify", "a", "--in", "-"]));
+        assert!(!parses(&["doctor", "a", "b", "--in2", "-"]));
+        assert!(!parses(&["keygen", "key", "--out", "key"]));
+    }
 
-            match String::from_utf8(recovered.clone()) {
-                Ok(s) => println!("{s}"),
-                Err(_) => {
-                    // If not valid UTF-8, output as hex
-                    println!("Binary data (hex): {}", hex::encode(recovered));
-                }
-            }
-        }
+    #[test]
+    fn test_in_flags_replace_positionals() {
+        assert!(parses(&["split", "--in", "-", "--out", "shares.txt"]));
+        assert!(parses(&[
+            "recover", "--in1", "a", "--in2", "-", "--out", "-"
+        ]));
+        assert!(parses(&["recover", "a", "--in2", "b"]));
+        assert!(parses(&["verify", "--in", "-"]));
+        assert!(parses(&["verify", "--pair", "a", "b"]));
+        assert!(!parses(&["verify", "--pair", "a"]));
+        assert!(!parses(&["verify", "a", "--pair", "a", "b"]));
+        assert!(parses(&[
+            "doctor", "--in1", "a", "--in2", "b", "--out", "-"
+        ]));
+        assert!(parses(&["keygen", "--out", "key"]));
+    }
+
+    #[test]
+    fn test_missing_share_is_rejected() {
+        // A lone positional may hold both shares, so it is checked when run
+        assert!(parses(&["recover", "a"]));
+        assert!(!parses(&["recover", "--in1", "a"]));
+        assert!(!parses(&["verify"]));
+        assert!(!parses(&["split", "--out", "-", "--output", "prefix"]));
     }
 
-    Ok(())
+    #[test]
+    fn test_keyring_replaces_share_arguments() {
+        assert!(parses(&["split", "secret", "--keyring", "vault"]));
+        assert!(!parses(&[
+            "split",
+            "secret",
+            "--keyring",
+            "vault",
+            "-o",
+            "p"
+        ]));
+        assert!(parses(&["recover", "--keyring", "keychain:vault"]));
+        assert!(!parses(&["recover", "--keyring", "vault", "a", "b"]));
+        assert!(!parses(&["recover", "--keyring", ""]));
+    }
+
+    #[test]
+    fn test_clear_after_needs_to_clip() {
+        assert!(parses(&["recover", "a", "b", "--to-clip"]));
+        assert!(parses(&[
+            "recover",
+            "a",
+            "b",
+            "--to-clip",
+            "--clear-after",
+            "5"
+        ]));
+        assert!(!parses(&["recover", "a", "b", "--clear-after", "5"]));
+        assert!(!parses(&[
+            "recover",
+            "a",
+            "b",
+            "--to-clip",
+            "--clear-after",
+            "0"
+        ]));
+        assert!(!parses(&["recover", "a", "b", "--to-clip", "--out", "-"]));
+    }
 }
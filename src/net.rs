@@ -0,0 +1,362 @@
+//! A stateless splitting service over TCP
+//!
+//! One machine runs [`serve_split`]; clients send a secret with [`request_split`]
+//! and receive both shares back. Nothing is stored server-side: the received
+//! plaintext is zeroized as soon as the shares have been computed, and the
+//! shares, which together are as good as the secret, once they have been sent.
+//!
+//! # Wire format
+//!
+//! Every message is a single frame (integers big-endian):
+//!
+//! ```text
+//! magic "XPN1" (4) | kind (1) | length u32 (4) | payload (length bytes)
+//! ```
+//!
+//! | kind   | direction       | payload                                  |
+//! |--------|-----------------|------------------------------------------|
+//! | `0x01` | client → server | secret bytes                             |
+//! | `0x02` | server → client | `len1 u32` \| share1 \| share2           |
+//! | `0x03` | server → client | UTF-8 error message                      |
+//!
+//! Payloads larger than [`MAX_FRAME_LEN`] are rejected before any allocation.
+//! Both shares of a secret have to fit in one reply frame, so secrets longer
+//! than [`MAX_SECRET_LEN`] are answered with an error frame. One request is
+//! served per connection, each on its own thread, at most
+//! [`MAX_CONNECTIONS`] at a time, and a client that stalls for [`IO_TIMEOUT`]
+//! is dropped.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use zeroize::Zeroizing;
+
+use crate::stream::Splitter;
+use crate::{TwoShares, share_len};
+
+/// Magic prefix of every frame
+pub const FRAME_MAGIC: &[u8; 4] = b"XPN1";
+
+/// Largest payload accepted in a single frame (16 MiB)
+pub const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+/// Largest secret a request can hold, so that the reply fits in one frame
+///
+/// The reply is a 4-byte length and two shares, each the secret plus a 4-byte
+/// checksum.
+pub const MAX_SECRET_LEN: u32 = (MAX_FRAME_LEN - 12) / 2;
+
+/// Most connections [`serve_split`] handles at once
+///
+/// Each one can hold a full request frame and both shares in memory.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// How long [`serve_split`] waits on a silent client, per read or write
+pub const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+const KIND_SPLIT_REQUEST: u8 = 0x01;
+const KIND_SHARES: u8 = 0x02;
+const KIND_ERROR: u8 = 0x03;
+
+/// Accept connections forever, answering one split request on each
+///
+/// Each connection is handled on its own thread, with reads and writes timing
+/// out after [`IO_TIMEOUT`], so a client that connects and goes quiet only
+/// holds up itself. Once [`MAX_CONNECTIONS`] are being handled, no more are
+/// accepted until one finishes. A failing connection (bad frame, client
+/// hang-up, timeout) is dropped without stopping the server.
+///
+/// # Errors
+///
+/// Returns an error only if accepting on the listener itself fails.
+pub fn serve_split(listener: &TcpListener) -> io::Result<()> {
+    let slots = Slots::new(MAX_CONNECTIONS);
+    loop {
+        let slot = slots.acquire();
+        let (mut stream, _) = listener.accept()?;
+        thread::spawn(move || {
+            let _slot = slot;
+            stream.set_read_timeout(Some(IO_TIMEOUT))?;
+            stream.set_write_timeout(Some(IO_TIMEOUT))?;
+            handle_split(&mut stream)
+        });
+    }
+}
+
+/// Counting semaphore bounding the connections handled at once
+#[derive(Clone)]
+struct Slots(Arc<(Mutex<usize>, Condvar)>);
+
+/// A taken slot, given back when dropped
+struct Slot(Slots);
+
+impl Slots {
+    fn new(count: usize) -> Self {
+        Self(Arc::new((Mutex::new(count), Condvar::new())))
+    }
+
+    /// Wait for a free slot and take it
+    fn acquire(&self) -> Slot {
+        let (free, freed) = &*self.0;
+        let mut free = freed
+            .wait_while(
+                free.lock().unwrap_or_else(PoisonError::into_inner),
+                |free| *free == 0,
+            )
+            .unwrap_or_else(PoisonError::into_inner);
+        *free -= 1;
+        Slot(self.clone())
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let (free, freed) = &*(self.0).0;
+        *free.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        freed.notify_one();
+    }
+}
+
+/// Answer a single split request on an established stream
+///
+/// The secret goes through a [`Splitter`], and the secret and shares are
+/// zeroized once the reply is written.
+///
+/// # Errors
+///
+/// Returns an error if the request frame is malformed or the stream fails.
+/// Secrets the library refuses (e.g. empty ones) and secrets longer than
+/// [`MAX_SECRET_LEN`] are reported to the client as an error frame rather than
+/// returned here.
+pub fn handle_split<S: Read + Write>(stream: &mut S) -> io::Result<()> {
+    let (kind, payload) = read_frame(stream)?;
+    let secret = Zeroizing::new(payload);
+
+    if kind != KIND_SPLIT_REQUEST {
+        write_frame(stream, KIND_ERROR, b"expected a split request frame")?;
+        return Err(invalid_data("unexpected frame kind"));
+    }
+    if secret.len() > MAX_SECRET_LEN as usize {
+        let message = format!("secret exceeds {MAX_SECRET_LEN} bytes");
+        return write_frame(stream, KIND_ERROR, message.as_bytes());
+    }
+
+    let mut splitter = Splitter::new();
+    let (data1, data2) = splitter.push(&secret);
+    let (data1, data2) = (Zeroizing::new(data1), Zeroizing::new(data2));
+    match splitter.finish() {
+        Ok((crc1, crc2)) => {
+            let len = share_len(secret.len());
+            let share1_len =
+                u32::try_from(len).map_err(|_| invalid_data("share too large for frame"))?;
+            let mut payload = Zeroizing::new(Vec::with_capacity(4 + 2 * len));
+            payload.extend_from_slice(&share1_len.to_be_bytes());
+            payload.extend_from_slice(&data1);
+            payload.extend_from_slice(&crc1);
+            payload.extend_from_slice(&data2);
+            payload.extend_from_slice(&crc2);
+            write_frame(stream, KIND_SHARES, &payload)
+        }
+        Err(e) => write_frame(stream, KIND_ERROR, e.to_string().as_bytes()),
+    }
+}
+
+/// Send `secret` to a splitting service and return the resulting shares
+///
+/// # Errors
+///
+/// Returns an error if the connection fails, the server replies with an error
+/// frame, or the response is malformed.
+pub fn request_split<A: ToSocketAddrs>(addr: A, secret: &[u8]) -> io::Result<TwoShares> {
+    let mut stream = TcpStream::connect(addr)?;
+    write_frame(&mut stream, KIND_SPLIT_REQUEST, secret)?;
+
+    let (kind, payload) = read_frame(&mut stream)?;
+    match kind {
+        KIND_SHARES => {
+            let Some((len_bytes, body)) = payload.split_first_chunk::<4>() else {
+                return Err(invalid_data("shares frame too short"));
+            };
+            let share1_len = u32::from_be_bytes(*len_bytes) as usize;
+            if share1_len > body.len() {
+                return Err(invalid_data("shares frame length mismatch"));
+            }
+            let (share1, share2) = body.split_at(share1_len);
+            Ok(TwoShares {
+                share1: share1.to_vec(),
+                share2: share2.to_vec(),
+            })
+        }
+        KIND_ERROR => Err(io::Error::other(format!(
+            "server refused split: {}",
+            String::from_utf8_lossy(&payload)
+        ))),
+        _ => Err(invalid_data("unexpected frame kind")),
+    }
+}
+
+/// Write one frame
+fn write_frame<W: Write>(writer: &mut W, kind: u8, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|&len| len <= MAX_FRAME_LEN)
+        .ok_or_else(|| invalid_data("payload exceeds maximum frame length"))?;
+
+    writer.write_all(FRAME_MAGIC)?;
+    writer.write_all(&[kind])?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Read one frame, tolerating partial reads
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 9];
+    reader.read_exact(&mut header)?;
+
+    if &header[..4] != FRAME_MAGIC {
+        return Err(invalid_data("bad frame magic"));
+    }
+    let kind = header[4];
+    let len = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+    if len > MAX_FRAME_LEN {
+        return Err(invalid_data("frame exceeds maximum length"));
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok((kind, payload))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recover_secret;
+    use std::thread;
+
+    #[test]
+    fn test_request_split_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve_split(&listener));
+
+        let shares = request_split(addr, b"Hello, World!").unwrap();
+        assert_eq!(
+            recover_secret(&shares.share1, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_silent_client_does_not_block_others() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve_split(&listener));
+
+        let _silent = TcpStream::connect(addr).unwrap();
+        let shares = request_split(addr, b"Hello, World!").unwrap();
+        assert_eq!(
+            recover_secret(&shares.share1, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_empty_secret_returns_error_frame() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve_split(&listener));
+
+        let err = request_split(addr, b"").unwrap_err();
+        assert!(err.to_string().contains("empty"));
+    }
+
+    #[test]
+    fn test_secret_limit_keeps_reply_in_one_frame() {
+        // A stream reading from a request and collecting the reply
+        struct Exchange(io::Cursor<Vec<u8>>, Vec<u8>);
+        impl Read for Exchange {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.0.read(buf)
+            }
+        }
+        impl Write for Exchange {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.1.write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let reply_kind = |len: u32| {
+            let mut request = Vec::new();
+            write_frame(&mut request, KIND_SPLIT_REQUEST, &vec![7; len as usize]).unwrap();
+            let mut exchange = Exchange(io::Cursor::new(request), Vec::new());
+            handle_split(&mut exchange).unwrap();
+            let (kind, payload) = read_frame(&mut exchange.1.as_slice()).unwrap();
+            (kind, payload.len())
+        };
+        assert_eq!(
+            reply_kind(MAX_SECRET_LEN),
+            (KIND_SHARES, MAX_FRAME_LEN as usize)
+        );
+        assert_eq!(reply_kind(MAX_SECRET_LEN + 1).0, KIND_ERROR);
+    }
+
+    #[test]
+    fn test_slots_bound_concurrent_holders() {
+        let slots = Slots::new(1);
+        let held = slots.acquire();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let waiting = slots.clone();
+        thread::spawn(move || {
+            let _slot = waiting.acquire();
+            tx.send(()).unwrap();
+        });
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+        drop(held);
+        rx.recv_timeout(IO_TIMEOUT).unwrap();
+    }
+
+    #[test]
+    fn test_read_frame_handles_partial_reads() {
+        // A reader that hands out one byte at a time
+        struct Trickle(Vec<u8>, usize);
+        impl Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.1 >= self.0.len() || buf.is_empty() {
+                    return Ok(0);
+                }
+                buf[0] = self.0[self.1];
+                self.1 += 1;
+                Ok(1)
+            }
+        }
+
+        let mut frame = Vec::new();
+        write_frame(&mut frame, KIND_SPLIT_REQUEST, b"secret").unwrap();
+        let (kind, payload) = read_frame(&mut Trickle(frame, 0)).unwrap();
+        assert_eq!(kind, KIND_SPLIT_REQUEST);
+        assert_eq!(payload, b"secret");
+    }
+
+    #[test]
+    fn test_read_frame_rejects_oversized_length() {
+        let mut frame = FRAME_MAGIC.to_vec();
+        frame.push(KIND_SPLIT_REQUEST);
+        frame.extend_from_slice(&(MAX_FRAME_LEN + 1).to_be_bytes());
+
+        let err = read_frame(&mut frame.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,234 @@
+//! Length-hiding padding
+//!
+//! A share is exactly as long as its secret, so a share on its own reveals the
+//! secret's length. Padding the secret before splitting rounds that up to a
+//! multiple of a block size, or to the next power of two, so only the rounded
+//! length shows.
+//!
+//! Padding hides the exact length, not the size class: a 16-byte block still
+//! tells a password from a key file, and power-of-two rounding still reveals
+//! the length to within a factor of two. Anyone holding both shares recovers
+//! the exact secret, as usual.
+//!
+//! Layout (integers big-endian, see [`crate::format`]):
+//!
+//! ```text
+//! magic "XPD1" | block u32 (0 for power of two) | share
+//! ```
+//!
+//! The inner share is a plain share of the padded secret: the secret, a 0x80
+//! marker, then zeros up to the rounded length (ISO/IEC 7816-4 padding).
+
+use crate::format::read_u32_be;
+use crate::{ShareError, TwoShares, recover_secret, share_len, split_secret};
+
+/// Magic prefix identifying a padded share
+pub const PADDED_MAGIC: &[u8; 4] = b"XPD1";
+
+/// Largest accepted block size (1 MiB)
+pub const MAX_PAD_BLOCK: usize = 1 << 20;
+
+const HEADER_LEN: usize = PADDED_MAGIC.len() + 4;
+const MARKER: u8 = 0x80;
+
+/// How the secret length is rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Round up to a multiple of this many bytes
+    Block(usize),
+    /// Round up to the next power of two
+    PowerOfTwo,
+}
+
+impl Padding {
+    /// Length of the padded secret for a secret of `secret_len` bytes
+    ///
+    /// Always longer than `secret_len`, since the marker byte is always added.
+    /// A block size must be nonzero.
+    #[must_use]
+    pub const fn padded_len(self, secret_len: usize) -> usize {
+        match self {
+            Self::Block(block) => (secret_len / block + 1) * block,
+            Self::PowerOfTwo => (secret_len + 1).next_power_of_two(),
+        }
+    }
+
+    fn header_block(self) -> u32 {
+        match self {
+            // Checked against MAX_PAD_BLOCK before use
+            Self::Block(block) => u32::try_from(block).unwrap_or(u32::MAX),
+            Self::PowerOfTwo => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for Padding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Block(block) => write!(f, "block {block}"),
+            Self::PowerOfTwo => write!(f, "power of two"),
+        }
+    }
+}
+
+/// Returns `true` if `data` starts with the padded-share magic
+#[must_use]
+pub fn is_padded(data: &[u8]) -> bool {
+    data.starts_with(PADDED_MAGIC)
+}
+
+/// Length of each share [`split_padded`] produces for a secret of `secret_len` bytes
+#[must_use]
+pub const fn padded_share_len(secret_len: usize, padding: Padding) -> usize {
+    HEADER_LEN + share_len(padding.padded_len(secret_len))
+}
+
+/// Pad a secret and split it into 2 shares that record the padding
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the secret is empty
+/// - [`ShareError::InvalidPadBlock`] if a block size is 0 or above [`MAX_PAD_BLOCK`]
+pub fn split_padded(secret: &[u8], padding: Padding) -> Result<TwoShares, ShareError> {
+    if let Padding::Block(block) = padding
+        && !(1..=MAX_PAD_BLOCK).contains(&block)
+    {
+        return Err(ShareError::InvalidPadBlock);
+    }
+    if secret.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+
+    let mut padded = Vec::with_capacity(padding.padded_len(secret.len()));
+    padded.extend_from_slice(secret);
+    padded.push(MARKER);
+    padded.resize(padding.padded_len(secret.len()), 0);
+    let shares = split_secret(&padded);
+    wipe(&mut padded);
+    let shares = shares?;
+
+    let wrap = |share: Vec<u8>| {
+        let mut wrapped = Vec::with_capacity(HEADER_LEN + share.len());
+        wrapped.extend_from_slice(PADDED_MAGIC);
+        wrapped.extend_from_slice(&padding.header_block().to_be_bytes());
+        wrapped.extend_from_slice(&share);
+        wrapped
+    };
+    Ok(TwoShares {
+        share1: wrap(shares.share1),
+        share2: wrap(shares.share2),
+    })
+}
+
+/// Read the padding header, returning the padding and the inner share
+///
+/// The inner share's checksum is not checked here.
+///
+/// # Errors
+///
+/// Returns [`ShareError::InvalidPadding`] if the header is missing or malformed.
+pub fn open_padded(share: &[u8]) -> Result<(Padding, &[u8]), ShareError> {
+    if !is_padded(share) {
+        return Err(ShareError::InvalidPadding);
+    }
+    let block = read_u32_be(share, PADDED_MAGIC.len()).map_err(|_| ShareError::InvalidPadding)?;
+    let padding = match block as usize {
+        0 => Padding::PowerOfTwo,
+        block if block <= MAX_PAD_BLOCK => Padding::Block(block),
+        _ => return Err(ShareError::InvalidPadding),
+    };
+    Ok((padding, &share[HEADER_LEN..]))
+}
+
+/// Recover the secret from two padded shares and strip the padding
+///
+/// # Errors
+///
+/// Returns:
+/// - the errors of [`recover_secret`] for the inner shares
+/// - [`ShareError::InvalidPadding`] if a header is malformed, the shares record
+///   different padding, or the recovered secret isn't padded as recorded
+pub fn recover_padded(share1: &[u8], share2: &[u8]) -> Result<Vec<u8>, ShareError> {
+    let (padding1, inner1) = open_padded(share1)?;
+    let (padding2, inner2) = open_padded(share2)?;
+    if padding1 != padding2 {
+        return Err(ShareError::InvalidPadding);
+    }
+
+    let mut secret = recover_secret(inner1, inner2)?;
+    let end = secret.iter().rposition(|&b| b != 0);
+    match end {
+        Some(end) if secret[end] == MARKER && padding1.padded_len(end) == secret.len() => {
+            secret.truncate(end);
+            Ok(secret)
+        }
+        _ => {
+            wipe(&mut secret);
+            Err(ShareError::InvalidPadding)
+        }
+    }
+}
+
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+    #[cfg(not(feature = "zeroize"))]
+    bytes.fill(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padded_roundtrip_hides_length() {
+        for secret in [&b"a"[..], b"Hello, World!", b"exactly sixteen!"] {
+            let shares = split_padded(secret, Padding::Block(16)).unwrap();
+            assert_eq!(
+                shares.share1.len(),
+                padded_share_len(secret.len(), Padding::Block(16))
+            );
+            assert_eq!(
+                recover_padded(&shares.share1, &shares.share2).unwrap(),
+                secret
+            );
+        }
+        let short = split_padded(b"a", Padding::Block(16)).unwrap();
+        let long = split_padded(b"Hello, World!", Padding::Block(16)).unwrap();
+        assert_eq!(short.share1.len(), long.share1.len());
+    }
+
+    #[test]
+    fn test_power_of_two_padding() {
+        assert_eq!(Padding::PowerOfTwo.padded_len(13), 16);
+        assert_eq!(Padding::PowerOfTwo.padded_len(16), 32);
+
+        let shares = split_padded(b"Hello, World!", Padding::PowerOfTwo).unwrap();
+        assert_eq!(open_padded(&shares.share2).unwrap().0, Padding::PowerOfTwo);
+        assert_eq!(
+            recover_padded(&shares.share1, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_invalid_padding() {
+        assert_eq!(
+            split_padded(b"secret", Padding::Block(0)).err(),
+            Some(ShareError::InvalidPadBlock)
+        );
+        assert_eq!(
+            split_padded(b"secret", Padding::Block(MAX_PAD_BLOCK + 1)).err(),
+            Some(ShareError::InvalidPadBlock)
+        );
+
+        let block = split_padded(b"secret", Padding::Block(16)).unwrap();
+        let pow2 = split_padded(b"secret", Padding::PowerOfTwo).unwrap();
+        assert_eq!(
+            recover_padded(&block.share1, &pow2.share2),
+            Err(ShareError::InvalidPadding)
+        );
+        assert_eq!(open_padded(b"plain"), Err(ShareError::InvalidPadding));
+    }
+}
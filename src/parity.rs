@@ -0,0 +1,284 @@
+//! 2-of-3 shares by XOR parity
+//!
+//! The secret is split into three pieces that XOR back to it: two random pads
+//! and the secret XOR both pads. Each share holds the two pieces other than
+//! its own number, so any two shares hold all three pieces between them, while
+//! one share alone is missing a piece that could be anything. Each share is
+//! twice the secret's length.
+//!
+//! Shares record their number and a random split id, so recovery takes any two
+//! in any order and tells shares from different splits apart from corrupted
+//! ones.
+//!
+//! Layout (see [`crate::format`]):
+//!
+//! ```text
+//! magic "XPT1" | number u8 (1-3) | split id [16] | piece | piece | crc32
+//! ```
+//!
+//! The pieces are the two the share holds, lowest number first, and the CRC32
+//! (as in [`crate::seal`]) covers every byte before it.
+
+use rand::RngCore;
+
+use crate::{ShareError, rng, seal, unseal, xor_into};
+
+/// Magic prefix identifying a 2-of-3 share
+pub const PARITY_MAGIC: &[u8; 4] = b"XPT1";
+
+/// Length of a split id
+pub const SPLIT_ID_LEN: usize = 16;
+
+const HEADER_LEN: usize = PARITY_MAGIC.len() + 1 + SPLIT_ID_LEN;
+
+/// Three shares of a 2-of-3 split; any two recover the secret
+#[derive(Debug)]
+pub struct ThreeShares {
+    pub share1: Vec<u8>,
+    pub share2: Vec<u8>,
+    pub share3: Vec<u8>,
+}
+
+/// A 2-of-3 share's header, read without its pieces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParityHeader {
+    /// The share's number, 1 to 3
+    pub number: u8,
+    /// Random id, the same in all three shares of a split
+    pub split_id: [u8; SPLIT_ID_LEN],
+}
+
+/// Returns `true` if `data` starts with the 2-of-3 share magic
+#[must_use]
+pub fn is_parity(data: &[u8]) -> bool {
+    data.starts_with(PARITY_MAGIC)
+}
+
+/// Length of each share [`split_2of3`] produces for a secret of `secret_len` bytes
+#[must_use]
+pub const fn parity_share_len(secret_len: usize) -> usize {
+    HEADER_LEN + 2 * secret_len + 4
+}
+
+/// Split a secret into 3 shares, any 2 of which recover it
+///
+/// # Errors
+///
+/// Returns [`ShareError::EmptyInput`] if the secret is empty.
+pub fn split_2of3(secret: &[u8]) -> Result<ThreeShares, ShareError> {
+    rng::with_default_rng(|rng| split_2of3_with_rng(secret, rng))
+}
+
+/// [`split_2of3`], drawing the pads and split id from a caller-supplied RNG
+///
+/// # Errors
+///
+/// Returns [`ShareError::EmptyInput`] if the secret is empty.
+pub fn split_2of3_with_rng<R: RngCore + ?Sized>(
+    secret: &[u8],
+    rng: &mut R,
+) -> Result<ThreeShares, ShareError> {
+    if secret.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+
+    let mut split_id = [0u8; SPLIT_ID_LEN];
+    rng.fill_bytes(&mut split_id);
+    let mut pieces = [
+        vec![0u8; secret.len()],
+        vec![0u8; secret.len()],
+        secret.to_vec(),
+    ];
+    rng.fill_bytes(&mut pieces[0]);
+    rng.fill_bytes(&mut pieces[1]);
+    let [pad1, pad2, last] = &mut pieces;
+    xor_into(last, pad1);
+    xor_into(last, pad2);
+
+    let share = |number: u8| {
+        let mut data = Vec::with_capacity(parity_share_len(secret.len()));
+        data.extend_from_slice(PARITY_MAGIC);
+        data.push(number);
+        data.extend_from_slice(&split_id);
+        for (piece, held) in (1..=3).zip(&pieces) {
+            if piece != number {
+                data.extend_from_slice(held);
+            }
+        }
+        let sealed = seal(&data);
+        wipe(&mut data);
+        sealed
+    };
+    let shares = ThreeShares {
+        share1: share(1),
+        share2: share(2),
+        share3: share(3),
+    };
+    for piece in &mut pieces {
+        wipe(piece);
+    }
+    Ok(shares)
+}
+
+/// Read a 2-of-3 share's number and split id
+///
+/// The share's checksum is not checked here.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::UnsupportedFormat`] if the share doesn't start with
+///   [`PARITY_MAGIC`] or its number isn't 1 to 3
+/// - [`ShareError::ShareTooShort`] if its header is cut off
+pub fn parity_header(share: &[u8]) -> Result<ParityHeader, ShareError> {
+    if !is_parity(share) {
+        return Err(ShareError::UnsupportedFormat);
+    }
+    let header = share.get(..HEADER_LEN).ok_or(ShareError::ShareTooShort)?;
+    let number = header[PARITY_MAGIC.len()];
+    if !(1..=3).contains(&number) {
+        return Err(ShareError::UnsupportedFormat);
+    }
+    let mut split_id = [0u8; SPLIT_ID_LEN];
+    split_id.copy_from_slice(&header[PARITY_MAGIC.len() + 1..]);
+    Ok(ParityHeader { number, split_id })
+}
+
+/// Recover the secret from any 2 shares of a [`split_2of3`], in either order
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::InvalidChecksum`] or [`ShareError::ShareTooShort`] if a
+///   share is damaged
+/// - [`ShareError::UnsupportedFormat`] if a share isn't a 2-of-3 share
+/// - [`ShareError::DifferentSplits`] if the shares have different split ids
+/// - [`ShareError::TooFewShares`] if both are the same share
+/// - [`ShareError::LengthMismatch`] if the shares differ in length
+pub fn recover_2of3(a: &[u8], b: &[u8]) -> Result<Vec<u8>, ShareError> {
+    let (first, mut pieces1) = open(a)?;
+    let (second, mut pieces2) = open(b)?;
+    let checked = if first.split_id != second.split_id {
+        Err(ShareError::DifferentSplits)
+    } else if first.number == second.number {
+        Err(ShareError::TooFewShares)
+    } else if pieces1.len() != pieces2.len() {
+        Err(ShareError::LengthMismatch)
+    } else {
+        Ok(())
+    };
+    let secret = checked.map(|()| {
+        // The first share holds every piece but its own, which the second
+        // holds next to the piece numbered neither
+        let half = pieces1.len() / 2;
+        let other = 6 - first.number - second.number;
+        let at = if first.number < other { 0 } else { half };
+        let mut secret = pieces1[..half].to_vec();
+        xor_into(&mut secret, &pieces1[half..]);
+        xor_into(&mut secret, &pieces2[at..at + half]);
+        secret
+    });
+    wipe(&mut pieces1);
+    wipe(&mut pieces2);
+    secret
+}
+
+/// Check a share and return its header and the two pieces it holds
+fn open(share: &[u8]) -> Result<(ParityHeader, Vec<u8>), ShareError> {
+    if !is_parity(share) {
+        return Err(ShareError::UnsupportedFormat);
+    }
+    let mut data = unseal(share)?;
+    let header = parity_header(&data);
+    let pieces = data.split_off(HEADER_LEN.min(data.len()));
+    wipe(&mut data);
+    let header = header?;
+    if pieces.is_empty() || pieces.len() % 2 != 0 {
+        return Err(ShareError::ShareTooShort);
+    }
+    Ok((header, pieces))
+}
+
+fn wipe(bytes: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(bytes);
+    #[cfg(not(feature = "zeroize"))]
+    bytes.fill(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    /// Secret and shares also used by the wasm tests, one per line
+    const FIXTURE: &str = include_str!("../tests/fixtures/parity.txt");
+
+    fn fixture() -> (Vec<u8>, [Vec<u8>; 3]) {
+        let mut lines = FIXTURE.lines();
+        let secret = lines.next().unwrap().as_bytes().to_vec();
+        let shares = [(); 3].map(|()| STANDARD.decode(lines.next().unwrap()).unwrap());
+        (secret, shares)
+    }
+
+    #[test]
+    fn test_every_pair_of_the_fixture_recovers() {
+        let (secret, shares) = fixture();
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            assert_eq!(recover_2of3(&shares[i], &shares[j]).unwrap(), secret);
+            assert_eq!(recover_2of3(&shares[j], &shares[i]).unwrap(), secret);
+        }
+        for (number, share) in (1..=3).zip(&shares) {
+            assert_eq!(parity_header(share).unwrap().number, number);
+            assert_eq!(share.len(), parity_share_len(secret.len()));
+        }
+    }
+
+    #[test]
+    fn test_split_2of3_roundtrip() {
+        let shares = split_2of3(b"Hello, World!").unwrap();
+        let shares = [&shares.share1, &shares.share2, &shares.share3];
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            assert_eq!(
+                recover_2of3(shares[i], shares[j]).unwrap(),
+                b"Hello, World!"
+            );
+        }
+        let id = parity_header(shares[0]).unwrap().split_id;
+        assert!(
+            shares
+                .iter()
+                .all(|s| parity_header(s).unwrap().split_id == id)
+        );
+        assert_eq!(split_2of3(b"").unwrap_err(), ShareError::EmptyInput);
+    }
+
+    #[test]
+    fn test_different_splits_are_told_apart_from_damage() {
+        let (_, shares) = fixture();
+        let other = split_2of3(b"Hello, World!").unwrap();
+        assert_eq!(
+            recover_2of3(&shares[0], &other.share2).unwrap_err(),
+            ShareError::DifferentSplits
+        );
+
+        let mut damaged = shares[1].clone();
+        damaged[HEADER_LEN] ^= 1;
+        assert_eq!(
+            recover_2of3(&shares[0], &damaged).unwrap_err(),
+            ShareError::InvalidChecksum
+        );
+        assert_eq!(
+            recover_2of3(&shares[0], &shares[0]).unwrap_err(),
+            ShareError::TooFewShares
+        );
+        assert_eq!(
+            recover_2of3(&shares[0], b"plain share").unwrap_err(),
+            ShareError::UnsupportedFormat
+        );
+        assert_eq!(
+            recover_2of3(&shares[0], &seal(b"XPT1\x02")).unwrap_err(),
+            ShareError::ShareTooShort
+        );
+    }
+}
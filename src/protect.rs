@@ -0,0 +1,436 @@
+//! Passphrase-protected shares
+//!
+//! Wraps a complete share (payload + CRC32) in an Argon2id + XChaCha20-Poly1305
+//! envelope so a share file at rest is useless without its custodian's passphrase.
+//!
+//! Layout (integers big-endian, see [`crate::format`]):
+//!
+//! ```text
+//! magic "XPP1" | m_cost u32 | t_cost u32 | p_cost u32 | salt [16] | check [8] | nonce [24] | ciphertext
+//! ```
+//!
+//! The KDF output is split into the encryption key and a short passphrase check
+//! value. Comparing the check value first is what lets [`unprotect_share`] tell a
+//! wrong passphrase apart from a corrupted envelope.
+//!
+//! The costs are read from the envelope before the passphrase can be checked,
+//! so an envelope asking for more than [`MAX_MEMORY_KIB`], [`MAX_ITERATIONS`]
+//! or [`MAX_PARALLELISM`] is refused as corrupted instead of derived.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use zeroize::Zeroizing;
+
+use crate::format::read_u32_be;
+use crate::{ShareError, TwoShares, recover_secret, rng, split_secret};
+
+/// Magic prefix identifying a protected share
+pub const PROTECTED_MAGIC: &[u8; 4] = b"XPP1";
+
+const SALT_LEN: usize = 16;
+const CHECK_LEN: usize = 8;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = PROTECTED_MAGIC.len() + 12 + SALT_LEN + CHECK_LEN + NONCE_LEN;
+/// Poly1305 authentication tag appended to the ciphertext
+const TAG_LEN: usize = 16;
+
+/// Most memory an envelope may use, in KiB (1 GiB)
+pub const MAX_MEMORY_KIB: u32 = 1 << 20;
+/// Most passes over the memory an envelope may use
+pub const MAX_ITERATIONS: u32 = 16;
+/// Most lanes an envelope may use
+pub const MAX_PARALLELISM: u32 = 16;
+
+/// Returns `true` if `data` starts with the protected-share magic
+#[must_use]
+pub fn is_protected(data: &[u8]) -> bool {
+    data.starts_with(PROTECTED_MAGIC)
+}
+
+/// Length of the protected envelope [`protect_share`] produces for a share of `share_len` bytes
+#[must_use]
+pub const fn protected_len(share_len: usize) -> usize {
+    HEADER_LEN + share_len + TAG_LEN
+}
+
+/// Argon2id cost parameters for deriving the key from a passphrase
+///
+/// The defaults are argon2's own (19 MiB, 2 passes, 1 lane). Slower devices may
+/// need less memory; every envelope records the parameters it was made with,
+/// so opening one never needs them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Argon2 parameters for these costs, if argon2 accepts them and they are
+    /// within the maximums
+    fn to_argon2(self) -> Result<Params, ShareError> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            return Err(ShareError::InvalidKdfParams);
+        }
+        Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LEN + CHECK_LEN),
+        )
+        .map_err(|_| ShareError::InvalidKdfParams)
+    }
+}
+
+/// Encrypt a share under `passphrase` using the default Argon2id cost parameters
+///
+/// # Errors
+///
+/// Returns [`ShareError::EmptyInput`] if the share is empty.
+pub fn protect_share(share: &[u8], passphrase: &str) -> Result<Vec<u8>, ShareError> {
+    protect_share_with(share, passphrase, KdfParams::default())
+}
+
+/// Encrypt a share under `passphrase` with the given Argon2id cost parameters
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the share is empty
+/// - [`ShareError::InvalidKdfParams`] if argon2 rejects the parameters or they
+///   exceed the maximums
+pub fn protect_share_with(
+    share: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>, ShareError> {
+    if share.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+    seal_envelope(PROTECTED_MAGIC, share, passphrase, params)
+}
+
+/// Decrypt a protected share, returning the inner share (payload + CRC32)
+///
+/// The inner share's checksum is verified as well, so a successful result is
+/// ready to pass to [`crate::recover_secret`].
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::WrongPassphrase`] if the passphrase does not match
+/// - [`ShareError::CorruptedProtectedShare`] if the envelope is malformed or fails authentication
+/// - [`ShareError::InvalidChecksum`] if the decrypted share fails its CRC32 check
+pub fn unprotect_share(protected: &[u8], passphrase: &str) -> Result<Vec<u8>, ShareError> {
+    let share = open_envelope(PROTECTED_MAGIC, protected, passphrase)?;
+    crate::unseal(&share)?;
+    Ok(share)
+}
+
+/// Encrypt `plaintext` into an envelope starting with `magic`
+///
+/// The layout is the one in the module docs, whatever the magic.
+pub(crate) fn seal_envelope(
+    magic: &[u8],
+    plaintext: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+) -> Result<Vec<u8>, ShareError> {
+    let params = params.to_argon2()?;
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng::with_default_rng(|rng| {
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+    });
+
+    let (key, check) = derive(passphrase, &salt, &params)?;
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(magic);
+    header.extend_from_slice(&params.m_cost().to_be_bytes());
+    header.extend_from_slice(&params.t_cost().to_be_bytes());
+    header.extend_from_slice(&params.p_cost().to_be_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&*check);
+    header.extend_from_slice(&nonce);
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&*key));
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| ShareError::CorruptedProtectedShare)?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// The Argon2id costs an envelope (a protected share or a bundle) asks for
+///
+/// Lets a caller short on memory check it can afford [`unprotect_share`]
+/// before calling it.
+///
+/// # Errors
+///
+/// Returns [`ShareError::CorruptedProtectedShare`] if the header is cut off or
+/// the costs are ones xplit would never have written: rejected by argon2, or
+/// over the maximums.
+pub fn envelope_params(envelope: &[u8]) -> Result<KdfParams, ShareError> {
+    let field =
+        |offset| read_u32_be(envelope, offset).map_err(|_| ShareError::CorruptedProtectedShare);
+    let params = KdfParams {
+        memory_kib: field(4)?,
+        iterations: field(8)?,
+        parallelism: field(12)?,
+    };
+    params
+        .to_argon2()
+        .map(|_| params)
+        .map_err(|_| ShareError::CorruptedProtectedShare)
+}
+
+/// Decrypt an envelope made by [`seal_envelope`] with the same `magic`
+///
+/// Fails with [`ShareError::WrongPassphrase`] or
+/// [`ShareError::CorruptedProtectedShare`]; callers rename the latter for
+/// their own format.
+pub(crate) fn open_envelope(
+    magic: &[u8],
+    envelope: &[u8],
+    passphrase: &str,
+) -> Result<Vec<u8>, ShareError> {
+    if !envelope.starts_with(magic) || envelope.len() <= HEADER_LEN {
+        return Err(ShareError::CorruptedProtectedShare);
+    }
+
+    let (header, ciphertext) = envelope.split_at(HEADER_LEN);
+    let params = envelope_params(header)?
+        .to_argon2()
+        .map_err(|_| ShareError::CorruptedProtectedShare)?;
+
+    let salt = &header[16..16 + SALT_LEN];
+    let stored_check = &header[16 + SALT_LEN..16 + SALT_LEN + CHECK_LEN];
+    let nonce = &header[16 + SALT_LEN + CHECK_LEN..];
+
+    let (key, check) = derive(passphrase, salt, &params)?;
+    if check[..] != *stored_check {
+        return Err(ShareError::WrongPassphrase);
+    }
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&*key));
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| ShareError::CorruptedProtectedShare)
+}
+
+/// Split a secret and protect each share under its own passphrase
+///
+/// Each share gets its own salt and nonce in its envelope, so one custodian's
+/// passphrase reveals nothing about the other's share.
+///
+/// # Errors
+///
+/// Returns [`ShareError::EmptyInput`] if the secret is empty.
+pub fn split_secret_per_share_pass(
+    secret: &[u8],
+    pass1: &str,
+    pass2: &str,
+) -> Result<TwoShares, ShareError> {
+    let shares = split_secret(secret)?;
+    Ok(TwoShares {
+        share1: protect_share(&shares.share1, pass1)?,
+        share2: protect_share(&shares.share2, pass2)?,
+    })
+}
+
+/// Recover a secret from two shares made by [`split_secret_per_share_pass`]
+///
+/// # Errors
+///
+/// Returns the errors of [`unprotect_share`] for either share, then those of
+/// [`recover_secret`].
+pub fn recover_secret_per_share_pass(
+    share1: &[u8],
+    share2: &[u8],
+    pass1: &str,
+    pass2: &str,
+) -> Result<Vec<u8>, ShareError> {
+    let share1 = unprotect_share(share1, pass1)?;
+    let share2 = unprotect_share(share2, pass2)?;
+    recover_secret(&share1, &share2)
+}
+
+/// Encryption key derived from a passphrase, wiped on drop
+type DerivedKey = Zeroizing<[u8; KEY_LEN]>;
+
+/// Derive the encryption key and passphrase check value
+fn derive(
+    passphrase: &str,
+    salt: &[u8],
+    params: &Params,
+) -> Result<(DerivedKey, Zeroizing<[u8; CHECK_LEN]>), ShareError> {
+    let params = Params::new(
+        params.m_cost(),
+        params.t_cost(),
+        params.p_cost(),
+        Some(KEY_LEN + CHECK_LEN),
+    )
+    .map_err(|_| ShareError::CorruptedProtectedShare)?;
+
+    let mut output = Zeroizing::new([0u8; KEY_LEN + CHECK_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut *output)
+        .map_err(|_| ShareError::CorruptedProtectedShare)?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    let mut check = Zeroizing::new([0u8; CHECK_LEN]);
+    key.copy_from_slice(&output[..KEY_LEN]);
+    check.copy_from_slice(&output[KEY_LEN..]);
+    Ok((key, check))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protect_roundtrip() {
+        let shares = split_secret(b"Hello, World!").unwrap();
+        let protected = protect_share(&shares.share1, "alice").unwrap();
+
+        assert!(is_protected(&protected));
+        assert_eq!(protected.len(), protected_len(shares.share1.len()));
+        let unlocked = unprotect_share(&protected, "alice").unwrap();
+        assert_eq!(unlocked, shares.share1);
+        assert_eq!(
+            recover_secret(&unlocked, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let shares = split_secret(b"secret").unwrap();
+        let protected = protect_share(&shares.share2, "bob").unwrap();
+
+        assert_eq!(
+            unprotect_share(&protected, "mallory"),
+            Err(ShareError::WrongPassphrase)
+        );
+    }
+
+    #[test]
+    fn test_corrupted_ciphertext() {
+        let shares = split_secret(b"secret").unwrap();
+        let mut protected = protect_share(&shares.share1, "alice").unwrap();
+        let last = protected.len() - 1;
+        protected[last] ^= 0x01;
+
+        assert_eq!(
+            unprotect_share(&protected, "alice"),
+            Err(ShareError::CorruptedProtectedShare)
+        );
+    }
+
+    #[test]
+    fn test_truncated_envelope() {
+        assert_eq!(
+            unprotect_share(b"XPP1short", "alice"),
+            Err(ShareError::CorruptedProtectedShare)
+        );
+        assert!(!is_protected(b"plain share"));
+    }
+
+    #[test]
+    fn test_kdf_params() {
+        let shares = split_secret(b"secret").unwrap();
+        let cheap = KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let protected = protect_share_with(&shares.share1, "alice", cheap).unwrap();
+        assert_eq!(read_u32_be(&protected, 4), Ok(64));
+        assert_eq!(unprotect_share(&protected, "alice").unwrap(), shares.share1);
+
+        let too_little = KdfParams {
+            memory_kib: 1,
+            ..cheap
+        };
+        assert_eq!(
+            protect_share_with(&shares.share1, "alice", too_little),
+            Err(ShareError::InvalidKdfParams)
+        );
+    }
+
+    #[test]
+    fn test_crafted_costs_are_refused_before_deriving() {
+        let shares = split_secret(b"secret").unwrap();
+        let protected = protect_share(&shares.share1, "alice").unwrap();
+        assert_eq!(envelope_params(&protected), Ok(KdfParams::default()));
+
+        for (offset, cost) in [
+            (4, MAX_MEMORY_KIB + 1),
+            (8, u32::MAX),
+            (12, MAX_PARALLELISM + 1),
+        ] {
+            let mut crafted = protected.clone();
+            crafted[offset..offset + 4].copy_from_slice(&cost.to_be_bytes());
+            assert_eq!(
+                unprotect_share(&crafted, "alice"),
+                Err(ShareError::CorruptedProtectedShare)
+            );
+        }
+        let too_many_passes = KdfParams {
+            iterations: MAX_ITERATIONS + 1,
+            ..KdfParams::default()
+        };
+        assert_eq!(
+            protect_share_with(&shares.share1, "alice", too_many_passes),
+            Err(ShareError::InvalidKdfParams)
+        );
+    }
+
+    #[test]
+    fn test_per_share_passphrases() {
+        let shares = split_secret_per_share_pass(b"Hello, World!", "alice", "bob").unwrap();
+        assert!(is_protected(&shares.share1) && is_protected(&shares.share2));
+        assert_eq!(
+            recover_secret_per_share_pass(&shares.share1, &shares.share2, "alice", "bob").unwrap(),
+            b"Hello, World!"
+        );
+        assert_eq!(
+            recover_secret_per_share_pass(&shares.share1, &shares.share2, "bob", "alice"),
+            Err(ShareError::WrongPassphrase)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Swapping the RNG behind the entry points that don't take one
+//!
+//! [`crate::split_secret`], [`crate::split_secret_raw`],
+//! [`crate::split_secret_n`] and [`crate::refresh_shares`] (and the formats
+//! built on `split_secret`: padded, chunked and checksum-placed shares), as
+//! well as [`crate::stream::split_stream`], [`crate::stream::Splitter::new`]
+//! and the passphrase salts and nonces of `protect`, draw their randomness
+//! from `rand::thread_rng()`. [`set_default_rng`] replaces that source, e.g.
+//! with a hardware RNG or a seeded generator in a test harness, without
+//! passing it to every call. The `*_with_rng` functions always use the RNG
+//! they are given.
+//!
+//! The setting is **per thread**: it applies to calls on the thread that set
+//! it and nowhere else, so no locking is involved and one test's RNG can't leak
+//! into a test running in parallel. Set it on every thread that splits.
+//!
+//! Shares are only as strong as the RNG: anything installed here must be a
+//! cryptographically secure generator unless the shares are throwaway.
+
+use rand::RngCore;
+use std::cell::RefCell;
+
+thread_local! {
+    static DEFAULT_RNG: RefCell<Option<Box<dyn RngCore>>> = const { RefCell::new(None) };
+}
+
+/// Use `rng` for the splits on this thread that don't take an RNG
+pub fn set_default_rng<R: RngCore + 'static>(rng: R) {
+    DEFAULT_RNG.with(|cell| *cell.borrow_mut() = Some(Box::new(rng)));
+}
+
+/// Go back to `thread_rng()` on this thread
+pub fn reset_default_rng() {
+    DEFAULT_RNG.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// Run `f` with this thread's default RNG
+pub(crate) fn with_default_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    DEFAULT_RNG.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(rng) => f(rng.as_mut()),
+        None => f(&mut rand::thread_rng()),
+    })
+}
+
+/// Handle to the default RNG of whichever thread uses it
+///
+/// For state that draws randomness across many calls, like a
+/// [`crate::stream::Splitter`]: every draw goes to the default RNG at that
+/// moment, so nothing is borrowed between calls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRng;
+
+impl RngCore for DefaultRng {
+    fn next_u32(&mut self) -> u32 {
+        with_default_rng(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        with_default_rng(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        with_default_rng(|rng| rng.fill_bytes(dest));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        with_default_rng(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_secret;
+    use crate::stream::{Splitter, split_stream};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_default_rng_is_swappable_per_thread() {
+        set_default_rng(ChaCha20Rng::seed_from_u64(7));
+        let first = split_secret(b"Hello, World!").unwrap();
+        set_default_rng(ChaCha20Rng::seed_from_u64(7));
+        let again = split_secret(b"Hello, World!").unwrap();
+        assert_eq!(first.share2, again.share2);
+
+        // Other threads keep thread_rng
+        let elsewhere = std::thread::spawn(|| split_secret(b"Hello, World!").unwrap())
+            .join()
+            .unwrap();
+        assert_ne!(elsewhere.share2, first.share2);
+
+        reset_default_rng();
+        let fresh = split_secret(b"Hello, World!").unwrap();
+        assert_ne!(fresh.share2, first.share2);
+    }
+
+    #[test]
+    fn test_default_rng_drives_streaming_splits() {
+        let streamed = || {
+            let (mut share1, mut share2) = (Vec::new(), Vec::new());
+            split_stream(&b"Hello, World!"[..], &mut share1, &mut share2, None).unwrap();
+            (share1, share2)
+        };
+        let pushed = || {
+            let mut splitter = Splitter::new();
+            let (_, pad) = splitter.push(b"Hello, World!");
+            pad
+        };
+
+        set_default_rng(ChaCha20Rng::seed_from_u64(7));
+        let expected = split_secret(b"Hello, World!").unwrap();
+        set_default_rng(ChaCha20Rng::seed_from_u64(7));
+        let (share1, share2) = streamed();
+        assert_eq!((share1, share2), (expected.share1, expected.share2.clone()));
+        set_default_rng(ChaCha20Rng::seed_from_u64(7));
+        assert_eq!(pushed(), expected.share2[..13]);
+
+        reset_default_rng();
+        assert_ne!(streamed().1, expected.share2);
+        assert_ne!(pushed(), expected.share2[..13]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Plaintext secret types
+//!
+//! Keeps secrets and share bytes apart at the type level: a `Secret` can be
+//! split, but a share can't be passed where a secret is expected by accident.
+//! A [`RecoveredSecret`] is what comes back, already sorted into text or binary.
+
+use crate::{ShareError, TwoShares, split_secret};
+
+/// Plaintext secret bytes
+///
+/// With the `zeroize` feature enabled the bytes are wiped when the value is dropped.
+/// `Debug` never prints the contents.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    /// Wrap raw secret bytes
+    #[must_use]
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Borrow the secret bytes
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Length of the secret in bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the secret has no bytes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Unwrap into the underlying bytes
+    ///
+    /// The returned buffer is no longer wiped on drop.
+    #[must_use]
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        std::mem::take(&mut self.0)
+    }
+
+    /// Split this secret into 2 shares
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShareError::EmptyInput`] if the secret is empty.
+    pub fn split(&self) -> Result<TwoShares, ShareError> {
+        split_secret(&self.0)
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for Secret {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(s: &str) -> Self {
+        Self(s.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for Secret {
+    fn from(s: String) -> Self {
+        Self(s.into_bytes())
+    }
+}
+
+impl AsRef<[u8]> for Secret {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret([REDACTED; {}])", self.0.len())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Secret {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+/// A recovered secret, classified by whether it is valid UTF-8
+///
+/// Unlike [`Secret`], the contents are not wiped on drop, so the variants can be
+/// moved out of with a plain `match`. `Debug` never prints the contents.
+#[derive(Clone, PartialEq, Eq)]
+pub enum RecoveredSecret {
+    /// Valid UTF-8, including the empty secret
+    Text(String),
+    /// Anything else
+    Binary(Vec<u8>),
+}
+
+impl RecoveredSecret {
+    /// Borrow the secret bytes, whichever variant holds them
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(text) => text.as_bytes(),
+            Self::Binary(bytes) => bytes,
+        }
+    }
+
+    /// Returns `true` for [`RecoveredSecret::Text`]
+    #[must_use]
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Text(_))
+    }
+
+    /// Unwrap into the underlying bytes
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Self::Text(text) => text.into_bytes(),
+            Self::Binary(bytes) => bytes,
+        }
+    }
+}
+
+impl From<Vec<u8>> for RecoveredSecret {
+    /// Classify `bytes` without copying them
+    fn from(bytes: Vec<u8>) -> Self {
+        String::from_utf8(bytes).map_or_else(|e| Self::Binary(e.into_bytes()), Self::Text)
+    }
+}
+
+impl std::fmt::Debug for RecoveredSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_text() { "Text" } else { "Binary" };
+        write!(f, "{kind}([REDACTED; {}])", self.as_bytes().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recover_secret;
+
+    #[test]
+    fn test_secret_split_roundtrip() {
+        let secret = Secret::from("Hello, World!");
+        let shares = secret.split().unwrap();
+
+        let recovered = recover_secret(&shares.share1, &shares.share2).unwrap();
+        assert_eq!(recovered, secret.as_bytes());
+    }
+
+    #[test]
+    fn test_secret_conversions() {
+        let from_vec = Secret::from(b"abc".to_vec());
+        let from_str = Secret::from("abc");
+
+        assert_eq!(from_vec, from_str);
+        assert_eq!(from_vec.len(), 3);
+        assert_eq!(from_str.into_bytes(), b"abc");
+    }
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let secret = Secret::from("hunter2");
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED; 7])");
+    }
+
+    #[test]
+    fn test_recovered_secret_classification() {
+        let text = RecoveredSecret::from("héllo".as_bytes().to_vec());
+        assert_eq!(text, RecoveredSecret::Text("héllo".to_owned()));
+        assert_eq!(format!("{text:?}"), "Text([REDACTED; 6])");
+
+        let binary = RecoveredSecret::from(vec![b'a', 0xff]);
+        assert_eq!(binary, RecoveredSecret::Binary(vec![b'a', 0xff]));
+        assert_eq!(binary.into_bytes(), [b'a', 0xff]);
+    }
+
+    #[test]
+    fn test_empty_secret_split_fails() {
+        assert!(matches!(
+            Secret::from("").split(),
+            Err(ShareError::EmptyInput)
+        ));
+    }
+}
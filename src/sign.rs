@@ -0,0 +1,176 @@
+//! Ed25519-signed shares
+//!
+//! A signed share records who issued it, so an auditor can later confirm that
+//! the share a custodian presents is the one the ceremony produced. Signing
+//! wraps the share as issued (plain or passphrase-protected), so signatures can
+//! be checked without knowing any passphrase.
+//!
+//! Layout:
+//!
+//! ```text
+//! magic "XPS1" | public key [32] | signature [64] | share
+//! ```
+//!
+//! The signature covers the magic and the share bytes.
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use ed25519_dalek::{Signature, Signer, Verifier};
+use sha2::{Digest, Sha256};
+
+use crate::ShareError;
+
+/// Magic prefix identifying a signed share
+pub const SIGNED_MAGIC: &[u8; 4] = b"XPS1";
+
+const PUBLIC_KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+const HEADER_LEN: usize = SIGNED_MAGIC.len() + PUBLIC_KEY_LEN + SIGNATURE_LEN;
+
+/// Returns `true` if `data` starts with the signed-share magic
+#[must_use]
+pub fn is_signed(data: &[u8]) -> bool {
+    data.starts_with(SIGNED_MAGIC)
+}
+
+/// Generate a fresh signing key from the OS random source
+#[must_use]
+pub fn generate_signing_key() -> SigningKey {
+    SigningKey::generate(&mut rand::rngs::OsRng)
+}
+
+/// Length of the signed envelope [`sign_share`] produces for a share of `share_len` bytes
+#[must_use]
+pub const fn signed_len(share_len: usize) -> usize {
+    HEADER_LEN + share_len
+}
+
+/// Sign a share, embedding the signer's public key
+#[must_use]
+pub fn sign_share(share: &[u8], key: &SigningKey) -> Vec<u8> {
+    let signature = key.sign(&signed_message(share));
+
+    let mut signed = Vec::with_capacity(HEADER_LEN + share.len());
+    signed.extend_from_slice(SIGNED_MAGIC);
+    signed.extend_from_slice(key.verifying_key().as_bytes());
+    signed.extend_from_slice(&signature.to_bytes());
+    signed.extend_from_slice(share);
+    signed
+}
+
+/// Verify a signed share and return the inner share with its signer
+///
+/// When `expected_signer` is given, the embedded public key must match it;
+/// otherwise the signature is only checked against the embedded key, which
+/// proves integrity but not provenance.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::InvalidSignature`] if the envelope is malformed or the signature does not verify
+/// - [`ShareError::UnexpectedSigner`] if the share was signed by a different key than `expected_signer`
+pub fn open_signed_share(
+    signed: &[u8],
+    expected_signer: Option<&VerifyingKey>,
+) -> Result<(Vec<u8>, VerifyingKey), ShareError> {
+    if !is_signed(signed) || signed.len() <= HEADER_LEN {
+        return Err(ShareError::InvalidSignature);
+    }
+
+    let (header, share) = signed.split_at(HEADER_LEN);
+    let key_bytes: [u8; PUBLIC_KEY_LEN] = header[4..4 + PUBLIC_KEY_LEN]
+        .try_into()
+        .map_err(|_| ShareError::InvalidSignature)?;
+    let signature_bytes: [u8; SIGNATURE_LEN] = header[4 + PUBLIC_KEY_LEN..]
+        .try_into()
+        .map_err(|_| ShareError::InvalidSignature)?;
+
+    let issuer = VerifyingKey::from_bytes(&key_bytes).map_err(|_| ShareError::InvalidSignature)?;
+    if expected_signer.is_some_and(|expected| expected != &issuer) {
+        return Err(ShareError::UnexpectedSigner);
+    }
+
+    issuer
+        .verify(
+            &signed_message(share),
+            &Signature::from_bytes(&signature_bytes),
+        )
+        .map_err(|_| ShareError::InvalidSignature)?;
+
+    Ok((share.to_vec(), issuer))
+}
+
+/// Short human-comparable fingerprint of a public key
+///
+/// The first 8 bytes of SHA-256 over the key, as colon-separated hex pairs.
+#[must_use]
+pub fn key_fingerprint(key: &VerifyingKey) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn signed_message(share: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SIGNED_MAGIC.len() + share.len());
+    message.extend_from_slice(SIGNED_MAGIC);
+    message.extend_from_slice(share);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_secret;
+
+    #[test]
+    fn test_sign_and_open() {
+        let key = generate_signing_key();
+        let shares = split_secret(b"Hello, World!").unwrap();
+        let signed = sign_share(&shares.share1, &key);
+
+        assert!(is_signed(&signed));
+        assert_eq!(signed.len(), signed_len(shares.share1.len()));
+        let (share, issuer) = open_signed_share(&signed, Some(&key.verifying_key())).unwrap();
+        assert_eq!(share, shares.share1);
+        assert_eq!(issuer, key.verifying_key());
+    }
+
+    #[test]
+    fn test_tampered_share_fails() {
+        let key = generate_signing_key();
+        let shares = split_secret(b"secret").unwrap();
+        let mut signed = sign_share(&shares.share2, &key);
+        let last = signed.len() - 1;
+        signed[last] ^= 0x01;
+
+        assert_eq!(
+            open_signed_share(&signed, None),
+            Err(ShareError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_unexpected_signer() {
+        let issuer = generate_signing_key();
+        let other = generate_signing_key();
+        let shares = split_secret(b"secret").unwrap();
+        let signed = sign_share(&shares.share1, &issuer);
+
+        assert_eq!(
+            open_signed_share(&signed, Some(&other.verifying_key())),
+            Err(ShareError::UnexpectedSigner)
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let fingerprint = key_fingerprint(&key.verifying_key());
+
+        assert_eq!(fingerprint.len(), 8 * 2 + 7);
+        assert_eq!(fingerprint, key_fingerprint(&key.verifying_key()));
+    }
+}
@@ -0,0 +1,59 @@
+//! Hand-vectorized XOR, behind the `unsafe-simd` feature
+//!
+//! This is the only module allowed to contain unsafe code, and it is only
+//! compiled with `unsafe-simd`; every other build forbids unsafe code at the
+//! crate root. On x86-64 it XORs 16 bytes at a time with SSE2, which every
+//! x86-64 CPU has, so no runtime detection is needed. Elsewhere it is the
+//! scalar loop.
+#![allow(unsafe_code)]
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128};
+
+use crate::xor_scalar;
+
+/// XOR `src` into `dst` in place; callers guarantee equal lengths
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn xor_into(dst: &mut [u8], src: &[u8]) {
+    const LANE: usize = size_of::<__m128i>();
+    let len = dst.len().min(src.len());
+    let (dst_blocks, dst_tail) = dst[..len].split_at_mut(len - len % LANE);
+    let (src_blocks, src_tail) = src[..len].split_at(len - len % LANE);
+
+    for (d, s) in dst_blocks
+        .chunks_exact_mut(LANE)
+        .zip(src_blocks.chunks_exact(LANE))
+    {
+        // SAFETY: both chunks are exactly LANE bytes long, the unaligned
+        // load/store intrinsics have no alignment requirement, and SSE2 is
+        // part of the x86-64 baseline.
+        unsafe {
+            let a = _mm_loadu_si128(d.as_ptr().cast());
+            let b = _mm_loadu_si128(s.as_ptr().cast());
+            _mm_storeu_si128(d.as_mut_ptr().cast(), _mm_xor_si128(a, b));
+        }
+    }
+    xor_scalar(dst_tail, src_tail);
+}
+
+/// XOR `src` into `dst` in place; callers guarantee equal lengths
+#[cfg(not(target_arch = "x86_64"))]
+pub(crate) fn xor_into(dst: &mut [u8], src: &[u8]) {
+    xor_scalar(dst, src);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    quickcheck::quickcheck! {
+        fn prop_matches_scalar_xor(pairs: Vec<(u8, u8)>) -> bool {
+            let (a, b): (Vec<u8>, Vec<u8>) = pairs.into_iter().unzip();
+            let mut simd = a.clone();
+            let mut scalar = a;
+            xor_into(&mut simd, &b);
+            xor_scalar(&mut scalar, &b);
+            simd == scalar
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Streaming split and recover for inputs too large to hold in memory
+//!
+//! Shares have exactly the format [`crate::split_secret`] produces: the payload
+//! followed by its big-endian CRC32, so streamed and in-memory shares are
+//! interchangeable. Both functions check an optional cancellation flag between
+//! chunks and stop with [`ShareError::Cancelled`] once it is set.
+//!
+//! [`Splitter`] and [`Recoverer`] produce and read the same format for callers
+//! that are handed chunks instead of owning a reader, such as the WASM bindings.
+
+use crc32fast::Hasher;
+use rand::RngCore;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+
+use crate::rng::DefaultRng;
+use crate::telemetry::{record_bytes, traced};
+use crate::{ShareError, xor_into};
+
+/// Bytes processed between cancellation checks
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+const CRC_LEN: usize = 4;
+
+/// Error from a streaming operation
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Share(#[from] ShareError),
+}
+
+/// Split everything read from `input` into two shares written to `share1` and `share2`
+///
+/// Returns the number of secret bytes split. On error the outputs hold partial
+/// shares and must be discarded.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if `input` is empty
+/// - [`ShareError::Cancelled`] if `cancel` was set
+/// - [`StreamError::Io`] if reading or writing fails
+pub fn split_stream<R: Read, W1: Write, W2: Write>(
+    mut input: R,
+    mut share1: W1,
+    mut share2: W2,
+    cancel: Option<&AtomicBool>,
+) -> Result<u64, StreamError> {
+    traced!("split_stream", bytes = ::tracing::field::Empty; {
+        let mut rng = DefaultRng;
+        let mut data = vec![0u8; CHUNK_SIZE];
+        let mut pad = vec![0u8; CHUNK_SIZE];
+        let mut hasher1 = Hasher::new();
+        let mut hasher2 = Hasher::new();
+        let mut total = 0u64;
+
+        let result: Result<(), StreamError> = loop {
+            if is_cancelled(cancel) {
+                break Err(ShareError::Cancelled.into());
+            }
+            let n = match read_full(&mut input, &mut data) {
+                Ok(0) => break Ok(()),
+                Ok(n) => n,
+                Err(e) => break Err(e.into()),
+            };
+
+            rng.fill_bytes(&mut pad[..n]);
+            xor_into(&mut data[..n], &pad[..n]);
+            hasher1.update(&data[..n]);
+            hasher2.update(&pad[..n]);
+            if let Err(e) = share1
+                .write_all(&data[..n])
+                .and_then(|()| share2.write_all(&pad[..n]))
+            {
+                break Err(e.into());
+            }
+            total += n as u64;
+        };
+        wipe(&mut data);
+        wipe(&mut pad);
+        result?;
+
+        if total == 0 {
+            return Err(ShareError::EmptyInput.into());
+        }
+        share1.write_all(&hasher1.finalize().to_be_bytes())?;
+        share2.write_all(&hasher2.finalize().to_be_bytes())?;
+        share1.flush()?;
+        share2.flush()?;
+        record_bytes(total);
+        Ok(total)
+    })
+}
+
+/// Recover the secret from two streamed shares into `output`
+///
+/// Returns the number of secret bytes written. Shares of different lengths
+/// recover to the shorter payload, like [`crate::recover_secret`]. Checksums
+/// can only be verified once both shares are fully read, so **on error the
+/// output holds unverified data and must be discarded**.
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if either share is empty
+/// - [`ShareError::ShareTooShort`] if either share is shorter than 4 bytes
+/// - [`ShareError::InvalidChecksum`] if either share has a corrupted checksum
+/// - [`ShareError::EmptyPayload`] if the shares recover to an empty secret
+/// - [`ShareError::Cancelled`] if `cancel` was set
+/// - [`StreamError::Io`] if reading or writing fails
+pub fn recover_stream<R1: Read, R2: Read, W: Write>(
+    share1: R1,
+    share2: R2,
+    mut output: W,
+    cancel: Option<&AtomicBool>,
+) -> Result<u64, StreamError> {
+    traced!("recover_stream", bytes = ::tracing::field::Empty; {
+        let mut payload1 = Payload::new(share1);
+        let mut payload2 = Payload::new(share2);
+        let mut data1 = vec![0u8; CHUNK_SIZE];
+        let mut data2 = vec![0u8; CHUNK_SIZE];
+        let mut total = 0u64;
+
+        let result: Result<(), StreamError> = loop {
+            if is_cancelled(cancel) {
+                break Err(ShareError::Cancelled.into());
+            }
+            let (n1, n2) = match payload1
+                .fill(&mut data1)
+                .and_then(|n1| Ok((n1, payload2.fill(&mut data2)?)))
+            {
+                Ok((0, 0)) => break Ok(()),
+                Ok(lens) => lens,
+                Err(e) => break Err(e.into()),
+            };
+
+            // Once the shorter share ends, keep draining the longer one for its checksum
+            let n = n1.min(n2);
+            xor_into(&mut data1[..n], &data2[..n]);
+            if let Err(e) = output.write_all(&data1[..n]) {
+                break Err(e.into());
+            }
+            total += n as u64;
+        };
+        wipe(&mut data1);
+        wipe(&mut data2);
+        result?;
+
+        payload1.verify()?;
+        payload2.verify()?;
+        if total == 0 {
+            return Err(ShareError::EmptyPayload.into());
+        }
+        output.flush()?;
+        record_bytes(total);
+        Ok(total)
+    })
+}
+
+/// Push-based counterpart of [`split_stream`]
+///
+/// Each pushed chunk of the secret comes straight back as the matching chunks
+/// of both shares, and [`Splitter::finish`] gives the checksum trailers to
+/// append. Nothing is buffered between pushes.
+pub struct Splitter<R = DefaultRng> {
+    rng: R,
+    hasher1: Hasher,
+    hasher2: Hasher,
+    len: u64,
+}
+
+impl Splitter {
+    /// Splitter drawing its pads from the default RNG, like [`split_stream`]
+    ///
+    /// See [`crate::rng`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_rng(DefaultRng)
+    }
+}
+
+impl Default for Splitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: RngCore> Splitter<R> {
+    /// Splitter drawing its pads from `rng`, which must be cryptographically secure
+    pub fn with_rng(rng: R) -> Self {
+        Self {
+            rng,
+            hasher1: Hasher::new(),
+            hasher2: Hasher::new(),
+            len: 0,
+        }
+    }
+
+    /// Split the next chunk of the secret, returning the next chunk of each share
+    pub fn push(&mut self, chunk: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut pad = vec![0u8; chunk.len()];
+        self.rng.fill_bytes(&mut pad);
+        let mut data = chunk.to_vec();
+        xor_into(&mut data, &pad);
+        self.hasher1.update(&data);
+        self.hasher2.update(&pad);
+        self.len += chunk.len() as u64;
+        (data, pad)
+    }
+
+    /// The checksum trailers to append to share 1 and share 2
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShareError::EmptyInput`] if nothing was pushed.
+    pub fn finish(self) -> Result<([u8; CRC_LEN], [u8; CRC_LEN]), ShareError> {
+        if self.len == 0 {
+            return Err(ShareError::EmptyInput);
+        }
+        Ok((
+            self.hasher1.finalize().to_be_bytes(),
+            self.hasher2.finalize().to_be_bytes(),
+        ))
+    }
+}
+
+/// Push-based counterpart of [`recover_stream`]
+///
+/// Chunks of both shares are pushed side by side and the secret comes back as
+/// far as both have arrived, less the last 4 bytes of each, which may be the
+/// checksum. Only what one share is ahead of the other is buffered, so
+/// pushing chunks of the same size from both keeps memory flat. As with
+/// [`recover_stream`], **the output is unverified until [`Recoverer::finish`]
+/// succeeds** and must be discarded if it fails.
+#[derive(Default)]
+pub struct Recoverer {
+    held1: Vec<u8>,
+    held2: Vec<u8>,
+    hasher1: Hasher,
+    hasher2: Hasher,
+    len: u64,
+}
+
+impl Recoverer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the next chunk of each share, returning the next chunk of the secret
+    ///
+    /// Either chunk may be empty, for instance once one share has ended.
+    pub fn push(&mut self, chunk1: &[u8], chunk2: &[u8]) -> Vec<u8> {
+        self.held1.extend_from_slice(chunk1);
+        self.held2.extend_from_slice(chunk2);
+        let n = self
+            .held1
+            .len()
+            .min(self.held2.len())
+            .saturating_sub(CRC_LEN);
+
+        let mut secret: Vec<u8> = self.held1.drain(..n).collect();
+        let data2: Vec<u8> = self.held2.drain(..n).collect();
+        self.hasher1.update(&secret);
+        self.hasher2.update(&data2);
+        xor_into(&mut secret, &data2);
+        self.len += n as u64;
+        secret
+    }
+
+    /// Verify both checksums once both shares have been pushed in full,
+    /// returning the length of the secret
+    ///
+    /// Shares of different lengths recover to the shorter payload, like
+    /// [`crate::recover_secret`].
+    ///
+    /// # Errors
+    ///
+    /// Returns:
+    /// - [`ShareError::EmptyInput`] if either share is empty
+    /// - [`ShareError::ShareTooShort`] if either share is shorter than 4 bytes
+    /// - [`ShareError::InvalidChecksum`] if either share has a corrupted checksum
+    /// - [`ShareError::EmptyPayload`] if the shares recover to an empty secret
+    pub fn finish(mut self) -> Result<u64, ShareError> {
+        let len = self.len;
+        for (held, hasher) in [
+            (&self.held1, &mut self.hasher1),
+            (&self.held2, &mut self.hasher2),
+        ] {
+            if len == 0 && held.is_empty() {
+                return Err(ShareError::EmptyInput);
+            }
+            let Some(data_len) = held.len().checked_sub(CRC_LEN) else {
+                return Err(ShareError::ShareTooShort);
+            };
+            // The longer share's extra payload is checked but not recovered
+            hasher.update(&held[..data_len]);
+            let stored = crate::format::read_u32_be(held, data_len)?;
+            if hasher.clone().finalize() != stored {
+                return Err(ShareError::InvalidChecksum);
+            }
+        }
+        if len == 0 {
+            return Err(ShareError::EmptyPayload);
+        }
+        Ok(len)
+    }
+}
+
+impl Drop for Recoverer {
+    fn drop(&mut self) {
+        wipe(&mut self.held1);
+        wipe(&mut self.held2);
+    }
+}
+
+/// Reads a share's payload, holding back the trailing checksum
+struct Payload<R> {
+    inner: R,
+    /// Last bytes read, which may turn out to be the checksum
+    tail: Vec<u8>,
+    scratch: Vec<u8>,
+    hasher: Hasher,
+    len: u64,
+    eof: bool,
+}
+
+impl<R: Read> Payload<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            tail: Vec::with_capacity(CRC_LEN + CHUNK_SIZE),
+            scratch: vec![0; CHUNK_SIZE],
+            hasher: Hasher::new(),
+            len: 0,
+            eof: false,
+        }
+    }
+
+    /// Fill `out` with payload bytes; returns fewer than `out.len()` only at the end
+    fn fill(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < out.len() && !self.eof {
+            let want = out.len() - filled;
+            let n = read_full(&mut self.inner, &mut self.scratch[..want])?;
+            if n == 0 {
+                self.eof = true;
+                break;
+            }
+            self.tail.extend_from_slice(&self.scratch[..n]);
+            if self.tail.len() > CRC_LEN {
+                let emit = self.tail.len() - CRC_LEN;
+                out[filled..filled + emit].copy_from_slice(&self.tail[..emit]);
+                self.tail.drain(..emit);
+                filled += emit;
+            }
+        }
+        self.hasher.update(&out[..filled]);
+        self.len += filled as u64;
+        Ok(filled)
+    }
+
+    /// Check the held-back checksum once the share has been read to the end
+    fn verify(&self) -> Result<(), ShareError> {
+        if self.len == 0 && self.tail.is_empty() {
+            return Err(ShareError::EmptyInput);
+        }
+        let Ok(stored) = <[u8; CRC_LEN]>::try_from(self.tail.as_slice()) else {
+            return Err(ShareError::ShareTooShort);
+        };
+        if self.hasher.clone().finalize() != u32::from_be_bytes(stored) {
+            return Err(ShareError::InvalidChecksum);
+        }
+        Ok(())
+    }
+}
+
+impl<R> Drop for Payload<R> {
+    fn drop(&mut self) {
+        wipe(&mut self.scratch);
+        wipe(&mut self.tail);
+    }
+}
+
+/// Read until `buf` is full or the reader is exhausted
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+    cancel.is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+/// Clear a buffer that held secret data
+fn wipe(buf: &mut [u8]) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    #[cfg(not(feature = "zeroize"))]
+    buf.fill(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recover_secret, split_secret};
+
+    /// Hands out one byte per read, to exercise short reads
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_stream_matches_in_memory_format() {
+        let secret: Vec<u8> = (0..=255u8).cycle().take(CHUNK_SIZE * 2 + 17).collect();
+        let (mut share1, mut share2) = (Vec::new(), Vec::new());
+        let len = split_stream(secret.as_slice(), &mut share1, &mut share2, None).unwrap();
+        assert_eq!(len, secret.len() as u64);
+        assert_eq!(recover_secret(&share1, &share2).unwrap(), secret);
+
+        let in_memory = split_secret(b"in memory").unwrap();
+        let mut recovered = Vec::new();
+        recover_stream(
+            Trickle(&in_memory.share1),
+            Trickle(&in_memory.share2),
+            &mut recovered,
+            None,
+        )
+        .unwrap();
+        assert_eq!(recovered, b"in memory");
+    }
+
+    #[test]
+    fn test_push_api_matches_one_shot_split() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let secret: Vec<u8> = (0..=255u8).cycle().take(CHUNK_SIZE * 3 + 5).collect();
+        let mut splitter = Splitter::with_rng(ChaCha20Rng::seed_from_u64(7));
+        let (mut share1, mut share2) = (Vec::new(), Vec::new());
+        for chunk in secret.chunks(CHUNK_SIZE) {
+            let (chunk1, chunk2) = splitter.push(chunk);
+            share1.extend(chunk1);
+            share2.extend(chunk2);
+        }
+        let (trailer1, trailer2) = splitter.finish().unwrap();
+        share1.extend(trailer1);
+        share2.extend(trailer2);
+
+        let one_shot =
+            crate::split_secret_with_rng(&secret, &mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+        assert_eq!((&share1, &share2), (&one_shot.share1, &one_shot.share2));
+
+        // Uneven chunks, with share 2 lagging behind
+        let mut recoverer = Recoverer::new();
+        let mut output = Vec::new();
+        let (mut rest1, mut rest2) = (share1.as_slice(), share2.as_slice());
+        while !rest1.is_empty() || !rest2.is_empty() {
+            let (chunk1, tail1) = rest1.split_at(rest1.len().min(1000));
+            let (chunk2, tail2) = rest2.split_at(rest2.len().min(999));
+            output.extend(recoverer.push(chunk1, chunk2));
+            (rest1, rest2) = (tail1, tail2);
+        }
+        assert_eq!(recoverer.finish().unwrap(), secret.len() as u64);
+        assert_eq!(output, secret);
+    }
+
+    #[test]
+    fn test_push_api_errors() {
+        assert_eq!(Splitter::new().finish(), Err(ShareError::EmptyInput));
+        assert_eq!(Recoverer::new().finish(), Err(ShareError::EmptyInput));
+
+        let mut shares = split_secret(b"secret").unwrap();
+        shares.share1[0] ^= 1;
+        let mut recoverer = Recoverer::new();
+        recoverer.push(&shares.share1, &shares.share2);
+        assert_eq!(recoverer.finish(), Err(ShareError::InvalidChecksum));
+
+        let mut recoverer = Recoverer::new();
+        recoverer.push(b"ab", &shares.share2);
+        assert_eq!(recoverer.finish(), Err(ShareError::ShareTooShort));
+
+        // Two bare checksums of nothing verify, but recover no secret
+        let empty = crate::seal(b"");
+        let mut recoverer = Recoverer::new();
+        recoverer.push(&empty, &empty);
+        assert_eq!(recoverer.finish(), Err(ShareError::EmptyPayload));
+    }
+
+    #[test]
+    fn test_cancelled_stream_stops() {
+        let cancel = AtomicBool::new(true);
+        let result = split_stream(&b"secret"[..], Vec::new(), Vec::new(), Some(&cancel));
+        assert!(matches!(
+            result,
+            Err(StreamError::Share(ShareError::Cancelled))
+        ));
+
+        let shares = split_secret(b"secret").unwrap();
+        let result = recover_stream(
+            shares.share1.as_slice(),
+            shares.share2.as_slice(),
+            Vec::new(),
+            Some(&cancel),
+        );
+        assert!(matches!(
+            result,
+            Err(StreamError::Share(ShareError::Cancelled))
+        ));
+    }
+
+    #[test]
+    fn test_recover_stream_detects_corruption() {
+        let mut shares = split_secret(b"secret").unwrap();
+        shares.share2[0] ^= 1;
+        let result = recover_stream(
+            shares.share1.as_slice(),
+            shares.share2.as_slice(),
+            Vec::new(),
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(StreamError::Share(ShareError::InvalidChecksum))
+        ));
+
+        let result = recover_stream(&b"ab"[..], shares.share1.as_slice(), Vec::new(), None);
+        assert!(matches!(
+            result,
+            Err(StreamError::Share(ShareError::ShareTooShort))
+        ));
+
+        let empty = crate::seal(b"");
+        let result = recover_stream(empty.as_slice(), empty.as_slice(), Vec::new(), None);
+        assert!(matches!(
+            result,
+            Err(StreamError::Share(ShareError::EmptyPayload))
+        ));
+    }
+}
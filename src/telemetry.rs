@@ -0,0 +1,179 @@
+//! Optional `tracing` spans around splitting and recovering
+//!
+//! With the `tracing` feature, [`crate::split_secret`], [`crate::recover_secret`],
+//! [`crate::stream::split_stream`] and [`crate::stream::recover_stream`] each
+//! run in a debug-level span and end it with an event giving the elapsed time,
+//! or the error on failure. Spans record lengths and byte counts only: never a
+//! secret, a share or anything derived from their contents. Without the
+//! feature [`traced!`] is just its body.
+
+/// Run `$body` in a span named `$name`, then record its duration or error
+///
+/// The body runs in a closure, so `?` and `return` inside it still end up in
+/// the closing event.
+macro_rules! traced {
+    ($name:literal $(, $field:ident = $value:expr)*; $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!($name $(, $field = $value)*).entered();
+        #[cfg(feature = "tracing")]
+        let started = ::std::time::Instant::now();
+        #[allow(clippy::redundant_closure_call)]
+        let result = (|| $body)();
+        #[cfg(feature = "tracing")]
+        $crate::telemetry::finished(&result, started);
+        result
+    }};
+}
+pub(crate) use traced;
+
+/// Record the byte count of a streaming operation on its span
+pub(crate) fn record_bytes(bytes: u64) {
+    #[cfg(feature = "tracing")]
+    ::tracing::Span::current().record("bytes", bytes);
+    #[cfg(not(feature = "tracing"))]
+    let _ = bytes;
+}
+
+/// Closing event of a [`traced!`] span; the `Ok` value is never recorded
+#[cfg(feature = "tracing")]
+pub(crate) fn finished<T, E: std::fmt::Debug>(result: &Result<T, E>, started: std::time::Instant) {
+    match result {
+        Ok(_) => ::tracing::debug!(elapsed = ?started.elapsed(), "finished"),
+        Err(error) => ::tracing::debug!(elapsed = ?started.elapsed(), ?error, "failed"),
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::{Debug, Write};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing_core::span::Current;
+
+    use crate::stream::{recover_stream, split_stream};
+    use crate::{recover_secret, split_secret};
+
+    /// Subscriber writing every span name, field and event as a line of text
+    #[derive(Default)]
+    struct Capture {
+        lines: Arc<Mutex<Vec<String>>>,
+        next_id: AtomicU64,
+        spans: Mutex<HashMap<u64, &'static Metadata<'static>>>,
+        entered: Mutex<Vec<Id>>,
+    }
+
+    struct Line<'a>(&'a mut String);
+
+    impl Visit for Line<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    impl Capture {
+        fn push(&self, name: &str, record: impl FnOnce(&mut Line<'_>)) {
+            let mut line = name.to_owned();
+            record(&mut Line(&mut line));
+            self.lines.lock().unwrap().push(line);
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            self.spans.lock().unwrap().insert(id, span.metadata());
+            self.push(span.metadata().name(), |line| span.record(line));
+            Id::from_u64(id)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let name = self.spans.lock().unwrap()[&span.into_u64()].name();
+            self.push(name, |line| values.record(line));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            self.push("event", |line| event.record(line));
+        }
+
+        fn enter(&self, span: &Id) {
+            self.entered.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.entered.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            match self.entered.lock().unwrap().last() {
+                Some(id) => Current::new(id.clone(), self.spans.lock().unwrap()[&id.into_u64()]),
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_spans_never_record_secret_material() {
+        use base64::prelude::*;
+
+        let secret = b"correct horse battery staple";
+        let capture = Capture::default();
+        let lines = Arc::clone(&capture.lines);
+
+        let shares = tracing::subscriber::with_default(capture, || {
+            let shares = split_secret(secret).unwrap();
+            assert_eq!(
+                recover_secret(&shares.share1, &shares.share2).unwrap(),
+                secret
+            );
+            let mut corrupted = shares.share2.clone();
+            corrupted[0] ^= 1;
+            assert!(recover_secret(&shares.share1, &corrupted).is_err());
+
+            let (mut streamed1, mut streamed2) = (Vec::new(), Vec::new());
+            split_stream(&secret[..], &mut streamed1, &mut streamed2, None).unwrap();
+            let mut recovered = Vec::new();
+            recover_stream(&streamed1[..], &streamed2[..], &mut recovered, None).unwrap();
+            assert_eq!(recovered, secret);
+            [shares.share1, shares.share2, streamed1, streamed2]
+        });
+
+        let log = lines.lock().unwrap().join("\n");
+        for expected in [
+            "split_secret secret_len=28",
+            "recover_secret share1_len=32 share2_len=32",
+            "error=InvalidChecksum",
+            "split_stream bytes=28",
+            "recover_stream bytes=28",
+        ] {
+            assert!(log.contains(expected), "missing {expected:?} in:\n{log}");
+        }
+
+        let mut forbidden = vec![
+            String::from_utf8_lossy(secret).into_owned(),
+            hex::encode(secret),
+            BASE64_STANDARD.encode(secret),
+            format!("{secret:?}"),
+        ];
+        for share in &shares {
+            forbidden.extend([hex::encode(share), BASE64_STANDARD.encode(share)]);
+            // A short run of the payload, in the forms Debug could print it
+            forbidden.push(format!("{:?}", &share[..4]));
+        }
+        for material in &forbidden {
+            assert!(
+                !log.contains(material.as_str()),
+                "{material:?} leaked into:\n{log}"
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Unicode normalization of string secrets
+//!
+//! The same visible passphrase can be typed as different byte sequences (a
+//! precomposed "é" vs "e" plus a combining accent). Normalizing before splitting
+//! means the recovered secret matches what a user re-types on another system.
+//!
+//! The shares hold the **normalized** form: recovery returns those bytes, not
+//! the original input, which only matters if something compares the raw bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{ShareError, TwoShares, split_secret};
+
+/// Unicode normalization form
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Canonical composition; the usual choice for passphrases
+    Nfc,
+    /// Canonical decomposition
+    Nfd,
+    /// Compatibility composition (also folds e.g. full-width and ligature forms)
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+impl Normalization {
+    /// Normalize `s` into this form
+    #[must_use]
+    pub fn apply(self, s: &str) -> String {
+        match self {
+            Self::Nfc => s.nfc().collect(),
+            Self::Nfd => s.nfd().collect(),
+            Self::Nfkc => s.nfkc().collect(),
+            Self::Nfkd => s.nfkd().collect(),
+        }
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nfc" => Ok(Self::Nfc),
+            "nfd" => Ok(Self::Nfd),
+            "nfkc" => Ok(Self::Nfkc),
+            "nfkd" => Ok(Self::Nfkd),
+            _ => Err(format!(
+                "unknown normalization form '{s}' (expected nfc, nfd, nfkc or nfkd)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Nfc => "nfc",
+            Self::Nfd => "nfd",
+            Self::Nfkc => "nfkc",
+            Self::Nfkd => "nfkd",
+        })
+    }
+}
+
+/// Normalize a string secret, then split it into 2 shares
+///
+/// Recovery yields the normalized bytes.
+///
+/// # Errors
+///
+/// Returns [`ShareError::EmptyInput`] if the secret is empty.
+pub fn split_secret_str_normalized(
+    secret: &str,
+    form: Normalization,
+) -> Result<TwoShares, ShareError> {
+    split_secret(form.apply(secret).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recover_secret;
+
+    #[test]
+    fn test_nfc_and_nfd_inputs_recover_identically() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_ne!(composed.as_bytes(), decomposed.as_bytes());
+
+        for input in [composed, decomposed] {
+            let shares = split_secret_str_normalized(input, Normalization::Nfc).unwrap();
+            let recovered = recover_secret(&shares.share1, &shares.share2).unwrap();
+            assert_eq!(recovered, composed.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_parse_form() {
+        assert_eq!("NFC".parse(), Ok(Normalization::Nfc));
+        assert_eq!("nfkd".parse(), Ok(Normalization::Nfkd));
+        assert!("nfx".parse::<Normalization>().is_err());
+    }
+}
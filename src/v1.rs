@@ -0,0 +1,463 @@
+//! Self-describing v1 share format
+//!
+//! Plain shares are headerless, so everything about them (checksum placement,
+//! which split they came from, what secret they hold) has to be known out of
+//! band. A v1 share records it in a header. Grammar (integers big-endian, see
+//! [`crate::format`]):
+//!
+//! ```text
+//! share    = magic flags [group-id] [label] body
+//! magic    = "XP1"
+//! flags    = u8
+//!            bits 0-1  checksum algorithm: 0 = CRC32 (1-3 reserved)
+//!            bit 2     checksum placement: 0 = after the payload, 1 = before it
+//!            bit 3     group-id present
+//!            bit 4     label present
+//!            bits 5-7  reserved, must be 0
+//! group-id = 16 bytes, the same in both shares of a split
+//! label    = length u8 | UTF-8, 1 to 64 bytes, no control characters
+//! body     = payload checksum     (placement bit 0)
+//!          | checksum payload     (placement bit 1)
+//! checksum = u32, CRC32 over every other byte of the share
+//! ```
+//!
+//! Unlike a plain share's, the checksum covers the header too, so a damaged
+//! label or group id is caught like a damaged payload. A share using a
+//! reserved bit is refused with [`ShareError::UnsupportedFormat`] rather than
+//! misread.
+//!
+//! [`crate::recover_secret`] and everything built on its checks accept v1
+//! shares alongside plain ones, refusing two whose group ids differ with
+//! [`ShareError::DifferentSplits`].
+//!
+//! The format is opt-in: only [`split_secret_v1`] produces it, and every other
+//! split still writes plain shares. The headers of the other modules (labeled
+//! `XPL1`, padded `XPD1`, chunked `XPC1`, signed `XPS1`, protected `XPP1`)
+//! are separate wrappers, not v1 fields. In particular a v1 label lives in the
+//! checksummed header, while [`crate::label`] wraps a share of any format in
+//! an unchecked `XPL1` envelope; the two are independent.
+
+use crc32fast::Hasher;
+
+use crate::label::{MAX_LABEL_LEN, validate_label};
+use crate::{ChecksumPlacement, ShareError, TwoShares, rng, split_secret_with_rng};
+
+/// Magic prefix identifying a v1 share
+pub const V1_MAGIC: &[u8; 3] = b"XP1";
+
+/// Length of a group id
+pub const GROUP_ID_LEN: usize = 16;
+
+const ALGORITHM_MASK: u8 = 0b0000_0011;
+const ALGORITHM_CRC32: u8 = 0;
+const PLACEMENT_PREFIX: u8 = 0b0000_0100;
+const HAS_GROUP_ID: u8 = 0b0000_1000;
+const HAS_LABEL: u8 = 0b0001_0000;
+const KNOWN_FLAGS: u8 = ALGORITHM_MASK | PLACEMENT_PREFIX | HAS_GROUP_ID | HAS_LABEL;
+const CRC_LEN: usize = 4;
+
+/// Every field of a v1 share
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedShare {
+    /// Where the checksum sits relative to the payload
+    pub placement: ChecksumPlacement,
+    /// Identifier shared by both shares of a split
+    pub group_id: Option<[u8; GROUP_ID_LEN]>,
+    /// Name of the secret, as accepted by [`validate_label`]
+    pub label: Option<String>,
+    /// The share's data, what a plain share holds before its checksum
+    pub payload: Vec<u8>,
+}
+
+/// A v1 share's fields, borrowed from the share
+struct ShareView<'a> {
+    placement: ChecksumPlacement,
+    group_id: Option<[u8; GROUP_ID_LEN]>,
+    label: Option<&'a str>,
+    payload: &'a [u8],
+}
+
+/// Returns `true` if `data` starts with the v1 magic
+#[must_use]
+pub fn is_v1(data: &[u8]) -> bool {
+    data.starts_with(V1_MAGIC)
+}
+
+/// Parse a v1 share, verifying its checksum
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if `share` is empty
+/// - [`ShareError::UnsupportedFormat`] if the magic is missing or a reserved
+///   flag is set
+/// - [`ShareError::ShareTooShort`] if the share ends inside a field
+/// - [`ShareError::InvalidLabel`] if the label isn't valid UTF-8 or fails
+///   [`validate_label`]
+/// - [`ShareError::InvalidChecksum`] if the checksum doesn't match
+pub fn parse_v1(share: &[u8]) -> Result<ParsedShare, ShareError> {
+    let view = parse_view(share)?;
+    Ok(ParsedShare {
+        placement: view.placement,
+        group_id: view.group_id,
+        label: view.label.map(str::to_owned),
+        payload: view.payload.to_vec(),
+    })
+}
+
+/// Verify a v1 share and borrow its payload
+///
+/// # Errors
+///
+/// Same as [`parse_v1`].
+pub fn v1_payload(share: &[u8]) -> Result<&[u8], ShareError> {
+    parse_view(share).map(|view| view.payload)
+}
+
+/// Borrow a v1 share's payload without checking its checksum
+///
+/// The header is still parsed and checked, as in [`parse_v1`]. For
+/// [`crate::recover_secret_force`]; use [`v1_payload`] everywhere else.
+///
+/// # Errors
+///
+/// Same as [`parse_v1`], except never [`ShareError::InvalidChecksum`].
+pub fn v1_payload_unchecked(share: &[u8]) -> Result<&[u8], ShareError> {
+    parse_unverified(share).map(|(view, _)| view.payload)
+}
+
+fn parse_view(share: &[u8]) -> Result<ShareView<'_>, ShareError> {
+    let (view, stored) = parse_unverified(share)?;
+    // The header is what precedes the payload and checksum, in either placement
+    let header = &share[..share.len() - view.payload.len() - CRC_LEN];
+    if checksum(header, view.payload) != stored {
+        return Err(ShareError::InvalidChecksum);
+    }
+    Ok(view)
+}
+
+/// Parse a v1 share's header, returning its view and the stored checksum
+fn parse_unverified(share: &[u8]) -> Result<(ShareView<'_>, u32), ShareError> {
+    if share.is_empty() {
+        return Err(ShareError::EmptyInput);
+    }
+    let rest = share
+        .strip_prefix(V1_MAGIC)
+        .ok_or(ShareError::UnsupportedFormat)?;
+    let (&flags, mut rest) = rest.split_first().ok_or(ShareError::ShareTooShort)?;
+    if flags & !KNOWN_FLAGS != 0 || flags & ALGORITHM_MASK != ALGORITHM_CRC32 {
+        return Err(ShareError::UnsupportedFormat);
+    }
+
+    let mut group_id = None;
+    if flags & HAS_GROUP_ID != 0 {
+        let (id, tail) = rest
+            .split_first_chunk::<GROUP_ID_LEN>()
+            .ok_or(ShareError::ShareTooShort)?;
+        group_id = Some(*id);
+        rest = tail;
+    }
+    let mut label = None;
+    if flags & HAS_LABEL != 0 {
+        let (&len, tail) = rest.split_first().ok_or(ShareError::ShareTooShort)?;
+        let (bytes, tail) = tail
+            .split_at_checked(usize::from(len))
+            .ok_or(ShareError::ShareTooShort)?;
+        let text = std::str::from_utf8(bytes).map_err(|_| ShareError::InvalidLabel)?;
+        validate_label(text)?;
+        label = Some(text);
+        rest = tail;
+    }
+
+    let (placement, stored, payload) = if flags & PLACEMENT_PREFIX == 0 {
+        let (payload, crc) = rest
+            .split_last_chunk::<CRC_LEN>()
+            .ok_or(ShareError::ShareTooShort)?;
+        (ChecksumPlacement::Suffix, crc, payload)
+    } else {
+        let (crc, payload) = rest
+            .split_first_chunk::<CRC_LEN>()
+            .ok_or(ShareError::ShareTooShort)?;
+        (ChecksumPlacement::Prefix, crc, payload)
+    };
+    let view = ShareView {
+        placement,
+        group_id,
+        label,
+        payload,
+    };
+    Ok((view, u32::from_be_bytes(*stored)))
+}
+
+/// Write `share` in the v1 format
+///
+/// # Errors
+///
+/// Returns [`ShareError::InvalidLabel`] if the label fails [`validate_label`].
+pub fn serialize_v1(share: &ParsedShare) -> Result<Vec<u8>, ShareError> {
+    let mut flags = ALGORITHM_CRC32;
+    if share.placement == ChecksumPlacement::Prefix {
+        flags |= PLACEMENT_PREFIX;
+    }
+    if share.group_id.is_some() {
+        flags |= HAS_GROUP_ID;
+    }
+    if share.label.is_some() {
+        flags |= HAS_LABEL;
+    }
+
+    let mut out = Vec::with_capacity(
+        V1_MAGIC.len() + 1 + GROUP_ID_LEN + 1 + MAX_LABEL_LEN + share.payload.len() + CRC_LEN,
+    );
+    out.extend_from_slice(V1_MAGIC);
+    out.push(flags);
+    if let Some(group_id) = &share.group_id {
+        out.extend_from_slice(group_id);
+    }
+    if let Some(label) = &share.label {
+        validate_label(label)?;
+        // validate_label keeps the length within MAX_LABEL_LEN
+        out.push(u8::try_from(label.len()).unwrap_or(u8::MAX));
+        out.extend_from_slice(label.as_bytes());
+    }
+
+    let crc = checksum(&out, &share.payload).to_be_bytes();
+    match share.placement {
+        ChecksumPlacement::Suffix => {
+            out.extend_from_slice(&share.payload);
+            out.extend_from_slice(&crc);
+        }
+        ChecksumPlacement::Prefix => {
+            out.extend_from_slice(&crc);
+            out.extend_from_slice(&share.payload);
+        }
+    }
+    Ok(out)
+}
+
+/// Split a secret into 2 v1 shares sharing a random group id
+///
+/// # Errors
+///
+/// Returns:
+/// - [`ShareError::EmptyInput`] if the secret is empty
+/// - [`ShareError::InvalidLabel`] if the label fails [`validate_label`]
+pub fn split_secret_v1(secret: &[u8], label: Option<&str>) -> Result<TwoShares, ShareError> {
+    if let Some(label) = label {
+        validate_label(label)?;
+    }
+    let mut group_id = [0u8; GROUP_ID_LEN];
+    let shares = rng::with_default_rng(|rng| {
+        rng.fill_bytes(&mut group_id);
+        split_secret_with_rng(secret, rng)
+    })?;
+
+    let wrap = |mut share: Vec<u8>| {
+        share.truncate(share.len() - CRC_LEN);
+        serialize_v1(&ParsedShare {
+            placement: ChecksumPlacement::Suffix,
+            group_id: Some(group_id),
+            label: label.map(str::to_owned),
+            payload: share,
+        })
+    };
+    Ok(TwoShares {
+        share1: wrap(shares.share1)?,
+        share2: wrap(shares.share2)?,
+    })
+}
+
+/// `share` with its payload replaced, keeping its header
+///
+/// # Errors
+///
+/// Same as [`parse_v1`].
+pub(crate) fn with_payload(share: &[u8], payload: Vec<u8>) -> Result<Vec<u8>, ShareError> {
+    let parsed = parse_v1(share)?;
+    serialize_v1(&ParsedShare { payload, ..parsed })
+}
+
+/// [`with_payload`], also replacing the group id if `share` has one
+///
+/// # Errors
+///
+/// Same as [`parse_v1`].
+pub(crate) fn regrouped(
+    share: &[u8],
+    payload: Vec<u8>,
+    group_id: [u8; GROUP_ID_LEN],
+) -> Result<Vec<u8>, ShareError> {
+    let parsed = parse_v1(share)?;
+    serialize_v1(&ParsedShare {
+        group_id: parsed.group_id.map(|_| group_id),
+        payload,
+        ..parsed
+    })
+}
+
+/// Group id of an intact v1 share, if it has one
+pub(crate) fn group_id(share: &[u8]) -> Option<[u8; GROUP_ID_LEN]> {
+    parse_view(share).ok().and_then(|view| view.group_id)
+}
+
+/// Refuse two v1 shares whose group ids differ
+///
+/// Shares without a group id, plain ones included, can't be told apart and
+/// pass.
+///
+/// # Errors
+///
+/// Returns [`ShareError::DifferentSplits`] if both shares carry a group id and
+/// they differ.
+pub(crate) fn check_same_split(share1: &[u8], share2: &[u8]) -> Result<(), ShareError> {
+    match (group_id(share1), group_id(share2)) {
+        (Some(id1), Some(id2)) if id1 != id2 => Err(ShareError::DifferentSplits),
+        _ => Ok(()),
+    }
+}
+
+fn checksum(header: &[u8], payload: &[u8]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(header);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{recover_secret, refresh_shares, verify_pair};
+    use quickcheck::{Arbitrary, Gen};
+
+    #[derive(Clone, Debug)]
+    struct AnyShare(ParsedShare);
+
+    impl Arbitrary for AnyShare {
+        fn arbitrary(g: &mut Gen) -> Self {
+            // Keep the longest valid label the generated text starts with
+            let label = String::arbitrary(g)
+                .chars()
+                .filter(|c| !c.is_control())
+                .scan(0, |len, c| {
+                    *len += c.len_utf8();
+                    (*len <= 64).then_some(c)
+                })
+                .collect::<String>();
+            Self(ParsedShare {
+                placement: if bool::arbitrary(g) {
+                    ChecksumPlacement::Prefix
+                } else {
+                    ChecksumPlacement::Suffix
+                },
+                group_id: Option::<u128>::arbitrary(g).map(u128::to_be_bytes),
+                label: (bool::arbitrary(g) && !label.is_empty()).then_some(label),
+                payload: Vec::arbitrary(g),
+            })
+        }
+    }
+
+    quickcheck::quickcheck! {
+        fn prop_v1_roundtrip(share: AnyShare) -> bool {
+            let bytes = serialize_v1(&share.0).unwrap();
+            is_v1(&bytes) && parse_v1(&bytes).as_ref() == Ok(&share.0)
+        }
+
+        fn prop_v1_detects_any_flipped_bit(share: AnyShare, bit: usize) -> bool {
+            let mut bytes = serialize_v1(&share.0).unwrap();
+            let bit = bit % (bytes.len() * 8);
+            bytes[bit / 8] ^= 1 << (bit % 8);
+            parse_v1(&bytes) != Ok(share.0)
+        }
+    }
+
+    #[test]
+    fn test_v1_layout() {
+        let share = ParsedShare {
+            placement: ChecksumPlacement::Suffix,
+            group_id: None,
+            label: Some("vault".to_owned()),
+            payload: b"data".to_vec(),
+        };
+        let bytes = serialize_v1(&share).unwrap();
+        assert_eq!(&bytes[..10], b"XP1\x10\x05vault");
+        assert_eq!(&bytes[10..14], b"data");
+        assert_eq!(bytes.len(), 18);
+
+        let prefix = serialize_v1(&ParsedShare {
+            placement: ChecksumPlacement::Prefix,
+            ..share
+        })
+        .unwrap();
+        assert_eq!(prefix[3], 0x14);
+        assert_eq!(&prefix[14..], b"data");
+    }
+
+    #[test]
+    fn test_v1_rejects_malformed_shares() {
+        let share = ParsedShare {
+            placement: ChecksumPlacement::Suffix,
+            group_id: Some([7; GROUP_ID_LEN]),
+            label: None,
+            payload: b"data".to_vec(),
+        };
+        let bytes = serialize_v1(&share).unwrap();
+
+        assert_eq!(parse_v1(b""), Err(ShareError::EmptyInput));
+        assert_eq!(parse_v1(b"XP2\x00"), Err(ShareError::UnsupportedFormat));
+        assert_eq!(parse_v1(b"XP1"), Err(ShareError::ShareTooShort));
+        assert_eq!(parse_v1(&bytes[..10]), Err(ShareError::ShareTooShort));
+        for reserved in [0x01, 0x20, 0x80] {
+            let mut flagged = bytes.clone();
+            flagged[3] |= reserved;
+            assert_eq!(parse_v1(&flagged), Err(ShareError::UnsupportedFormat));
+        }
+        let mut corrupted = bytes.clone();
+        corrupted[5] ^= 1;
+        assert_eq!(parse_v1(&corrupted), Err(ShareError::InvalidChecksum));
+        assert_eq!(
+            parse_v1(b"XP1\x10\x03\xff\xfe\xfddata\x00\x00\x00\x00"),
+            Err(ShareError::InvalidLabel)
+        );
+
+        let long_label = ParsedShare {
+            label: Some("x".repeat(65)),
+            ..share
+        };
+        assert_eq!(serialize_v1(&long_label), Err(ShareError::InvalidLabel));
+    }
+
+    #[test]
+    fn test_v1_shares_recover_and_refresh() {
+        let shares = split_secret_v1(b"Hello, World!", Some("vault")).unwrap();
+        let parsed1 = parse_v1(&shares.share1).unwrap();
+        let parsed2 = parse_v1(&shares.share2).unwrap();
+        assert_eq!(parsed1.group_id, parsed2.group_id);
+        assert_eq!(parsed1.label.as_deref(), Some("vault"));
+        assert_eq!(
+            recover_secret(&shares.share1, &shares.share2).unwrap(),
+            b"Hello, World!"
+        );
+
+        let fresh = refresh_shares(&shares.share1, &shares.share2).unwrap();
+        let fresh1 = parse_v1(&fresh.share1).unwrap();
+        assert_ne!(fresh1.group_id, parsed1.group_id);
+        assert_eq!(fresh1.group_id, parse_v1(&fresh.share2).unwrap().group_id);
+        assert_eq!(fresh1.label.as_deref(), Some("vault"));
+        assert_eq!(
+            recover_secret(&fresh.share1, &fresh.share2).unwrap(),
+            b"Hello, World!"
+        );
+        assert_eq!(
+            recover_secret(&fresh.share1, &shares.share2),
+            Err(ShareError::DifferentSplits)
+        );
+        assert_eq!(
+            verify_pair(&shares.share1, &fresh.share2),
+            Err(ShareError::DifferentSplits)
+        );
+        assert_eq!(
+            split_secret_v1(b"secret", Some("")).err(),
+            Some(ShareError::InvalidLabel)
+        );
+    }
+}
@@ -1,120 +1,2178 @@
 //! WASM bindings for xplit
 //!
 //! This module provides JavaScript-friendly bindings for the core split/recover functionality.
+//!
+//! Every split draws its random pad from `getrandom`, which the `js` feature
+//! backs with `crypto.getRandomValues` in browsers and web workers and with the
+//! `crypto` module in Node.js. Where neither exists, splitting throws instead
+//! of falling back to a weaker source.
+//!
+//! Every function throws an `XplitError` object, `{ code, share, message }`:
+//! `code` is stable, upper snake case (`INVALID_CHECKSUM`), and can be
+//! switched on (the full list is in the generated TypeScript), `share` names the 1-based share at fault where one is, and
+//! `message` is prose for logs.
+//!
+//! Wasm linear memory is an `ArrayBuffer` the host page can read at any time,
+//! so the bindings wipe their copies of secrets and shares before returning
+//! (see [`Wiped`]). What they return has been copied out to JavaScript, and is
+//! the caller's to clear.
+
+use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use qrcode::render::svg;
+use qrcode::types::QrError;
+use qrcode::{EcLevel, QrCode};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use wasm_bindgen::prelude::*;
+
+use crate::armor::{armor_share, dearmor_share};
+use crate::chunked::{CHUNKED_MAGIC, check_chunks, is_chunked, split_chunked};
+use crate::grid::{is_grid, parse_grid};
+use crate::label::{label_share, strip_label};
+use crate::pad::{PADDED_MAGIC, Padding, is_padded, open_padded, split_padded};
+use crate::parity::{
+    PARITY_MAGIC, is_parity, parity_header, parity_share_len, recover_2of3, split_2of3_with_rng,
+};
+#[cfg(feature = "protect")]
+use crate::protect::{
+    MAX_MEMORY_KIB, envelope_params, is_protected, protect_share_with, protected_len,
+    unprotect_share,
+};
+use crate::stream::{Recoverer, Splitter};
+use crate::v1::{V1_MAGIC, is_v1, parse_v1};
+use crate::{
+    ChecksumPlacement, RecoveredSecret, ShareError, create_decoy, is_valid_share, recover_secret,
+    recover_secret_n, recover_typed, refresh_shares, share_fingerprint, share_len,
+    split_n_with_rng, split_secret_with_placement, split_secret_with_rng, unseal,
+};
+
+/// Initialize the panic hook for readable messages in the browser console
+///
+/// Only with the `panic-hook` feature. Bad input never panics: every binding
+/// throws an `XplitError` for it, so a panic is a bug in xplit, and the hook
+/// just makes it easier to report.
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "panic-hook")]
+    console_error_panic_hook::set_once();
+}
+
+/// Optional library features, each with whether this build has it
+const OPTIONAL_FEATURES: [(&str, bool); 8] = [
+    ("net", cfg!(feature = "net")),
+    ("panic-hook", cfg!(feature = "panic-hook")),
+    ("protect", cfg!(feature = "protect")),
+    ("sign", cfg!(feature = "sign")),
+    ("tracing", cfg!(feature = "tracing")),
+    ("unicode", cfg!(feature = "unicode")),
+    ("unsafe-simd", cfg!(feature = "unsafe-simd")),
+    ("zeroize", cfg!(feature = "zeroize")),
+];
+
+/// What a build of xplit is (for JS serialization)
+#[derive(Serialize)]
+pub struct VersionInfo {
+    /// The crate version, e.g. `"0.1.0"`
+    pub crate_version: &'static str,
+    /// Newest share format version the build reads and writes (see
+    /// [`crate::v1`]); older formats are always read too
+    pub format_version: u8,
+    /// Optional features compiled in, e.g. `"protect"` for
+    /// [`wasm_protect_share`] and [`wasm_bundle`]
+    pub features: Vec<&'static str>,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const VERSION_INFO_TS: &str = r#"
+/** Which xplit a page was built with, for logs and support requests */
+export interface VersionInfo {
+    crate_version: string;
+    format_version: number;
+    /** Optional features compiled in; hide what a missing one would offer */
+    features: ("net" | "panic-hook" | "protect" | "sign" | "tracing" | "unicode"
+        | "unsafe-simd" | "zeroize")[];
+}
+"#;
+
+/// Report the crate version, share format version and compiled-in features
+///
+/// Log it next to errors, so a share made elsewhere can be matched to a
+/// version that reads it.
+///
+/// # Returns
+/// A `VersionInfo` object `{ crate_version, format_version, features }`
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const { crate_version, features } = wasm_version();
+/// footer.textContent = `xplit ${crate_version}`;
+/// passphraseSection.hidden = !features.includes("protect");
+/// ```
+#[wasm_bindgen(unchecked_return_type = "VersionInfo")]
+pub fn wasm_version() -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&version_info()).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+fn version_info() -> VersionInfo {
+    VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        // Header magics end in their version digit
+        format_version: V1_MAGIC[V1_MAGIC.len() - 1] - b'0',
+        features: OPTIONAL_FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+    }
+}
+
+/// A secret or share buffer, wiped when dropped
+///
+/// Bindings take their inputs owned (`String`, `Vec<u8>`) rather than
+/// borrowed, so the copies wasm-bindgen makes of them can be wiped too.
+struct Wiped(Vec<u8>);
+
+impl std::ops::Deref for Wiped {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for Wiped {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Drop for Wiped {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.0);
+        #[cfg(not(feature = "zeroize"))]
+        {
+            self.0.fill(0);
+            std::hint::black_box(&self.0);
+        }
+    }
+}
+
+/// Error thrown by every binding: a stable `code` for callers to branch on,
+/// the 1-based `share` it concerns when that is known, and a `message` for
+/// people
+#[derive(Serialize)]
+struct XplitError {
+    code: &'static str,
+    share: Option<usize>,
+    message: String,
+    /// Only on `DECOY_LENGTH_MISMATCH`, so left out of every other error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_len: Option<usize>,
+}
+
+impl XplitError {
+    fn into_js(self) -> JsValue {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+        self.serialize(&serializer)
+            .unwrap_or_else(|_| JsValue::from_str(&self.message))
+    }
+}
+
+fn coded_error(code: &'static str, message: impl Into<String>) -> JsValue {
+    share_coded_error(code, None, message)
+}
+
+fn share_coded_error(
+    code: &'static str,
+    share: Option<usize>,
+    message: impl Into<String>,
+) -> JsValue {
+    XplitError {
+        code,
+        share,
+        message: message.into(),
+        required_len: None,
+    }
+    .into_js()
+}
+
+/// A failed recovery, blamed on the first share that fails its own checks
+/// when the error is about a single share
+fn recovery_error(error: &ShareError, shares: &[&[u8]]) -> JsValue {
+    blamed_error(error, shares, "Recovery failed")
+}
+
+/// [`recovery_error`] for any operation on a set of shares
+fn blamed_error(error: &ShareError, shares: &[&[u8]], failed: &str) -> JsValue {
+    let share = match error {
+        ShareError::InvalidChecksum | ShareError::ShareTooShort | ShareError::EmptyInput => {
+            shares.iter().position(|share| unseal(share).is_err())
+        }
+        _ => None,
+    };
+    share_coded_error(
+        error_code(error),
+        share.map(|i| i + 1),
+        format!("{}: {}", failed, error),
+    )
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const XPLIT_ERROR_TS: &str = r#"
+/** What every xplit function throws */
+export interface XplitError {
+    code:
+        // Share format errors, one per ShareError variant
+        | "INVALID_CHECKSUM" | "SHARE_TOO_SHORT" | "EMPTY" | "TOO_FEW_SHARES"
+        | "WRONG_PASSPHRASE" | "CORRUPTED_PROTECTED_SHARE" | "CORRUPTED_BUNDLE"
+        | "INVALID_KDF_PARAMS" | "INVALID_SIGNATURE" | "UNEXPECTED_SIGNER" | "CANCELLED"
+        | "LENGTH_MISMATCH" | "INVALID_CHUNK_SIZE" | "INVALID_PAD_BLOCK" | "INVALID_PADDING"
+        | "INVALID_LABEL" | "UNSUPPORTED_FORMAT" | "EMPTY_PAYLOAD" | "DIFFERENT_SPLITS"
+        | "NO_ARMOR" | "INVALID_ARMOR" | "DECOY_LENGTH_MISMATCH"
+        | "INVALID_CHUNK" | "INVALID_GRID" | "INVALID_GRID_ROW"
+        // Encoding errors
+        | "INVALID_BASE64" | "TRUNCATED_BASE64" | "NOT_A_STRING" | "NOT_UTF8"
+        // Errors of the bindings themselves
+        | "EMPTY_SECRET" | "INVALID_SHARE_COUNT" | "INVALID_OPTIONS" | "NO_RANDOM_SOURCE"
+        | "SERIALIZATION_FAILED" | "ALREADY_FINISHED" | "INVALID_ECC_LEVEL"
+        | "SECRET_TOO_LARGE" | "OUT_OF_MEMORY" | "SHARE_TOO_LARGE" | "QR_FAILED";
+    /** The 1-based share at fault, or null when no single share is */
+    share: number | null;
+    message: string;
+    /** On DECOY_LENGTH_MISMATCH only: the length the decoy secret must have */
+    required_len?: number;
+}
+"#;
+
+/// The platform's secure random source, checked to be available
+///
+/// `OsRng` panics if `getrandom` fails, so the source is probed first to turn
+/// a missing `crypto` API into an error JavaScript can catch.
+fn secure_rng() -> Result<OsRng, JsValue> {
+    getrandom::getrandom(&mut [0u8; 1]).map_err(|e| {
+        coded_error(
+            "NO_RANDOM_SOURCE",
+            format!("No secure random source available: {}", e),
+        )
+    })?;
+    Ok(OsRng)
+}
+
+/// Largest secret the one-shot split and recover functions take by default
+///
+/// A one-shot call holds the secret, both shares and their base64 text at
+/// once, about six times the secret, which is as much as a browser tab can be
+/// expected to spare. Larger secrets go through [`WasmSplitter`] and
+/// [`WasmRecoverer`], whose memory doesn't grow with the secret.
+pub const DEFAULT_MAX_INPUT_LEN: usize = 32 * 1024 * 1024;
+
+static MAX_INPUT_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_INPUT_LEN);
+
+/// Set the largest secret, in bytes, the one-shot split and recover functions
+/// take, e.g. lower on mobile or higher in Node.js
+///
+/// Applies to [`wasm_split`], [`wasm_split_json`], [`wasm_split_with_options`],
+/// [`wasm_split_bytes`], [`wasm_recover`] and [`wasm_recover_bytes`] for the
+/// rest of the module's life.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// wasm_set_max_input_len(8 * 1024 * 1024);
+/// ```
+#[wasm_bindgen]
+pub fn wasm_set_max_input_len(bytes: usize) {
+    MAX_INPUT_LEN.store(bytes, Ordering::Relaxed);
+}
+
+/// The limit set by [`wasm_set_max_input_len`], or [`DEFAULT_MAX_INPUT_LEN`]
+#[wasm_bindgen]
+pub fn wasm_max_input_len() -> usize {
+    MAX_INPUT_LEN.load(Ordering::Relaxed)
+}
+
+/// Refuse a secret over the input limit, pointing at the streaming API
+fn check_secret_len(len: usize, streaming: &str) -> Result<(), JsValue> {
+    let limit = wasm_max_input_len();
+    if len <= limit {
+        return Ok(());
+    }
+    Err(coded_error(
+        "SECRET_TOO_LARGE",
+        format!(
+            "Secret is {} bytes, over the {}-byte limit of this function; use {} to \
+             stream it, or raise the limit with wasm_set_max_input_len",
+            len, limit, streaming
+        ),
+    ))
+}
+
+/// Refuse shares whose secret would be over the input limit
+///
+/// Measured as plain shares, payload plus checksum; headers of the other
+/// formats count against the limit too.
+fn check_share_lens(shares: &[&[u8]]) -> Result<(), JsValue> {
+    let longest = shares.iter().map(|share| share.len()).max().unwrap_or(0);
+    check_secret_len(longest.saturating_sub(share_len(0)), "WasmRecoverer")
+}
+
+/// Reserve `len` bytes and give them back, so a call that would run out of
+/// memory throws `OUT_OF_MEMORY` before starting instead of aborting the
+/// instance halfway
+///
+/// Wasm memory only grows, so once reserved the space stays available to the
+/// allocations that follow.
+fn reserve_working_set(len: usize) -> Result<(), JsValue> {
+    Vec::<u8>::new().try_reserve_exact(len).map_err(|_| {
+        coded_error(
+            "OUT_OF_MEMORY",
+            format!("Not enough memory for the {} bytes this call needs", len),
+        )
+    })
+}
+
+/// Memory a one-shot split of a `secret_len`-byte secret needs on top of the
+/// secret: two shares, and their base64 text
+const fn split_working_set(secret_len: usize) -> usize {
+    let share = share_len(secret_len);
+    2 * share + 2 * share.div_ceil(3) * 4
+}
+
+/// Result of a split operation (for JSON serialization)
+#[derive(Serialize, Deserialize)]
+pub struct SplitResult {
+    /// The first share, encoded as `encoding` says
+    pub share1: String,
+    /// The second share, encoded as `encoding` says
+    pub share2: String,
+    /// `"base64"`, or `"base64url"` (URL-safe alphabet, no padding) when
+    /// asked for in [`SplitOptions::encoding`]
+    pub encoding: String,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const SPLIT_RESULT_TS: &str = r#"
+/** Both shares of a split, as text; every function taking shares reads either encoding */
+export interface SplitResult {
+    share1: string;
+    share2: string;
+    encoding: "base64" | "base64url";
+}
+"#;
+
+/// How shares are written out as text
+#[derive(Clone, Copy, Default)]
+enum ShareEncoding {
+    #[default]
+    Base64,
+    /// RFC 4648 URL-safe alphabet without padding, for links and fragments
+    Base64Url,
+}
+
+impl ShareEncoding {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Base64 => "base64",
+            Self::Base64Url => "base64url",
+        }
+    }
+
+    fn encode(self, share: &[u8]) -> String {
+        match self {
+            Self::Base64 => BASE64.encode(share),
+            Self::Base64Url => URL_SAFE_NO_PAD.encode(share),
+        }
+    }
+}
+
+/// Split a secret into two XOR-based shares with CRC32 integrity checks
+///
+/// # Arguments
+/// * `secret` - The secret text to split
+///
+/// # Returns
+/// A plain `{ share1, share2 }` object with both shares base64 encoded (typed
+/// as `SplitResult` in TypeScript), or throws an `XplitError`
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const { share1, share2 } = wasm_split("my secret message");
+/// console.log(`Share 1: ${share1}`);
+/// console.log(`Share 2: ${share2}`);
+/// ```
+#[wasm_bindgen(unchecked_return_type = "SplitResult")]
+pub fn wasm_split(secret: String) -> Result<JsValue, JsValue> {
+    let result = split_result(&Wiped(secret.into_bytes()))?;
+    serde_wasm_bindgen::to_value(&result).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+/// Split a secret into two shares, returned as a JSON string
+///
+/// Kept for callers written against the original API; use [`wasm_split`],
+/// which returns the object directly.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const data = JSON.parse(wasm_split_json("my secret message"));
+/// ```
+///
+/// @deprecated Use `wasm_split`, which returns `{ share1, share2 }` directly.
+#[wasm_bindgen]
+pub fn wasm_split_json(secret: String) -> Result<String, JsValue> {
+    let result = split_result(&Wiped(secret.into_bytes()))?;
+    serde_json::to_string(&result).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+fn split_result(secret: &[u8]) -> Result<SplitResult, JsValue> {
+    // Validate input
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    check_secret_len(secret.len(), "WasmSplitter")?;
+    reserve_working_set(split_working_set(secret.len()))?;
+
+    // Perform the split
+    let shares = split_secret_with_rng(secret, &mut secure_rng()?)
+        .map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))?;
+
+    // Encode shares as base64
+    Ok(encode_shares(shares, ShareEncoding::Base64))
+}
+
+/// Options for [`wasm_split_with_options`], given as a plain JS object
+///
+/// Every option is off when left out; unknown keys are an error, so a typo
+/// doesn't silently split without the option it meant.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SplitOptions {
+    /// Pad the secret to a multiple of this many bytes, hiding its length
+    pub pad_to: Option<usize>,
+    /// Pad the secret to the next power of two
+    #[serde(default)]
+    pub pad_pow2: bool,
+    /// Checksum every this many bytes separately, to locate corruption
+    pub chunk_size: Option<usize>,
+    /// Where a plain share keeps its CRC32: `"suffix"` (the default) or `"prefix"`
+    pub checksum_placement: Option<String>,
+    /// Name both shares carry, e.g. `"prod DB password"` (see [`crate::label`])
+    pub label: Option<String>,
+    /// How the shares are written: `"base64"` (the default) or `"base64url"`,
+    /// which survives URLs and fragments unescaped
+    pub encoding: Option<String>,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const SPLIT_OPTIONS_TS: &str = r#"
+/** Options for wasm_split_with_options; padding, chunking and prefix checksums don't mix */
+export interface SplitOptions {
+    padTo?: number;
+    padPow2?: boolean;
+    chunkSize?: number;
+    checksumPlacement?: "suffix" | "prefix";
+    /** Up to 64 bytes, no control characters; combines with every other option */
+    label?: string;
+    /** "base64url" for shares that go in links unescaped; combines with every other option */
+    encoding?: "base64" | "base64url";
+}
+"#;
+
+/// Split a secret with options, for the share formats beyond the plain one
+///
+/// # Arguments
+/// * `secret` - The secret text to split
+/// * `options` - A `SplitOptions` object, or `undefined` for the defaults of
+///   [`wasm_split`]
+///
+/// # Returns
+/// The same `{ share1, share2 }` object as [`wasm_split`], or throws an
+/// `XplitError`: `INVALID_OPTIONS` for an unknown key or options that can't
+/// be combined, `INVALID_PAD_BLOCK` or `INVALID_CHUNK_SIZE` for sizes out of
+/// range, `INVALID_LABEL` for a label that is too long or has control
+/// characters.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const { share1, share2 } = wasm_split_with_options("my secret", { padTo: 32 });
+/// const labeled = wasm_split_with_options("hunter2", { label: "prod DB password" });
+/// const linked = wasm_split_with_options("hunter2", { encoding: "base64url" });
+/// const link = `${location.origin}/recover#${linked.share1}`;
+/// ```
+#[wasm_bindgen(unchecked_return_type = "SplitResult")]
+pub fn wasm_split_with_options(
+    secret: String,
+    #[wasm_bindgen(unchecked_param_type = "SplitOptions | undefined")] options: JsValue,
+) -> Result<JsValue, JsValue> {
+    let options: SplitOptions = if options.is_undefined() || options.is_null() {
+        SplitOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| coded_error("INVALID_OPTIONS", format!("Invalid options: {}", e)))?
+    };
+    let encoding = match options.encoding.as_deref() {
+        None | Some("base64") => ShareEncoding::Base64,
+        Some("base64url") => ShareEncoding::Base64Url,
+        Some(other) => {
+            return Err(coded_error(
+                "INVALID_OPTIONS",
+                format!(
+                    "encoding must be \"base64\" or \"base64url\", got {:?}",
+                    other
+                ),
+            ));
+        }
+    };
+    let secret = Wiped(secret.into_bytes());
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    check_secret_len(secret.len(), "WasmSplitter")?;
+    reserve_working_set(split_working_set(secret.len()))?;
+    // The envelope formats draw their pad from the thread RNG, which is
+    // seeded from the same source; check it is there rather than panic
+    let rng = secure_rng()?;
+
+    let shares = split_with_options(&secret, &options, rng)?;
+    shares_value(shares, encoding)
+}
+
+fn split_with_options(
+    secret: &[u8],
+    options: &SplitOptions,
+    mut rng: OsRng,
+) -> Result<crate::TwoShares, JsValue> {
+    let invalid = |message: &str| coded_error("INVALID_OPTIONS", message);
+    let padding = match (options.pad_to, options.pad_pow2) {
+        (Some(_), true) => return Err(invalid("padTo and padPow2 can't be combined")),
+        (Some(block), false) => Some(Padding::Block(block)),
+        (None, true) => Some(Padding::PowerOfTwo),
+        (None, false) => None,
+    };
+    let prefix = match options.checksum_placement.as_deref() {
+        None | Some("suffix") => false,
+        Some("prefix") => true,
+        Some(other) => {
+            return Err(invalid(&format!(
+                "checksumPlacement must be \"suffix\" or \"prefix\", got {:?}",
+                other
+            )));
+        }
+    };
+
+    let split = match (padding, options.chunk_size) {
+        (Some(_), Some(_)) => return Err(invalid("Padding and chunkSize can't be combined")),
+        (Some(_), _) | (_, Some(_)) if prefix => {
+            return Err(invalid(
+                "checksumPlacement \"prefix\" only applies to plain shares",
+            ));
+        }
+        (Some(padding), None) => split_padded(secret, padding),
+        (None, Some(chunk_size)) => split_chunked(secret, chunk_size),
+        (None, None) if prefix => split_secret_with_placement(secret, ChecksumPlacement::Prefix),
+        (None, None) => split_secret_with_rng(secret, &mut rng),
+    };
+    let split = match (split, &options.label) {
+        (Ok(shares), Some(label)) => {
+            let (share1, share2) = (Wiped(shares.share1), Wiped(shares.share2));
+            label_share(&share1, label).and_then(|share1| {
+                Ok(crate::TwoShares {
+                    share1,
+                    share2: label_share(&share2, label)?,
+                })
+            })
+        }
+        (split, _) => split,
+    };
+    split.map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))
+}
+
+/// Recover the original secret from two shares
+///
+/// # Arguments
+/// * `share1` - First share (base64 or base64url encoded, padded or not, or a
+///   grid from `xplit split --grid`)
+/// * `share2` - Second share, likewise; the two needn't be written the same way
+///
+/// # Returns
+/// The recovered secret as a string, or throws an `XplitError`. A share cut off
+/// in transit throws `TRUNCATED_BASE64`, one with stray characters
+/// `INVALID_BASE64`, and a mistyped grid `INVALID_GRID` or `INVALID_GRID_ROW`.
+/// A secret that isn't valid UTF-8 throws `NOT_UTF8`; recover binary secrets
+/// with [`wasm_recover_bytes`].
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const share1 = "ZiTjk3OD6puSVM/JV3CYopI=";
+/// const share2 = "LkGP/xyvysz9JqOtdpOmJ8A=";
+/// const secret = wasm_recover(share1, share2);
+/// console.log(`Recovered secret: ${secret}`);
+/// ```
+#[wasm_bindgen]
+pub fn wasm_recover(share1: &str, share2: &str) -> Result<String, JsValue> {
+    // Decode from base64
+    let share1_bytes = decode_share(share1, 1)?;
+    let share2_bytes = decode_share(share2, 2)?;
+    check_share_lens(&[&share1_bytes, &share2_bytes])?;
+    // The secret, and its copy as a JS string
+    reserve_working_set(2 * share1_bytes.len())?;
+
+    // Perform the recovery
+    match recover_typed(&share1_bytes, &share2_bytes)
+        .map_err(|e| recovery_error(&e, &[&share1_bytes, &share2_bytes]))?
+    {
+        RecoveredSecret::Text(text) => Ok(text),
+        RecoveredSecret::Binary(bytes) => {
+            drop(Wiped(bytes));
+            Err(coded_error(
+                "NOT_UTF8",
+                "Recovered data is not valid UTF-8; use wasm_recover_bytes for binary secrets",
+            ))
+        }
+    }
+}
+
+/// Split a binary secret into two shares, returned as raw bytes
+///
+/// # Arguments
+/// * `secret` - The secret bytes to split (a `Uint8Array` from JavaScript)
+///
+/// # Returns
+/// An object `{ share1, share2 }` of `Uint8Array`s with CRC32 trailers, or the
+/// same error as [`wasm_split`] for an empty secret
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const key = crypto.getRandomValues(new Uint8Array(32));
+/// const { share1, share2 } = wasm_split_bytes(key);
+/// const recovered = wasm_recover_bytes(share1, share2); // Uint8Array
+/// ```
+#[wasm_bindgen(unchecked_return_type = "SplitBytesResult")]
+pub fn wasm_split_bytes(secret: Vec<u8>) -> Result<JsValue, JsValue> {
+    let secret = Wiped(secret);
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    check_secret_len(secret.len(), "WasmSplitter")?;
+    // Only the shares; the Uint8Arrays live outside wasm memory
+    reserve_working_set(2 * share_len(secret.len()))?;
+
+    let shares = split_secret_with_rng(&secret, &mut secure_rng()?)
+        .map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))?;
+    let (share1, share2) = (Wiped(shares.share1), Wiped(shares.share2));
+
+    bytes_object([("share1", &share1), ("share2", &share2)])
+}
+
+/// Recover a binary secret from two shares given as raw bytes
+///
+/// # Arguments
+/// * `share1` - First share as returned by [`wasm_split_bytes`]
+/// * `share2` - Second share as returned by [`wasm_split_bytes`]
+///
+/// # Returns
+/// The recovered secret as a `Uint8Array`, whether or not it is valid UTF-8,
+/// or throws an `XplitError`
+#[wasm_bindgen]
+pub fn wasm_recover_bytes(share1: Vec<u8>, share2: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    let (bytes1, bytes2) = (Wiped(share1), Wiped(share2));
+    let (_, share1) = unlabel(&bytes1, 1)?;
+    let (_, share2) = unlabel(&bytes2, 2)?;
+    check_share_lens(&[share1, share2])?;
+    reserve_working_set(share1.len())?;
+    recover_secret(share1, share2).map_err(|e| recovery_error(&e, &[share1, share2]))
+}
+
+/// Re-randomize a pair of shares, e.g. on a rotation schedule
+///
+/// Both shares get the same fresh random pad XOR'd in (see
+/// [`crate::refresh_shares`]), so the secret is never computed, in wasm memory
+/// or anywhere JavaScript can see. The new pair recovers the same secret; the
+/// old shares still do too, and have to be destroyed separately.
+///
+/// # Arguments
+/// * `share1` - First share (base64 encoded)
+/// * `share2` - Second share (base64 encoded)
+///
+/// # Returns
+/// The new pair as a `SplitResult`, or throws an `XplitError` naming the share
+/// at fault
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const { share1, share2 } = wasm_refresh(oldShare1, oldShare2);
+/// ```
+#[wasm_bindgen(unchecked_return_type = "SplitResult")]
+pub fn wasm_refresh(share1: &str, share2: &str) -> Result<JsValue, JsValue> {
+    let bytes1 = Wiped(decode_base64_share(share1, 1)?);
+    let bytes2 = Wiped(decode_base64_share(share2, 2)?);
+    let (label1, share1) = unlabel(&bytes1, 1)?;
+    let (label2, share2) = unlabel(&bytes2, 2)?;
+    // refresh_shares draws from the thread RNG; make sure it has a source
+    secure_rng()?;
+
+    let shares = refresh_shares(share1, share2)
+        .map_err(|e| blamed_error(&e, &[share1, share2], "Refresh failed"))?;
+    // Each new share keeps its old share's label
+    let relabel = |share: Vec<u8>, label: Option<&str>| match label {
+        Some(label) => label_share(&Wiped(share), label),
+        None => Ok(share),
+    };
+    let shares = relabel(shares.share1, label1)
+        .and_then(|share1| {
+            Ok(crate::TwoShares {
+                share1,
+                share2: relabel(shares.share2, label2)?,
+            })
+        })
+        .map_err(|e| coded_error(error_code(&e), format!("Refresh failed: {}", e)))?;
+    shares_value(shares, ShareEncoding::Base64)
+}
+
+/// Argon2id costs for [`wasm_bundle`] and [`wasm_protect_share`], given as a
+/// plain JS object
+///
+/// Left-out costs take the library defaults (see
+/// [`crate::protect::KdfParams`]); unknown keys are an error.
+#[cfg(feature = "protect")]
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KdfOptions {
+    /// Memory cost in KiB
+    pub memory_kib: Option<u32>,
+    /// Passes over the memory
+    pub iterations: Option<u32>,
+    /// Degree of parallelism
+    pub parallelism: Option<u32>,
+}
+
+#[cfg(feature = "protect")]
+#[wasm_bindgen(typescript_custom_section)]
+const KDF_OPTIONS_TS: &str = r#"
+/**
+ * Argon2id costs for wasm_bundle and wasm_protect_share.
+ *
+ * The defaults (19 MiB, 2 passes, 1 lane) are OWASP's recommended minimum and
+ * suit most browsers. Where 19 MiB is too much, trade memory for passes at the
+ * same strength: { memoryKib: 12288, iterations: 3 } or
+ * { memoryKib: 7168, iterations: 5 }. WebAssembly runs Argon2 on one thread,
+ * so more lanes only cost time.
+ */
+export interface KdfOptions {
+    /** Memory cost in KiB, at least 8 per lane and at most 1048576 (default 19456) */
+    memoryKib?: number;
+    /** Passes over the memory, at most 16 (default 2) */
+    iterations?: number;
+    /** Lanes, at most 16 (default 1) */
+    parallelism?: number;
+}
+"#;
+
+/// The costs in a `KdfOptions` object, or the defaults for `undefined`
+#[cfg(feature = "protect")]
+fn kdf_params(options: JsValue) -> Result<crate::protect::KdfParams, JsValue> {
+    let options: KdfOptions = if options.is_undefined() || options.is_null() {
+        KdfOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| coded_error("INVALID_OPTIONS", format!("Invalid options: {}", e)))?
+    };
+    let defaults = crate::protect::KdfParams::default();
+    let params = crate::protect::KdfParams {
+        memory_kib: options.memory_kib.unwrap_or(defaults.memory_kib),
+        iterations: options.iterations.unwrap_or(defaults.iterations),
+        parallelism: options.parallelism.unwrap_or(defaults.parallelism),
+    };
+    // Costs over the maximums are refused by the library, not reserved here
+    reserve_kdf_memory(params.memory_kib.min(MAX_MEMORY_KIB))?;
+    Ok(params)
+}
+
+/// Reserve the memory Argon2 will fill, so a cost the page can't afford
+/// throws `OUT_OF_MEMORY` instead of aborting the instance
+#[cfg(feature = "protect")]
+fn reserve_kdf_memory(memory_kib: u32) -> Result<(), JsValue> {
+    let len = usize::try_from(memory_kib).map_or(usize::MAX, |kib| kib.saturating_mul(1024));
+    reserve_working_set(len)
+}
+
+/// [`reserve_kdf_memory`] for the costs recorded in a protected share or
+/// bundle; a header too damaged to read is left for opening it to report
+#[cfg(feature = "protect")]
+fn reserve_envelope_memory(envelope: &[u8]) -> Result<(), JsValue> {
+    match envelope_params(envelope) {
+        Ok(params) => reserve_kdf_memory(params.memory_kib),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Encrypt both shares into one file under a passphrase, for transport
+///
+/// Whoever has the passphrase has both shares, and so the secret; see
+/// [`crate::bundle`]. The costs used are stored in the bundle, so
+/// [`wasm_unbundle`] needs only the passphrase.
+///
+/// # Arguments
+/// * `share1` - First share (base64 encoded), kept exactly as given
+/// * `share2` - Second share (base64 encoded), kept exactly as given
+/// * `passphrase` - The passphrase to encrypt under
+/// * `options` - A `KdfOptions` object, or `undefined` for the defaults
+///
+/// # Returns
+/// The bundle as a `Uint8Array`, or throws an `XplitError`: `INVALID_OPTIONS`
+/// for an unknown key, `INVALID_KDF_PARAMS` for costs argon2 refuses or over
+/// the maximums, `OUT_OF_MEMORY` for a memory cost the page can't spare, or
+/// the decoding errors of either share
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const file = wasm_bundle(share1, share2, passphrase, { memoryKib: 8192 });
+/// ```
+#[cfg(feature = "protect")]
+#[wasm_bindgen]
+pub fn wasm_bundle(
+    share1: &str,
+    share2: &str,
+    passphrase: String,
+    #[wasm_bindgen(unchecked_param_type = "KdfOptions | undefined")] options: JsValue,
+) -> Result<Vec<u8>, JsValue> {
+    let passphrase = Wiped(passphrase.into_bytes());
+    let params = kdf_params(options)?;
+    // The envelope draws its salt and nonce from the thread RNG
+    secure_rng()?;
+
+    let bytes1 = Wiped(decode_base64_share(share1, 1)?);
+    let bytes2 = Wiped(decode_base64_share(share2, 2)?);
+    for (index, bytes) in [(1, &bytes1), (2, &bytes2)] {
+        if bytes.is_empty() {
+            return Err(share_coded_error("EMPTY", Some(index), "Share is empty"));
+        }
+    }
+    crate::bundle::bundle_shares(&bytes1, &bytes2, wiped_str(&passphrase), params)
+        .map_err(|e| coded_error(error_code(&e), format!("Bundle failed: {}", e)))
+}
+
+/// Open a bundle made by [`wasm_bundle`]
+///
+/// # Arguments
+/// * `bundle` - The bundle bytes (a `Uint8Array`)
+/// * `passphrase` - The passphrase it was made with
+///
+/// # Returns
+/// Both shares as a `SplitResult`, as they were given to [`wasm_bundle`], or
+/// throws an `XplitError`: `WRONG_PASSPHRASE` when the passphrase doesn't
+/// match, `CORRUPTED_BUNDLE` when the file is damaged, isn't a bundle or asks
+/// for costs over the maximums
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const bytes = new Uint8Array(await file.arrayBuffer());
+/// const { share1, share2 } = wasm_unbundle(bytes, passphrase);
+/// ```
+#[cfg(feature = "protect")]
+#[wasm_bindgen(unchecked_return_type = "SplitResult")]
+pub fn wasm_unbundle(bundle: Vec<u8>, passphrase: String) -> Result<JsValue, JsValue> {
+    let passphrase = Wiped(passphrase.into_bytes());
+    reserve_envelope_memory(&bundle)?;
+    let shares = crate::bundle::unbundle_shares(&bundle, wiped_str(&passphrase))
+        .map_err(|e| coded_error(error_code(&e), format!("Unbundle failed: {}", e)))?;
+    shares_value(shares, ShareEncoding::Base64)
+}
+
+/// Lock a share under its custodian's passphrase, like `xplit split --protect`
+///
+/// Only plain shares can be protected, as with the CLI. A label stays outside
+/// the envelope, so a protected share can still be told apart without its
+/// passphrase. The costs used are stored in the share, so
+/// [`wasm_unprotect_share`] needs only the passphrase.
+///
+/// # Arguments
+/// * `share` - The share (base64 encoded, leniently as by [`wasm_verify_share`])
+/// * `passphrase` - The passphrase to lock it under
+/// * `options` - A `KdfOptions` object, or `undefined` for the defaults
+///
+/// # Returns
+/// The protected share (base64 encoded), or throws an `XplitError`:
+/// `INVALID_OPTIONS` or `INVALID_KDF_PARAMS` for bad costs, `OUT_OF_MEMORY`
+/// for a memory cost the page can't spare, `UNSUPPORTED_FORMAT` for a chunked, padded or already protected share, or
+/// the share's own error (`INVALID_CHECKSUM`, `INVALID_BASE64`, ...)
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const locked = wasm_protect_share(share, passphrase, { memoryKib: 12288, iterations: 3 });
+/// ```
+#[cfg(feature = "protect")]
+#[wasm_bindgen]
+pub fn wasm_protect_share(
+    share: &str,
+    passphrase: String,
+    #[wasm_bindgen(unchecked_param_type = "KdfOptions | undefined")] options: JsValue,
+) -> Result<String, JsValue> {
+    let passphrase = Wiped(passphrase.into_bytes());
+    let params = kdf_params(options)?;
+    // The envelope draws its salt and nonce from the thread RNG
+    secure_rng()?;
+
+    let bytes = Wiped(decode_nonempty(share)?);
+    let (label, inner) = strip_label(&bytes)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid label: {}", e)))?;
+    let failed = |e: ShareError| coded_error(error_code(&e), format!("Protect failed: {}", e));
+    if is_chunked(inner) || is_padded(inner) || is_protected(inner) {
+        return Err(failed(ShareError::UnsupportedFormat));
+    }
+    // Unlocking checks the inner checksum, so a damaged share could never be
+    // unlocked again
+    unseal(inner).map_err(failed)?;
+
+    let protected =
+        Wiped(protect_share_with(inner, wiped_str(&passphrase), params).map_err(failed)?);
+    let share = match label {
+        Some(label) => Wiped(label_share(&protected, label).map_err(failed)?),
+        None => protected,
+    };
+    Ok(BASE64.encode(&*share))
+}
+
+/// Unlock a share made by [`wasm_protect_share`] or `xplit split --protect`
+///
+/// # Arguments
+/// * `share` - The protected share (base64 encoded, leniently as by
+///   [`wasm_verify_share`])
+/// * `passphrase` - The passphrase it was locked under
+///
+/// # Returns
+/// The plain share (base64 encoded), keeping any label, or throws an
+/// `XplitError`: `WRONG_PASSPHRASE` when the passphrase doesn't match,
+/// `CORRUPTED_PROTECTED_SHARE` when the share is damaged, isn't protected or
+/// asks for costs over the maximums
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// if (wasm_inspect(pasted).protected) {
+///     pasted = wasm_unprotect_share(pasted, prompt("Passphrase"));
+/// }
+/// ```
+#[cfg(feature = "protect")]
+#[wasm_bindgen]
+pub fn wasm_unprotect_share(share: &str, passphrase: String) -> Result<String, JsValue> {
+    let passphrase = Wiped(passphrase.into_bytes());
+    let bytes = Wiped(decode_nonempty(share)?);
+    let (label, inner) = strip_label(&bytes)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid label: {}", e)))?;
+    let failed = |e: ShareError| coded_error(error_code(&e), format!("Unprotect failed: {}", e));
+    reserve_envelope_memory(inner)?;
+
+    let plain = Wiped(unprotect_share(inner, wiped_str(&passphrase)).map_err(failed)?);
+    let share = match label {
+        Some(label) => Wiped(label_share(&plain, label).map_err(failed)?),
+        None => plain,
+    };
+    Ok(BASE64.encode(&*share))
+}
+
+/// A wiped copy of a `String` argument, as text again
+fn wiped_str(text: &Wiped) -> &str {
+    // Only ever built from a String, so always UTF-8
+    std::str::from_utf8(text).unwrap_or_default()
+}
+
+/// Both shares as a `SplitResult` object
+fn shares_value(shares: crate::TwoShares, encoding: ShareEncoding) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&encode_shares(shares, encoding)).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+/// Both shares as text, wiping the raw shares
+fn encode_shares(shares: crate::TwoShares, encoding: ShareEncoding) -> SplitResult {
+    let (share1, share2) = (Wiped(shares.share1), Wiped(shares.share2));
+    SplitResult {
+        share1: encoding.encode(&share1),
+        share2: encoding.encode(&share2),
+        encoding: encoding.name().to_owned(),
+    }
+}
+
+/// Decode a base64 share and remove its label, blaming a failure on share `index`
+fn decode_share(share: &str, index: usize) -> Result<Wiped, JsValue> {
+    let bytes = Wiped(decode_base64_share(share, index)?);
+    Ok(Wiped(unlabel(&bytes, index)?.1.to_vec()))
+}
+
+/// Decode a share as a grid or in either base64 alphabet (see
+/// [`decode_text`]), blaming a failure on share `index`
+fn decode_base64_share(share: &str, index: usize) -> Result<Vec<u8>, JsValue> {
+    decode_text(share).map_err(|e| {
+        share_coded_error(
+            e.code(),
+            Some(index),
+            format!("Failed to decode share{}: {}", index, e),
+        )
+    })
+}
+
+/// Split a share into its label, if any, and the share it names
+fn unlabel(share: &[u8], index: usize) -> Result<(Option<&str>, &[u8]), JsValue> {
+    strip_label(share).map_err(|e| {
+        share_coded_error(
+            error_code(&e),
+            Some(index),
+            format!("Failed to read share{}: {}", index, e),
+        )
+    })
+}
+
+/// Split a large secret chunk by chunk, e.g. from `File.stream()`
+///
+/// Each pushed chunk comes back as the matching chunks of both shares, ready to
+/// write out, and nothing is held between pushes, so memory stays at about
+/// three times the chunk size however large the secret. The shares are the
+/// usual format, identical to what [`wasm_split_bytes`] returns once
+/// [`WasmSplitter::finish`]'s trailers are appended.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const splitter = new WasmSplitter();
+/// for await (const chunk of file.stream()) {
+///     const { share1_chunk, share2_chunk } = splitter.push(chunk);
+///     await out1.write(share1_chunk);
+///     await out2.write(share2_chunk);
+/// }
+/// const { share1_trailer, share2_trailer } = splitter.finish();
+/// await out1.write(share1_trailer);
+/// await out2.write(share2_trailer);
+/// ```
+#[wasm_bindgen]
+pub struct WasmSplitter {
+    inner: Option<Splitter<OsRng>>,
+}
+
+#[wasm_bindgen]
+impl WasmSplitter {
+    /// Start a split, failing if there is no secure random source
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<WasmSplitter, JsValue> {
+        Ok(Self {
+            inner: Some(Splitter::with_rng(secure_rng()?)),
+        })
+    }
+
+    /// Split the next chunk, returning `{ share1_chunk, share2_chunk }`
+    #[wasm_bindgen(unchecked_return_type = "SplitterChunks")]
+    pub fn push(&mut self, chunk: &[u8]) -> Result<JsValue, JsValue> {
+        let splitter = self
+            .inner
+            .as_mut()
+            .ok_or_else(|| coded_error("ALREADY_FINISHED", "Splitter already finished"))?;
+        let (share1, share2) = splitter.push(chunk);
+        bytes_object([("share1_chunk", &share1), ("share2_chunk", &share2)])
+    }
+
+    /// End the split, returning `{ share1_trailer, share2_trailer }`, the 4
+    /// checksum bytes to append to each share
+    #[wasm_bindgen(unchecked_return_type = "SplitterTrailers")]
+    pub fn finish(&mut self) -> Result<JsValue, JsValue> {
+        let splitter = self
+            .inner
+            .take()
+            .ok_or_else(|| coded_error("ALREADY_FINISHED", "Splitter already finished"))?;
+        let (trailer1, trailer2) = splitter
+            .finish()
+            .map_err(|_| coded_error("EMPTY_SECRET", "Secret cannot be empty"))?;
+        bytes_object([("share1_trailer", &trailer1), ("share2_trailer", &trailer2)])
+    }
+}
+
+/// Bytes split between yields to the event loop in [`wasm_split_async`]
+pub const ASYNC_CHUNK_SIZE: usize = 1024 * 1024;
+
+#[wasm_bindgen(typescript_custom_section)]
+const SPLIT_ASYNC_OPTIONS_TS: &str = r#"
+/** Options for wasm_split_async; every key is optional */
+export interface SplitAsyncOptions {
+    /** Called after each chunk with the bytes split so far and the total */
+    onProgress?: (bytesDone: number, bytesTotal: number) => void;
+    /** Stops the split once aborted; an AbortSignal will do */
+    signal?: { readonly aborted: boolean };
+}
+"#;
+
+/// Split a binary secret without blocking the page
+///
+/// Works through the secret [`ASYNC_CHUNK_SIZE`] bytes at a time with the
+/// streaming splitter, handing control back to the event loop between
+/// chunks, so a large split leaves the page responsive. The shares are
+/// exactly those of [`wasm_split_bytes`].
+///
+/// # Arguments
+/// * `secret` - The secret bytes to split (a `Uint8Array` from JavaScript)
+/// * `options` - Optional `{ onProgress, signal }` (typed as
+///   `SplitAsyncOptions`)
+///
+/// # Returns
+/// A promise of `{ share1, share2 }`, as from [`wasm_split_bytes`]. It rejects
+/// with an `XplitError`: `EMPTY_SECRET`, `INVALID_OPTIONS` for an unknown
+/// key, or `CANCELLED` once `signal` is aborted, in which case the partial
+/// shares are wiped. An exception thrown by `onProgress` rejects it as is.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const controller = new AbortController();
+/// cancelButton.onclick = () => controller.abort();
+/// const { share1, share2 } = await wasm_split_async(bytes, {
+///     onProgress: (done, total) => (progress.value = done / total),
+///     signal: controller.signal,
+/// });
+/// ```
+#[wasm_bindgen(unchecked_return_type = "Promise<SplitBytesResult>")]
+pub fn wasm_split_async(
+    secret: Vec<u8>,
+    #[wasm_bindgen(unchecked_param_type = "SplitAsyncOptions | undefined")] options: JsValue,
+) -> js_sys::Promise {
+    let secret = Wiped(secret);
+    wasm_bindgen_futures::future_to_promise(async move {
+        let (on_progress, signal) = split_async_options(&options)?;
+        split_async(&secret, on_progress.as_ref(), signal.as_ref()).await
+    })
+}
+
+/// Read `{ onProgress, signal }`, refusing anything else
+fn split_async_options(
+    options: &JsValue,
+) -> Result<(Option<js_sys::Function>, Option<JsValue>), JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok((None, None));
+    }
+    let invalid = |message: String| coded_error("INVALID_OPTIONS", message);
+    let object: &js_sys::Object = options
+        .dyn_ref()
+        .ok_or_else(|| invalid("Options must be an object".to_owned()))?;
+    let (mut on_progress, mut signal) = (None, None);
+    for key in js_sys::Object::keys(object).iter() {
+        let value = js_sys::Reflect::get(object, &key)?;
+        match key.as_string().as_deref() {
+            Some("onProgress") if value.is_undefined() => {}
+            Some("onProgress") => {
+                on_progress = Some(
+                    value
+                        .dyn_into::<js_sys::Function>()
+                        .map_err(|_| invalid("onProgress must be a function".to_owned()))?,
+                );
+            }
+            Some("signal") => signal = Some(value).filter(|v| !v.is_undefined()),
+            other => {
+                return Err(invalid(format!(
+                    "Unknown option {:?}, expected onProgress or signal",
+                    other.unwrap_or_default()
+                )));
+            }
+        }
+    }
+    Ok((on_progress, signal))
+}
+
+async fn split_async(
+    secret: &[u8],
+    on_progress: Option<&js_sys::Function>,
+    signal: Option<&JsValue>,
+) -> Result<JsValue, JsValue> {
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    let cancelled = || {
+        signal.is_some_and(|signal| {
+            js_sys::Reflect::get(signal, &JsValue::from_str("aborted"))
+                .is_ok_and(|aborted| aborted.is_truthy())
+        })
+    };
+
+    let mut splitter = Splitter::with_rng(secure_rng()?);
+    let total = secret.len() as f64;
+    let mut share1 = Wiped(Vec::with_capacity(crate::share_len(secret.len())));
+    let mut share2 = Wiped(Vec::with_capacity(crate::share_len(secret.len())));
+    let mut done = 0;
+    for chunk in secret.chunks(ASYNC_CHUNK_SIZE) {
+        if cancelled() {
+            return Err(coded_error(
+                error_code(&ShareError::Cancelled),
+                "Split was cancelled",
+            ));
+        }
+        let (chunk1, chunk2) = splitter.push(chunk);
+        share1.extend_from_slice(&Wiped(chunk1));
+        share2.extend_from_slice(&Wiped(chunk2));
+        done += chunk.len();
+        if let Some(on_progress) = on_progress {
+            on_progress.call2(&JsValue::NULL, &(done as f64).into(), &total.into())?;
+        }
+        next_task().await?;
+    }
+    if cancelled() {
+        return Err(coded_error(
+            error_code(&ShareError::Cancelled),
+            "Split was cancelled",
+        ));
+    }
+
+    let (trailer1, trailer2) = splitter
+        .finish()
+        .map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))?;
+    share1.extend_from_slice(&trailer1);
+    share2.extend_from_slice(&trailer2);
+    bytes_object([("share1", &share1), ("share2", &share2)])
+}
+
+/// Resolve on a fresh task via `setTimeout`, so the page can render and
+/// handle input in between; a resolved promise would only queue a microtask
+async fn next_task() -> Result<(), JsValue> {
+    let global = js_sys::global();
+    let set_timeout: js_sys::Function =
+        js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))?.dyn_into()?;
+    let tick = js_sys::Promise::new(&mut |resolve, _| {
+        let _ = set_timeout.call2(&global, &resolve, &JsValue::from(0));
+    });
+    wasm_bindgen_futures::JsFuture::from(tick).await.map(drop)
+}
+
+/// Recover a large secret chunk by chunk from two streamed shares
+///
+/// Push chunks of both shares side by side; the secret comes back as far as
+/// both have arrived. Only what one share is ahead of the other is held, so
+/// pushing equal-sized chunks of both keeps memory flat. The checksums can
+/// only be checked at the end: **discard everything written if
+/// [`WasmRecoverer::finish`] throws**.
+#[wasm_bindgen]
+pub struct WasmRecoverer {
+    inner: Option<Recoverer>,
+}
+
+#[wasm_bindgen]
+impl WasmRecoverer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmRecoverer {
+        Self {
+            inner: Some(Recoverer::new()),
+        }
+    }
+
+    /// Take the next chunk of each share (either may be empty), returning the
+    /// next chunk of the secret as a `Uint8Array`
+    pub fn push(&mut self, share1_chunk: &[u8], share2_chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let recoverer = self
+            .inner
+            .as_mut()
+            .ok_or_else(|| coded_error("ALREADY_FINISHED", "Recoverer already finished"))?;
+        Ok(recoverer.push(share1_chunk, share2_chunk))
+    }
+
+    /// Verify both checksums once both shares have been pushed in full,
+    /// returning the secret's length in bytes
+    pub fn finish(&mut self) -> Result<f64, JsValue> {
+        let recoverer = self
+            .inner
+            .take()
+            .ok_or_else(|| coded_error("ALREADY_FINISHED", "Recoverer already finished"))?;
+        recoverer
+            .finish()
+            .map(|len| len as f64)
+            .map_err(|e| coded_error(error_code(&e), format!("Recovery failed: {}", e)))
+    }
+}
+
+impl Default for WasmRecoverer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const BYTES_OBJECTS_TS: &str = r#"
+/** Both shares of a binary split, with their CRC32 trailers */
+export interface SplitBytesResult {
+    share1: Uint8Array;
+    share2: Uint8Array;
+}
+
+/** The chunks of both shares for one chunk of the secret */
+export interface SplitterChunks {
+    share1_chunk: Uint8Array;
+    share2_chunk: Uint8Array;
+}
+
+/** The 4 checksum bytes that end each share */
+export interface SplitterTrailers {
+    share1_trailer: Uint8Array;
+    share2_trailer: Uint8Array;
+}
+"#;
+
+/// A plain object of named `Uint8Array`s
+fn bytes_object<const N: usize>(fields: [(&str, &[u8]); N]) -> Result<JsValue, JsValue> {
+    let object = js_sys::Object::new();
+    for (name, bytes) in fields {
+        js_sys::Reflect::set(
+            &object,
+            &JsValue::from_str(name),
+            &js_sys::Uint8Array::from(bytes),
+        )?;
+    }
+    Ok(object.into())
+}
+
+/// Largest `n` accepted by [`wasm_split_n`]
+///
+/// Each share is as long as the secret, so this keeps a slip like `n = 1e6`
+/// from allocating a million copies.
+pub const MAX_SHARES_N: u32 = 255;
+
+/// Split a secret into `n` XOR-based shares, all of which are required for recovery
+///
+/// The shares are ordinary CRC32-sealed shares, exactly as the native library
+/// writes them, so they cross freely between the CLI and the browser.
+///
+/// # Arguments
+/// * `secret` - The secret bytes to split (a `Uint8Array` from JavaScript)
+/// * `n` - Number of shares, from 2 to [`MAX_SHARES_N`]
+///
+/// # Returns
+/// An array of `n` base64 encoded shares, or throws an `XplitError`
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const shares = wasm_split_n(new TextEncoder().encode("my secret message"), 4);
+/// const secret = new TextDecoder().decode(wasm_recover_n(shares));
+/// ```
+#[wasm_bindgen]
+pub fn wasm_split_n(secret: Vec<u8>, n: u32) -> Result<Vec<String>, JsValue> {
+    let secret = Wiped(secret);
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    if !(2..=MAX_SHARES_N).contains(&n) {
+        return Err(coded_error(
+            "INVALID_SHARE_COUNT",
+            format!(
+                "Share count must be between 2 and {}, got {}",
+                MAX_SHARES_N, n
+            ),
+        ));
+    }
+
+    let shares = split_n_with_rng(&secret, n as usize, &mut secure_rng()?)
+        .map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))?;
+    Ok(shares
+        .into_iter()
+        .map(|share| BASE64.encode(&*Wiped(share)))
+        .collect())
+}
+
+/// Recover the original secret from all shares of an N-of-N split
+///
+/// # Arguments
+/// * `shares` - Array of shares (base64 or base64url encoded), in any order
+///
+/// # Returns
+/// The recovered secret as a `Uint8Array`, or throws an `XplitError`
+#[wasm_bindgen]
+pub fn wasm_recover_n(
+    #[wasm_bindgen(unchecked_param_type = "string[]")] shares: Vec<JsValue>,
+) -> Result<Vec<u8>, JsValue> {
+    if shares.len() < 2 {
+        return Err(coded_error(
+            "INVALID_SHARE_COUNT",
+            format!("At least 2 shares are required, got {}", shares.len()),
+        ));
+    }
+
+    let decoded = shares
+        .iter()
+        .enumerate()
+        .map(|(i, share)| {
+            let share = share.as_string().ok_or_else(|| {
+                share_coded_error(
+                    "NOT_A_STRING",
+                    Some(i + 1),
+                    format!("share{} is not a string", i + 1),
+                )
+            })?;
+            let share = Wiped(share.into_bytes());
+            decode_text(wiped_str(&share)).map(Wiped).map_err(|e| {
+                share_coded_error(
+                    e.code(),
+                    Some(i + 1),
+                    format!("Failed to decode share{}: {}", i + 1, e),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(i) = decoded
+        .iter()
+        .position(|share| share.len() != decoded[0].len())
+    {
+        return Err(share_coded_error(
+            "LENGTH_MISMATCH",
+            Some(i + 1),
+            format!("share{} is not the same length as share1", i + 1),
+        ));
+    }
+    let share_refs: Vec<&[u8]> = decoded.iter().map(|share| share.as_slice()).collect();
+
+    recover_secret_n(&share_refs).map_err(|e| recovery_error(&e, &share_refs))
+}
+
+/// Result of a 2-of-3 split (for JS serialization)
+#[derive(Serialize, Deserialize)]
+pub struct Split2of3Result {
+    /// Share number 1 (base64 encoded)
+    pub share1: String,
+    /// Share number 2 (base64 encoded)
+    pub share2: String,
+    /// Share number 3 (base64 encoded)
+    pub share3: String,
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const SPLIT_2OF3_RESULT_TS: &str = r#"
+/** The three shares of a 2-of-3 split, base64 encoded; any two recover the secret */
+export interface Split2of3Result {
+    share1: string;
+    share2: string;
+    share3: string;
+}
+"#;
+
+/// Split a secret into three shares, any two of which recover it
+///
+/// Each share records its number and which split it came from (see
+/// [`crate::parity`]), and is twice the secret's length.
+///
+/// # Arguments
+/// * `secret` - The secret text to split
+///
+/// # Returns
+/// A `Split2of3Result` object `{ share1, share2, share3 }`, or throws an
+/// `XplitError`
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const { share1, share2, share3 } = wasm_split_2of3("my secret message");
+/// ```
+#[wasm_bindgen(unchecked_return_type = "Split2of3Result")]
+pub fn wasm_split_2of3(secret: String) -> Result<JsValue, JsValue> {
+    let secret = Wiped(secret.into_bytes());
+    if secret.is_empty() {
+        return Err(coded_error("EMPTY_SECRET", "Secret cannot be empty"));
+    }
+    let shares = split_2of3_with_rng(&secret, &mut secure_rng()?)
+        .map_err(|e| coded_error(error_code(&e), format!("Split failed: {}", e)))?;
+    let (share1, share2, share3) = (
+        Wiped(shares.share1),
+        Wiped(shares.share2),
+        Wiped(shares.share3),
+    );
+    let result = Split2of3Result {
+        share1: BASE64.encode(&*share1),
+        share2: BASE64.encode(&*share2),
+        share3: BASE64.encode(&*share3),
+    };
+    serde_wasm_bindgen::to_value(&result).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+/// Recover the secret from any two shares of [`wasm_split_2of3`]
+///
+/// The shares say which they are, so any pair works in either order.
+///
+/// # Arguments
+/// * `a` - One share (base64 or base64url encoded)
+/// * `b` - Another share of the same split
+///
+/// # Returns
+/// The recovered secret as a string, or throws an `XplitError`:
+/// `DIFFERENT_SPLITS` for two intact shares of different splits,
+/// `INVALID_CHECKSUM` naming the damaged share, `TOO_FEW_SHARES` for the same
+/// share twice, `UNSUPPORTED_FORMAT` for a share that isn't 2-of-3, or
+/// `NOT_UTF8` for a binary secret
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const secret = wasm_recover_2of3(share3, share1);
+/// ```
+#[wasm_bindgen]
+pub fn wasm_recover_2of3(a: &str, b: &str) -> Result<String, JsValue> {
+    let a = decode_share(a, 1)?;
+    let b = decode_share(b, 2)?;
+    let secret = recover_2of3(&a, &b).map_err(|e| {
+        let share = [&a, &b].iter().position(|share| !is_parity(share));
+        match e {
+            ShareError::UnsupportedFormat => share_coded_error(
+                error_code(&e),
+                share.map(|i| i + 1),
+                format!("Recovery failed: {}", e),
+            ),
+            e => recovery_error(&e, &[&a, &b]),
+        }
+    })?;
+    String::from_utf8(secret).map_err(|e| {
+        drop(Wiped(e.into_bytes()));
+        coded_error(
+            "NOT_UTF8",
+            "Recovered data is not valid UTF-8; 2-of-3 splits from wasm_split_2of3 are text",
+        )
+    })
+}
 
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use serde::{Deserialize, Serialize};
-use wasm_bindgen::prelude::*;
+/// Make a decoy share 2, which recovers `decoy_secret` with `share1` instead
+/// of the real secret
+///
+/// For a custodian who may be forced to hand over a pair: `share1` with the
+/// decoy recovers the decoy secret, while `share1` with the real share 2 still
+/// recovers the real one. The decoy passes every check a real share does.
+/// See [`crate::create_decoy`], whose caveats apply as they are:
+/// - the real share 2 has to stay hidden; whoever sees both knows one of them
+///   is a decoy
+/// - the decoy secret has to be exactly as long as the real secret, which the
+///   shares give away, and believable as the secret on its own
+/// - the decoy comes back without `share1`'s label, since labels name one
+///   share; label it as the real share 2 is labeled
+///
+/// Only plain and v1 shares take a decoy. Padded shares are refused: their
+/// padding is split along with the secret and checked on recovery, so a pair
+/// made with `padTo` would give a decoy away. Chunked, 2-of-3 and protected
+/// shares are refused too.
+///
+/// # Arguments
+/// * `share1` - Share 1 of the real pair (base64 encoded, leniently as by
+///   [`wasm_verify_share`])
+/// * `decoy_secret` - The secret the decoy pair should recover
+///
+/// # Returns
+/// The decoy share 2 (base64 encoded), or throws an `XplitError`:
+/// `DECOY_LENGTH_MISMATCH`, with the length needed in `required_len`, when the
+/// decoy secret is the wrong length, `UNSUPPORTED_FORMAT` for a share that
+/// can't take a decoy, or the share's own error (`INVALID_CHECKSUM`, ...)
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const decoy = new TextEncoder().encode("Goodbye, All!");
+/// const decoyShare2 = wasm_create_decoy(share1, decoy);
+/// ```
+#[wasm_bindgen]
+pub fn wasm_create_decoy(share1: &str, decoy_secret: Vec<u8>) -> Result<String, JsValue> {
+    let decoy_secret = Wiped(decoy_secret);
+    let bytes = Wiped(decode_nonempty(share1)?);
+    check_share_lens(&[&bytes])?;
+    let (_, inner) = strip_label(&bytes)
+        .map_err(|e| share_coded_error(error_code(&e), Some(1), format!("Invalid label: {}", e)))?;
+    let failed = |e: ShareError| match e {
+        ShareError::DecoyLengthMismatch { required } => XplitError {
+            code: error_code(&e),
+            share: None,
+            message: format!("Decoy failed: {}", e),
+            required_len: Some(required),
+        }
+        .into_js(),
+        e => share_coded_error(error_code(&e), Some(1), format!("Decoy failed: {}", e)),
+    };
+    if is_chunked(inner)
+        || is_padded(inner)
+        || is_parity(inner)
+        || unlocked_payload_len(inner).is_some()
+    {
+        return Err(failed(ShareError::UnsupportedFormat));
+    }
 
-use crate::{recover_secret, split_secret};
+    let decoy = Wiped(create_decoy(inner, &decoy_secret).map_err(failed)?);
+    Ok(BASE64.encode(&*decoy))
+}
 
-/// Initialize panic hook for better error messages in the browser console
-#[wasm_bindgen(start)]
-pub fn init() {
-    console_error_panic_hook::set_once();
+/// Result of checking a single share (for JS serialization)
+///
+/// Never holds any of the share's payload.
+#[derive(Serialize, Deserialize)]
+pub struct ShareReport {
+    /// Whether the share decoded and passed every checksum
+    pub valid: bool,
+    /// Length of the payload in bytes, once the header and trailers are removed
+    pub payload_len: Option<usize>,
+    /// The first 8 bytes of SHA-256 over the decoded share, as colon-separated
+    /// hex pairs; matches the fingerprints shown by the CLI
+    pub fingerprint: Option<String>,
+    /// Position of the share in its split, 1 to 3 for a 2-of-3 share; other
+    /// shares don't record it, so null
+    pub index: Option<usize>,
+    /// The share's label, or null when it has none
+    pub label: Option<String>,
+    /// Why the share is invalid, or null when it is valid
+    pub error_code: Option<String>,
+    /// `"grid"`, `"base64"` or `"base64url"`, as in [`ShareInfo`]
+    pub encoding_detected: String,
 }
 
-/// Use wee_alloc as the global allocator for smaller WASM binary size
-#[cfg(target_arch = "wasm32")]
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+#[wasm_bindgen(typescript_custom_section)]
+const SHARE_REPORT_TS: &str = r#"
+/** What wasm_verify_share found out about a share; never holds its payload */
+export interface ShareReport {
+    valid: boolean;
+    payload_len: number | null;
+    fingerprint: string | null;
+    index: number | null;
+    label: string | null;
+    error_code:
+        | "EMPTY" | "INVALID_BASE64" | "TRUNCATED_BASE64" | "SHARE_TOO_SHORT" | "INVALID_CHECKSUM"
+        | "INVALID_CHUNK" | "INVALID_CHUNK_SIZE" | "INVALID_PADDING" | "INVALID_LABEL"
+        | "UNSUPPORTED_FORMAT" | "INVALID_GRID" | "INVALID_GRID_ROW" | null;
+    encoding_detected: "base64" | "base64url" | "grid";
+}
+"#;
 
-/// Result of a split operation (for JSON serialization)
+/// Check a single share without its counterpart
+///
+/// Whitespace, the base64url alphabet and missing `=` padding are all
+/// accepted, so pasted input can be checked as-is, and so is a grid copied
+/// from `xplit split --grid`. Plain, chunked, padded and 2-of-3 shares are
+/// validated down to their checksums.
+///
+/// # Arguments
+/// * `share` - The share to check (base64 encoded)
+///
+/// # Returns
+/// A `ShareReport` object `{ valid, payload_len, fingerprint, index, label,
+/// error_code, encoding_detected }`.
+/// An invalid share is reported, not thrown.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const report = wasm_verify_share(textarea.value);
+/// if (!report.valid) showError(report.error_code);
+/// ```
+#[wasm_bindgen(unchecked_return_type = "ShareReport")]
+pub fn wasm_verify_share(share: &str) -> Result<JsValue, JsValue> {
+    let report = verify_share(share);
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    report.serialize(&serializer).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+fn verify_share(share: &str) -> ShareReport {
+    let report = |payload_len, fingerprint, error_code: Option<&str>| ShareReport {
+        valid: error_code.is_none(),
+        payload_len,
+        fingerprint,
+        index: None,
+        label: None,
+        error_code: error_code.map(str::to_owned),
+        encoding_detected: detected_encoding(share).to_owned(),
+    };
+
+    let bytes = match decode_text(share) {
+        Ok(bytes) if bytes.is_empty() => return report(None, None, Some("EMPTY")),
+        Ok(bytes) => bytes,
+        Err(e) => return report(None, None, Some(e.code())),
+    };
+    let fingerprint = share_fingerprint(&bytes);
+
+    let report = match share_payload_len(&bytes) {
+        Ok(len) => report(Some(len), Some(fingerprint), None),
+        Err(e) => report(None, Some(fingerprint), Some(error_code(&e))),
+    };
+    let unlabeled = strip_label(&bytes).ok();
+    ShareReport {
+        label: unlabeled.and_then(|(label, _)| label.map(str::to_owned)),
+        index: unlabeled.and_then(|(_, share)| share_number(share)),
+        ..report
+    }
+}
+
+/// The number a 2-of-3 share records, the only kind that records one
+fn share_number(share: &[u8]) -> Option<usize> {
+    parity_header(share)
+        .ok()
+        .map(|header| usize::from(header.number))
+}
+
+/// What a share is (for JS serialization)
+///
+/// Never holds any of the share's payload.
 #[derive(Serialize, Deserialize)]
-pub struct SplitResult {
-    /// The first share (base64 encoded)
-    pub share1: String,
-    /// The second share (base64 encoded)
-    pub share2: String,
+pub struct ShareInfo {
+    /// Format version from the share's header; 0 for headerless plain shares.
+    /// A label is a wrapper and doesn't count: this is the labeled share's.
+    pub version: u8,
+    /// `"plain"`, `"chunked"`, `"padded"` or `"2of3"`
+    pub scheme: String,
+    /// Position of the share in its split, 1 to 3 for a 2-of-3 share; other
+    /// shares don't record it, so null
+    pub index: Option<usize>,
+    /// The share's label, or null when it has none
+    pub label: Option<String>,
+    /// Length of the payload in bytes, or null when the checksums fail. For a
+    /// protected share, the length it will have once unlocked.
+    pub payload_len: Option<usize>,
+    /// Same as [`ShareReport::fingerprint`]
+    pub fingerprint: String,
+    /// Whether every checksum in the share passed; always false for a
+    /// protected share, whose checksum can't be read until it is unlocked
+    pub checksum_ok: bool,
+    /// Whether the share is locked under a passphrase (see
+    /// [`wasm_unprotect_share`]); only detected with the `protect` feature
+    pub protected: bool,
+    /// `"base64"`, `"base64url"` when the URL-safe alphabet was pasted, or
+    /// `"grid"` for a grid from `xplit split --grid`
+    pub encoding_detected: String,
 }
 
-/// Split a secret into two XOR-based shares with CRC32 integrity checks
+#[wasm_bindgen(typescript_custom_section)]
+const SHARE_INFO_TS: &str = r#"
+/** What wasm_inspect found a share to be; never holds its payload */
+export interface ShareInfo {
+    version: number;
+    scheme: "plain" | "chunked" | "padded" | "2of3";
+    index: number | null;
+    label: string | null;
+    payload_len: number | null;
+    fingerprint: string;
+    checksum_ok: boolean;
+    /** Locked under a passphrase: prompt for it and call wasm_unprotect_share */
+    protected: boolean;
+    encoding_detected: "base64" | "base64url" | "grid";
+}
+"#;
+
+/// Describe a pasted share before doing anything with it
+///
+/// Input is accepted as leniently as by [`wasm_verify_share`]. Unlike that
+/// function, a share that can't be decoded at all throws, since there is
+/// nothing to describe; a share whose checksums fail is still described, with
+/// `checksum_ok: false`. A protected share is described as the plain share it
+/// holds, with `protected: true`, so the page knows to ask for a passphrase.
 ///
 /// # Arguments
-/// * `secret` - The secret text to split
+/// * `share` - The share to inspect (base64 encoded)
+///
+/// # Returns
+/// A `ShareInfo` object `{ version, scheme, index, label, payload_len,
+/// fingerprint, checksum_ok, protected, encoding_detected }`, or throws an `XplitError`
+/// (`EMPTY`, `INVALID_BASE64`, `TRUNCATED_BASE64`, `INVALID_GRID`,
+/// `INVALID_GRID_ROW` or `INVALID_LABEL`).
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const info = wasm_inspect(textarea.value);
+/// label.textContent = `${info.scheme} share, ${info.payload_len} bytes`;
+/// ```
+#[wasm_bindgen(unchecked_return_type = "ShareInfo")]
+pub fn wasm_inspect(share: &str) -> Result<JsValue, JsValue> {
+    let info = inspect_share(share)?;
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+    info.serialize(&serializer).map_err(|e| {
+        coded_error(
+            "SERIALIZATION_FAILED",
+            format!("Serialization failed: {}", e),
+        )
+    })
+}
+
+fn inspect_share(share: &str) -> Result<ShareInfo, JsValue> {
+    let bytes = decode_nonempty(share)?;
+    let (label, inner) = strip_label(&bytes)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid label: {}", e)))?;
+
+    let (scheme, magic) = if is_chunked(inner) {
+        ("chunked", Some(CHUNKED_MAGIC))
+    } else if is_padded(inner) {
+        ("padded", Some(PADDED_MAGIC))
+    } else if is_parity(inner) {
+        ("2of3", Some(PARITY_MAGIC))
+    } else {
+        ("plain", None)
+    };
+    let unlocked_len = unlocked_payload_len(inner);
+    let protected = unlocked_len.is_some();
+    let payload_len = unlocked_len.unwrap_or_else(|| share_payload_len(inner).ok());
+    Ok(ShareInfo {
+        // Header magics end in their version digit
+        version: magic.map_or(0, |magic| magic[3] - b'0'),
+        scheme: scheme.to_owned(),
+        index: share_number(inner),
+        label: label.map(str::to_owned),
+        payload_len,
+        fingerprint: share_fingerprint(&bytes),
+        checksum_ok: !protected && payload_len.is_some(),
+        protected,
+        encoding_detected: detected_encoding(share).to_owned(),
+    })
+}
+
+/// Payload length a protected share will have once unlocked, or `None` for a
+/// share that isn't protected
+#[cfg(feature = "protect")]
+fn unlocked_payload_len(share: &[u8]) -> Option<Option<usize>> {
+    // Only plain shares are protected: the payload and its CRC32
+    is_protected(share).then(|| share.len().checked_sub(protected_len(4)))
+}
+
+#[cfg(not(feature = "protect"))]
+fn unlocked_payload_len(_share: &[u8]) -> Option<Option<usize>> {
+    None
+}
+
+/// Fingerprint of a share, for two people to compare over a call
+///
+/// The same fingerprint the CLI prints next to each share (see
+/// [`crate::share_fingerprint`]), taken over the share as stored, so a
+/// labeled share and its bare copy differ. Input is accepted as leniently as
+/// by [`wasm_verify_share`]; the share's checksums are not checked.
+///
+/// # Arguments
+/// * `share` - The share (base64 encoded)
 ///
 /// # Returns
-/// JSON string containing both shares (base64 encoded), or an error message
+/// The fingerprint, e.g. `"3d:6e:73:d0:ce:5c:19:11"`, or throws an
+/// `XplitError` (`EMPTY`, `INVALID_BASE64` or `TRUNCATED_BASE64`)
 ///
 /// # Example (JavaScript)
 /// ```javascript
-/// const result = wasm_split("my secret message");
-/// const data = JSON.parse(result);
-/// console.log(`Share 1: ${data.share1}`);
-/// console.log(`Share 2: ${data.share2}`);
+/// codeLabel.textContent = wasm_fingerprint(textarea.value);
 /// ```
 #[wasm_bindgen]
-pub fn wasm_split(secret: &str) -> Result<String, JsValue> {
-    // Validate input
-    if secret.is_empty() {
-        return Err(JsValue::from_str("Secret cannot be empty"));
+pub fn wasm_fingerprint(share: &str) -> Result<String, JsValue> {
+    decode_nonempty(share).map(|bytes| share_fingerprint(&bytes))
+}
+
+/// Group id of a v1 share, the same in both shares of its split
+///
+/// Two shares with the same id were split together. Only [`crate::v1`] shares
+/// carry one; plain, chunked and padded shares give null.
+///
+/// # Arguments
+/// * `share` - The share (base64 encoded, leniently as by [`wasm_verify_share`])
+///
+/// # Returns
+/// The id as 32 hex digits, or null, or throws an `XplitError`: the decoding
+/// errors of [`wasm_fingerprint`], or the share's own error for a damaged v1
+/// share (`INVALID_CHECKSUM`, `SHARE_TOO_SHORT`, ...)
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const together = wasm_pair_id(a) !== null && wasm_pair_id(a) === wasm_pair_id(b);
+/// ```
+#[wasm_bindgen]
+pub fn wasm_pair_id(share: &str) -> Result<Option<String>, JsValue> {
+    let bytes = decode_nonempty(share)?;
+    let (_, share) = strip_label(&bytes)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid label: {}", e)))?;
+    if !is_v1(share) {
+        return Ok(None);
+    }
+    match parse_v1(share) {
+        Ok(parsed) => Ok(parsed.group_id.map(hex::encode)),
+        // A plain share can start with the v1 magic by chance
+        Err(_) if is_valid_share(share) => Ok(None),
+        Err(e) => Err(coded_error(error_code(&e), format!("Invalid share: {}", e))),
+    }
+}
+
+/// Armor a share as a text block that survives email and chat clients
+///
+/// See [`crate::armor`] for the format. The share is wrapped as given; its
+/// checksums are not checked.
+///
+/// # Arguments
+/// * `share` - The share (base64 encoded, leniently as by [`wasm_verify_share`])
+///
+/// # Returns
+/// The armored block, ending in a newline, or throws an `XplitError` (`EMPTY`,
+/// `INVALID_BASE64` or `TRUNCATED_BASE64`)
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// await navigator.clipboard.writeText(wasm_armor(share1));
+/// ```
+#[wasm_bindgen]
+pub fn wasm_armor(share: &str) -> Result<String, JsValue> {
+    let bytes = Wiped(decode_nonempty(share)?);
+    Ok(armor_share(&bytes))
+}
+
+/// Read back a share armored by [`wasm_armor`] or `xplit` from pasted text
+///
+/// The block is found among any surrounding prose, and `> ` quote markers and
+/// re-wrapped lines are undone. The share inside is then checked like
+/// [`wasm_verify_share`] does, except a protected share, which can't be until
+/// it is unlocked.
+///
+/// # Arguments
+/// * `text` - Text holding an armored share
+///
+/// # Returns
+/// The share (base64 encoded), or throws an `XplitError`: `NO_ARMOR` when the
+/// text has no armored block, `INVALID_ARMOR` when the block is cut off or
+/// its body isn't base64, or the share's own error (`INVALID_CHECKSUM`,
+/// `SHARE_TOO_SHORT`, ...) when the block is intact but the share is corrupt
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const share = wasm_dearmor(await navigator.clipboard.readText());
+/// ```
+#[wasm_bindgen]
+pub fn wasm_dearmor(text: &str) -> Result<String, JsValue> {
+    let share = Wiped(
+        dearmor_share(text)
+            .map_err(|e| coded_error(error_code(&e), format!("Dearmor failed: {}", e)))?,
+    );
+    let (_, inner) = strip_label(&share)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid label: {}", e)))?;
+    if unlocked_payload_len(inner).is_none() {
+        share_payload_len(&share)
+            .map_err(|e| coded_error(error_code(&e), format!("Armored share is corrupt: {}", e)))?;
     }
+    Ok(BASE64.encode(&*share))
+}
 
-    let secret_bytes = secret.as_bytes();
+/// [`decode_text`], refusing an empty share
+fn decode_nonempty(share: &str) -> Result<Vec<u8>, JsValue> {
+    let bytes = decode_text(share)
+        .map_err(|e| coded_error(e.code(), format!("Invalid share text: {}", e)))?;
+    if bytes.is_empty() {
+        return Err(coded_error("EMPTY", "Share is empty"));
+    }
+    Ok(bytes)
+}
 
-    // Perform the split
-    let shares = split_secret(secret_bytes)
-        .map_err(|e| JsValue::from_str(&format!("Split failed: {}", e)))?;
+/// Why pasted share text didn't decode
+enum TextError {
+    Base64(base64::DecodeError),
+    Grid(ShareError),
+}
 
-    // Encode shares as base64
-    let result = SplitResult {
-        share1: BASE64.encode(&shares.share1),
-        share2: BASE64.encode(&shares.share2),
-    };
+impl TextError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Base64(e) => base64_error_code(e),
+            Self::Grid(e) => error_code(e),
+        }
+    }
+}
 
-    // Serialize to JSON
-    serde_json::to_string(&result)
-        .map_err(|e| JsValue::from_str(&format!("Serialization failed: {}", e)))
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Base64(e) => write!(f, "invalid base64: {}", e),
+            Self::Grid(e) => write!(f, "invalid grid: {}", e),
+        }
+    }
 }
 
-/// Recover the original secret from two shares
+/// Decode a pasted share the way the CLI reads a share file: a grid from
+/// `xplit split --grid` when the text starts like one (see
+/// [`crate::grid::is_grid`]), base64 otherwise
+fn decode_text(share: &str) -> Result<Vec<u8>, TextError> {
+    if is_grid(share.as_bytes()) {
+        parse_grid(share).map_err(TextError::Grid)
+    } else {
+        decode_lenient(share).map_err(TextError::Base64)
+    }
+}
+
+/// How a pasted share is written, as [`decode_text`] reads it
+fn detected_encoding(share: &str) -> &'static str {
+    if is_grid(share.as_bytes()) {
+        "grid"
+    } else if share.contains(['-', '_']) {
+        "base64url"
+    } else {
+        "base64"
+    }
+}
+
+/// Decode base64 as it tends to get pasted: wrapped, url-safe or unpadded
+fn decode_lenient(share: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let compact: String = share
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    STANDARD_NO_PAD.decode(compact.trim_end_matches('='))
+}
+
+/// Payload length of a share whose checksums all pass, looking past any label
+fn share_payload_len(share: &[u8]) -> Result<usize, ShareError> {
+    let (_, share) = strip_label(share)?;
+    if is_chunked(share) {
+        let report = check_chunks(share)?;
+        match report.bad.first() {
+            Some(range) => Err(ShareError::InvalidChunk {
+                index: range.start / report.chunk_size,
+                start: range.start,
+                end: range.end,
+            }),
+            None => Ok(report.payload_len),
+        }
+    } else if is_padded(share) {
+        let (_, inner) = open_padded(share)?;
+        unseal(inner).map(|payload| payload.len())
+    } else if is_parity(share) {
+        unseal(share)?;
+        parity_header(share)?;
+        // The header and checksum, then two pieces as long as the secret
+        match share.len().checked_sub(parity_share_len(0)) {
+            Some(pieces) if pieces > 0 && pieces % 2 == 0 => Ok(pieces / 2),
+            _ => Err(ShareError::ShareTooShort),
+        }
+    } else {
+        unseal(share).map(|payload| payload.len())
+    }
+}
+
+/// The stable code for each [`ShareError`]; matched exhaustively so a new
+/// variant can't ship without one
+fn error_code(error: &ShareError) -> &'static str {
+    match error {
+        ShareError::InvalidChecksum => "INVALID_CHECKSUM",
+        ShareError::ShareTooShort => "SHARE_TOO_SHORT",
+        ShareError::EmptyInput => "EMPTY",
+        ShareError::TooFewShares => "TOO_FEW_SHARES",
+        ShareError::WrongPassphrase => "WRONG_PASSPHRASE",
+        ShareError::CorruptedProtectedShare => "CORRUPTED_PROTECTED_SHARE",
+        ShareError::CorruptedBundle => "CORRUPTED_BUNDLE",
+        ShareError::InvalidKdfParams => "INVALID_KDF_PARAMS",
+        ShareError::InvalidSignature => "INVALID_SIGNATURE",
+        ShareError::UnexpectedSigner => "UNEXPECTED_SIGNER",
+        ShareError::Cancelled => "CANCELLED",
+        ShareError::LengthMismatch => "LENGTH_MISMATCH",
+        ShareError::InvalidChunkSize => "INVALID_CHUNK_SIZE",
+        ShareError::InvalidPadBlock => "INVALID_PAD_BLOCK",
+        ShareError::InvalidPadding => "INVALID_PADDING",
+        ShareError::InvalidLabel => "INVALID_LABEL",
+        ShareError::UnsupportedFormat => "UNSUPPORTED_FORMAT",
+        ShareError::EmptyPayload => "EMPTY_PAYLOAD",
+        ShareError::DifferentSplits => "DIFFERENT_SPLITS",
+        ShareError::NoArmor => "NO_ARMOR",
+        ShareError::InvalidArmor => "INVALID_ARMOR",
+        ShareError::DecoyLengthMismatch { .. } => "DECOY_LENGTH_MISMATCH",
+        ShareError::InvalidChunk { .. } => "INVALID_CHUNK",
+        ShareError::InvalidGrid { .. } => "INVALID_GRID",
+        ShareError::InvalidGridRow { .. } => "INVALID_GRID_ROW",
+    }
+}
+
+/// Tell a share that was cut off from one with characters outside base64
+///
+/// A cut leaves a length no base64 can have, padding missing or leftover bits
+/// in the last character; a stray character anywhere is a different mistake.
+fn base64_error_code(error: &base64::DecodeError) -> &'static str {
+    match error {
+        base64::DecodeError::InvalidByte(..) => "INVALID_BASE64",
+        base64::DecodeError::InvalidLength(_)
+        | base64::DecodeError::InvalidLastSymbol(..)
+        | base64::DecodeError::InvalidPadding => "TRUNCATED_BASE64",
+    }
+}
+
+/// Render a share as a QR code, for printing or scanning into another device
+///
+/// The QR code holds the share's base64 text, exactly what `xplit recover`
+/// accepts, re-encoded canonically from whatever was pasted. The SVG is
+/// self-contained, so it can be used as an `<img>` data URL.
 ///
 /// # Arguments
-/// * `share1` - First share (base64 encoded)
-/// * `share2` - Second share (base64 encoded)
+/// * `share` - The share (base64 encoded); it must pass its checksums
+/// * `ecc_level` - Error correction level: `"L"`, `"M"`, `"Q"` or `"H"`
 ///
 /// # Returns
-/// The recovered secret as a string, or an error message
+/// The SVG document as a string, or throws an `XplitError`. A share too large
+/// for a single QR code at the chosen level throws `SHARE_TOO_LARGE`, giving
+/// that level's capacity.
 ///
 /// # Example (JavaScript)
 /// ```javascript
-/// const share1 = "ZiTjk3OD6puSVM/JV3CYopI=";
-/// const share2 = "LkGP/xyvysz9JqOtdpOmJ8A=";
-/// const secret = wasm_recover(share1, share2);
-/// console.log(`Recovered secret: ${secret}`);
+/// const svg = wasm_share_to_qr_svg(share1, "M");
+/// img.src = `data:image/svg+xml;utf8,${encodeURIComponent(svg)}`;
 /// ```
 #[wasm_bindgen]
-pub fn wasm_recover(share1: &str, share2: &str) -> Result<String, JsValue> {
-    // Decode from base64
-    let share1_bytes = BASE64
-        .decode(share1)
-        .map_err(|e| JsValue::from_str(&format!("Failed to decode share1: {}", e)))?;
-
-    let share2_bytes = BASE64
-        .decode(share2)
-        .map_err(|e| JsValue::from_str(&format!("Failed to decode share2: {}", e)))?;
+pub fn wasm_share_to_qr_svg(share: &str, ecc_level: &str) -> Result<String, JsValue> {
+    // Capacity of the largest QR code (version 40) in byte mode
+    let (level, capacity) = match ecc_level.to_ascii_uppercase().as_str() {
+        "L" => (EcLevel::L, 2953),
+        "M" => (EcLevel::M, 2331),
+        "Q" => (EcLevel::Q, 1663),
+        "H" => (EcLevel::H, 1273),
+        _ => {
+            return Err(coded_error(
+                "INVALID_ECC_LEVEL",
+                format!(
+                    "Unknown error correction level {:?}; use L, M, Q or H",
+                    ecc_level
+                ),
+            ));
+        }
+    };
 
-    // Perform the recovery
-    let recovered = recover_secret(&share1_bytes, &share2_bytes)
-        .map_err(|e| JsValue::from_str(&format!("Recovery failed: {}", e)))?;
+    let bytes = decode_text(share)
+        .map_err(|e| coded_error(e.code(), format!("Failed to decode share: {}", e)))?;
+    share_payload_len(&bytes)
+        .map_err(|e| coded_error(error_code(&e), format!("Invalid share: {}", e)))?;
+    let text = BASE64.encode(&bytes);
 
-    // Convert to UTF-8 string
-    String::from_utf8(recovered)
-        .map_err(|e| JsValue::from_str(&format!("Recovered data is not valid UTF-8: {}", e)))
+    let code = QrCode::with_error_correction_level(&text, level).map_err(|e| match e {
+        QrError::DataTooLong => coded_error(
+            "SHARE_TOO_LARGE",
+            format!(
+                "Share is {} characters, over the {} a QR code holds at level {}",
+                text.len(),
+                capacity,
+                ecc_level.to_ascii_uppercase()
+            ),
+        ),
+        e => coded_error("QR_FAILED", format!("QR encoding failed: {}", e)),
+    })?;
+    Ok(code.render::<svg::Color>().min_dimensions(256, 256).build())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::v1::split_secret_v1;
 
     #[test]
     fn test_wasm_split_basic() {
         let secret = "Hello, World!";
-        let result = wasm_split(secret);
+        let result = wasm_split(secret.to_owned());
         assert!(result.is_ok());
 
-        let json = result.unwrap();
-        let data: SplitResult = serde_json::from_str(&json).unwrap();
+        let data: SplitResult = serde_wasm_bindgen::from_value(result.unwrap()).unwrap();
 
         // Shares should be base64 encoded
         assert!(!data.share1.is_empty());
@@ -127,10 +2185,20 @@ mod tests {
 
     #[test]
     fn test_wasm_split_empty() {
-        let result = wasm_split("");
+        let result = wasm_split(String::new());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_wasm_split_json_compat() {
+        let json = wasm_split_json("Hello, World!".to_owned()).unwrap();
+        let data: SplitResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            wasm_recover(&data.share1, &data.share2).unwrap(),
+            "Hello, World!"
+        );
+    }
+
     #[test]
     fn test_wasm_recover_readme_example() {
         let share1 = "ZiTjk3OD6puSVM/JV3CYopI=";
@@ -146,8 +2214,8 @@ mod tests {
         let secret = "Test secret message";
 
         // Split
-        let split_result = wasm_split(secret).unwrap();
-        let data: SplitResult = serde_json::from_str(&split_result).unwrap();
+        let split_result = wasm_split(secret.to_owned()).unwrap();
+        let data: SplitResult = serde_wasm_bindgen::from_value(split_result).unwrap();
 
         // Recover
         let recovered = wasm_recover(&data.share1, &data.share2);
@@ -161,10 +2229,596 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_base64_errors_tell_truncation_from_bad_characters() {
+        let code = |share: &str| BASE64.decode(share).map_err(|e| base64_error_code(&e));
+        // SHARE1 cut off at various points
+        assert_eq!(code("ZiTjk3OD6puSVM/JV3CYop"), Err("TRUNCATED_BASE64"));
+        assert_eq!(code("ZiTjk3OD6puSVM/JV3CYo"), Err("TRUNCATED_BASE64"));
+        assert_eq!(code("ZiTjk3OD6puSVM/JV3CYopI"), Err("TRUNCATED_BASE64"));
+        assert_eq!(code("ZiTjk3OD6p!SVM/JV3CYopI="), Err("INVALID_BASE64"));
+
+        let lenient = |share: &str| decode_lenient(share).map_err(|e| base64_error_code(&e));
+        assert_eq!(lenient("ZiTjk3OD6puSVM/JV3CYo"), Err("TRUNCATED_BASE64"));
+        assert_eq!(lenient("ZiTjk3OD6puSVM/JV3CY*"), Err("INVALID_BASE64"));
+    }
+
     #[test]
     fn test_wasm_recover_corrupted_share() {
         // Valid base64 but corrupted share (wrong checksum)
         let result = wasm_recover("AAAAAAAAAAAAAA==", "BBBBBBBBBBBBBB==");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_wasm_recover_bytes_keeps_binary() {
+        let share1 = BASE64.decode("ZiTjk3OD6puSVM/JV3CYopI=").unwrap();
+        let share2 = BASE64.decode("LkGP/xyvysz9JqOtdpOmJ8A=").unwrap();
+        assert_eq!(
+            wasm_recover_bytes(share1, share2).unwrap(),
+            b"Hello, World!"
+        );
+
+        let secret = [0xFF, 0x00, 0xFE, 0xFF];
+        let shares = crate::split_secret(secret).unwrap();
+        assert_eq!(
+            wasm_recover_bytes(shares.share1.clone(), shares.share2.clone()).unwrap(),
+            secret
+        );
+        let (share1, share2) = (BASE64.encode(&shares.share1), BASE64.encode(&shares.share2));
+        assert!(wasm_recover(&share1, &share2).is_err());
+    }
+
+    #[test]
+    fn test_verify_share_accepts_pasted_input() {
+        let report = verify_share("  ZiTjk3OD6puSVM/J\nV3CYopI  ");
+        assert!(report.valid);
+        assert_eq!(report.payload_len, Some(13));
+        assert_eq!(report.fingerprint.unwrap().len(), 23);
+        assert!(report.index.is_none() && report.error_code.is_none());
+
+        let url_safe = verify_share("LkGP_xyvysz9JqOtdpOmJ8A");
+        assert!(url_safe.valid);
+    }
+
+    #[test]
+    fn test_verify_share_reports_errors() {
+        let corrupted = verify_share("LkGP/xyvysz9JqOtdpOmJ8E=");
+        assert!(!corrupted.valid);
+        assert_eq!(corrupted.error_code.as_deref(), Some("INVALID_CHECKSUM"));
+        assert!(corrupted.payload_len.is_none());
+        assert!(corrupted.fingerprint.is_some());
+
+        let code = |share| verify_share(share).error_code;
+        assert_eq!(code("").as_deref(), Some("EMPTY"));
+        assert_eq!(code("not base64!").as_deref(), Some("INVALID_BASE64"));
+        assert_eq!(code("AAA=").as_deref(), Some("SHARE_TOO_SHORT"));
+    }
+
+    #[test]
+    fn test_inspect_describes_without_payload() {
+        let plain = inspect_share("ZiTjk3OD6puSVM/JV3CYopI=").unwrap();
+        assert_eq!((plain.version, plain.scheme.as_str()), (0, "plain"));
+        assert_eq!(plain.payload_len, Some(13));
+        assert!(plain.checksum_ok && plain.index.is_none());
+        assert_eq!(
+            plain.fingerprint,
+            verify_share("ZiTjk3OD6puSVM/JV3CYopI=")
+                .fingerprint
+                .unwrap()
+        );
+        assert_eq!(plain.encoding_detected, "base64");
+
+        let url_safe = inspect_share("LkGP_xyvysz9JqOtdpOmJ8A").unwrap();
+        assert_eq!(url_safe.encoding_detected, "base64url");
+
+        let corrupted = inspect_share("LkGP/xyvysz9JqOtdpOmJ8E=").unwrap();
+        assert!(!corrupted.checksum_ok && corrupted.payload_len.is_none());
+
+        let chunked = split_chunked(b"Hello, World!", 64).unwrap();
+        let chunked = inspect_share(&BASE64.encode(&chunked.share1)).unwrap();
+        assert_eq!((chunked.version, chunked.scheme.as_str()), (1, "chunked"));
+        assert_eq!(chunked.payload_len, Some(13));
+
+        let padded = split_padded(b"Hello, World!", Padding::Block(16)).unwrap();
+        let padded = inspect_share(&BASE64.encode(&padded.share2)).unwrap();
+        assert_eq!((padded.version, padded.scheme.as_str()), (1, "padded"));
+        assert_eq!(padded.payload_len, Some(16));
+
+        assert_eq!(thrown(inspect_share("").err().unwrap()).0, "EMPTY");
+        assert_eq!(
+            thrown(inspect_share("not base64!").err().unwrap()).0,
+            "INVALID_BASE64"
+        );
+    }
+
+    #[test]
+    fn test_version_info_describes_the_build() {
+        let info = version_info();
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.format_version, 1);
+        assert_eq!(
+            info.features.contains(&"protect"),
+            cfg!(feature = "protect")
+        );
+        assert!(wasm_version().is_ok());
+    }
+
+    #[test]
+    fn test_fingerprints_match_the_cli() {
+        // Printed by `xplit verify --pair` for the fixture shares
+        assert_eq!(
+            wasm_fingerprint("ZiTjk3OD6puSVM/JV3CYopI=").unwrap(),
+            "3d:6e:73:d0:ce:5c:19:11"
+        );
+        assert_eq!(
+            wasm_fingerprint(" LkGP_xyvysz9\nJqOtdpOmJ8A ").unwrap(),
+            "13:93:e2:41:28:6b:65:78"
+        );
+        assert_eq!(thrown(wasm_fingerprint("").unwrap_err()).0, "EMPTY");
+        assert_eq!(
+            thrown(wasm_fingerprint("not base64!").unwrap_err()).0,
+            "INVALID_BASE64"
+        );
+    }
+
+    #[test]
+    fn test_pair_id_is_shared_by_v1_pairs() {
+        let split = split_secret_v1(b"Hello, World!", None).unwrap();
+        let id1 = wasm_pair_id(&BASE64.encode(&split.share1))
+            .unwrap()
+            .unwrap();
+        let id2 = wasm_pair_id(&BASE64.encode(&split.share2)).unwrap();
+        assert_eq!(id1.len(), 32);
+        assert_eq!(Some(id1), id2);
+
+        let labeled = label_share(&split.share1, "vault").unwrap();
+        assert_eq!(wasm_pair_id(&BASE64.encode(labeled)).unwrap(), id2);
+        assert_eq!(wasm_pair_id("ZiTjk3OD6puSVM/JV3CYopI=").unwrap(), None);
+
+        let mut damaged = split.share1.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            thrown(wasm_pair_id(&BASE64.encode(damaged)).unwrap_err()).0,
+            "INVALID_CHECKSUM"
+        );
+    }
+
+    #[test]
+    fn test_wasm_split_n_and_recover_n() {
+        let secret = b"Four custodians";
+
+        let shares = wasm_split_n(secret.to_vec(), 4).unwrap();
+        assert_eq!(shares.len(), 4);
+
+        let to_js = |shares: &[String]| shares.iter().map(JsValue::from).collect::<Vec<_>>();
+        assert_eq!(wasm_recover_n(to_js(&shares)).unwrap(), secret);
+        assert_ne!(
+            wasm_recover_n(to_js(&shares[..3])).ok().as_deref(),
+            Some(&secret[..])
+        );
+    }
+
+    #[test]
+    fn test_wasm_recover_n_reads_native_shares() {
+        // Made outside this crate: the secret XOR two random pads, each sealed
+        // with a big-endian CRC32, as the native library does
+        let shares = [
+            "Xd6GSpu4QWI2OyKgmTzPmmVKcd0=",
+            "9gPySld6cuv0xvYU6+sK88QIQ9Y=",
+            "/7UGZaniUPyxibvQG7arGuDTtiI=",
+        ];
+        let shares = shares.map(JsValue::from).to_vec();
+        assert_eq!(wasm_recover_n(shares).unwrap(), b"Three custodians");
+    }
+
+    #[test]
+    fn test_wasm_n_errors_carry_codes() {
+        let code = |error: JsValue| {
+            js_sys::Reflect::get(&error, &JsValue::from_str("code"))
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        assert_eq!(
+            code(wasm_split_n(Vec::new(), 3).unwrap_err()),
+            "EMPTY_SECRET"
+        );
+        assert_eq!(
+            code(wasm_split_n(b"x".to_vec(), 1).unwrap_err()),
+            "INVALID_SHARE_COUNT"
+        );
+        assert_eq!(
+            code(wasm_split_n(b"x".to_vec(), 256).unwrap_err()),
+            "INVALID_SHARE_COUNT"
+        );
+        assert_eq!(
+            code(wasm_recover_n(vec![]).unwrap_err()),
+            "INVALID_SHARE_COUNT"
+        );
+        assert_eq!(
+            code(wasm_recover_n(vec![JsValue::from(1), JsValue::from(2)]).unwrap_err()),
+            "NOT_A_STRING"
+        );
+        let shares = vec![
+            JsValue::from("ZiTjk3OD6puSVM/JV3CYopI="),
+            JsValue::from("AAAAAAAA"),
+        ];
+        assert_eq!(code(wasm_recover_n(shares).unwrap_err()), "LENGTH_MISMATCH");
+    }
+
+    /// The `code` and `share` of a thrown `XplitError`
+    fn thrown(error: JsValue) -> (String, Option<f64>) {
+        let field = |name| js_sys::Reflect::get(&error, &JsValue::from_str(name)).unwrap();
+        (field("code").as_string().unwrap(), field("share").as_f64())
+    }
+
+    #[test]
+    fn test_errors_carry_exact_codes_and_share() {
+        let (share1, share2) = ("ZiTjk3OD6puSVM/JV3CYopI=", "LkGP/xyvysz9JqOtdpOmJ8A=");
+        let code = |code: &str, share| (code.to_owned(), share);
+
+        assert_eq!(
+            thrown(wasm_recover("ZiTjk3OD6puSVM/JV3CYo", share2).unwrap_err()),
+            code("TRUNCATED_BASE64", Some(1.0))
+        );
+        assert_eq!(
+            thrown(wasm_recover(share1, "LkGP/xyvysz9JqOtdpOm*8A=").unwrap_err()),
+            code("INVALID_BASE64", Some(2.0))
+        );
+        assert_eq!(
+            thrown(wasm_recover(share1, "LkGP/xyvysz9JqOtdpOmJ8E=").unwrap_err()),
+            code("INVALID_CHECKSUM", Some(2.0))
+        );
+        assert_eq!(
+            thrown(wasm_recover("AAA=", share2).unwrap_err()),
+            code("SHARE_TOO_SHORT", Some(1.0))
+        );
+        assert_eq!(
+            thrown(wasm_recover_bytes(Vec::new(), Vec::new()).unwrap_err()),
+            code("EMPTY", Some(1.0))
+        );
+
+        let binary = crate::split_secret([0xFF, 0xFE]).unwrap();
+        let (binary1, binary2) = (BASE64.encode(&binary.share1), BASE64.encode(&binary.share2));
+        assert_eq!(
+            thrown(wasm_recover(&binary1, &binary2).unwrap_err()),
+            code("NOT_UTF8", None)
+        );
+
+        assert_eq!(
+            thrown(wasm_split(String::new()).unwrap_err()),
+            code("EMPTY_SECRET", None)
+        );
+        let mut splitter = WasmSplitter::new().unwrap();
+        splitter.push(b"secret").unwrap();
+        splitter.finish().unwrap();
+        assert_eq!(
+            thrown(splitter.finish().unwrap_err()),
+            code("ALREADY_FINISHED", None)
+        );
+        assert_eq!(
+            thrown(wasm_share_to_qr_svg(share1, "X").unwrap_err()),
+            code("INVALID_ECC_LEVEL", None)
+        );
+        let shares = vec![JsValue::from(share1), JsValue::from(2)];
+        assert_eq!(
+            thrown(wasm_recover_n(shares).unwrap_err()),
+            code("NOT_A_STRING", Some(2.0))
+        );
+        let shares = vec![JsValue::from(share1), JsValue::from("AAAAAAAA")];
+        assert_eq!(
+            thrown(wasm_recover_n(shares).unwrap_err()),
+            code("LENGTH_MISMATCH", Some(2.0))
+        );
+    }
+
+    fn split_with(options: serde_json::Value) -> Result<(Vec<u8>, Vec<u8>), JsValue> {
+        let options = options
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap();
+        let result: SplitResult = serde_wasm_bindgen::from_value(wasm_split_with_options(
+            "Hello, World!".to_owned(),
+            options,
+        )?)
+        .unwrap();
+        let decode = |share: &str| decode_lenient(share).unwrap();
+        Ok((decode(&result.share1), decode(&result.share2)))
+    }
+
+    #[test]
+    fn test_split_options_shape_the_shares() {
+        let (plain, _) = split_with(serde_json::json!({})).unwrap();
+        assert_eq!(plain.len(), 13 + 4);
+
+        let (share1, share2) = split_with(serde_json::json!({ "padTo": 32 })).unwrap();
+        assert_eq!(
+            share1.len(),
+            crate::pad::padded_share_len(13, Padding::Block(32))
+        );
+        assert_eq!(
+            crate::pad::recover_padded(&share1, &share2).unwrap(),
+            b"Hello, World!"
+        );
+        let (share1, _) = split_with(serde_json::json!({ "padPow2": true })).unwrap();
+        assert_eq!(
+            share1.len(),
+            crate::pad::padded_share_len(13, Padding::PowerOfTwo)
+        );
+
+        let (share1, share2) = split_with(serde_json::json!({ "chunkSize": 64 })).unwrap();
+        assert!(is_chunked(&share1));
+        assert_eq!(
+            crate::chunked::recover_chunked(&share1, &share2).unwrap(),
+            b"Hello, World!"
+        );
+
+        let (share1, share2) =
+            split_with(serde_json::json!({ "checksumPlacement": "prefix" })).unwrap();
+        assert!(crate::recover_secret(&share1, &share2).is_err());
+        assert_eq!(
+            crate::recover_secret_with_placement(&share1, &share2, ChecksumPlacement::Prefix)
+                .unwrap(),
+            b"Hello, World!"
+        );
+    }
+
+    #[test]
+    fn test_split_options_reject_unknown_and_conflicting_keys() {
+        let code = |options| thrown(split_with(options).unwrap_err()).0;
+        assert_eq!(
+            code(serde_json::json!({ "lable": "vault" })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "padTo": 32, "padPow2": true })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "padTo": 32, "chunkSize": 64 })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "chunkSize": 64, "checksumPlacement": "prefix" })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "checksumPlacement": "middle" })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "encoding": "hex" })),
+            "INVALID_OPTIONS"
+        );
+        assert_eq!(code(serde_json::json!({ "padTo": 0 })), "INVALID_PAD_BLOCK");
+        assert_eq!(
+            code(serde_json::json!({ "chunkSize": 8 })),
+            "INVALID_CHUNK_SIZE"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "label": "two\nlines" })),
+            "INVALID_LABEL"
+        );
+        assert_eq!(
+            code(serde_json::json!({ "label": "x".repeat(65) })),
+            "INVALID_LABEL"
+        );
+        assert!(wasm_split_with_options("Hello, World!".to_owned(), JsValue::UNDEFINED).is_ok());
+    }
+
+    #[test]
+    fn test_labels_round_trip() {
+        let label = "prod DB password";
+        let (share1, share2) =
+            split_with(serde_json::json!({ "label": label, "padTo": 16 })).unwrap();
+        let (share1, share2) = (BASE64.encode(&share1), BASE64.encode(&share2));
+
+        let info = inspect_share(&share2).unwrap();
+        assert_eq!(info.label.as_deref(), Some(label));
+        assert_eq!(
+            (info.scheme.as_str(), info.payload_len),
+            ("padded", Some(16))
+        );
+        let report = verify_share(&share1);
+        assert!(report.valid);
+        assert_eq!(report.label.as_deref(), Some(label));
+        assert!(
+            inspect_share("ZiTjk3OD6puSVM/JV3CYopI=")
+                .unwrap()
+                .label
+                .is_none()
+        );
+
+        let plain = split_with(serde_json::json!({ "label": label })).unwrap();
+        let fresh: SplitResult = serde_wasm_bindgen::from_value(
+            wasm_refresh(&BASE64.encode(&plain.0), &BASE64.encode(&plain.1)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            inspect_share(&fresh.share1).unwrap().label.as_deref(),
+            Some(label)
+        );
+        assert_eq!(
+            wasm_recover_bytes(plain.0.clone(), plain.1.clone()).unwrap(),
+            b"Hello, World!"
+        );
+        assert_eq!(
+            wasm_recover(&BASE64.encode(&plain.0), &BASE64.encode(&plain.1)).unwrap(),
+            "Hello, World!"
+        );
+
+        let mut broken = plain.0.clone();
+        broken[4] = 200;
+        assert_eq!(
+            thrown(wasm_recover_bytes(broken, plain.1).unwrap_err()),
+            ("INVALID_LABEL".to_owned(), Some(1.0))
+        );
+    }
+
+    #[test]
+    fn test_refresh_keeps_the_secret_but_not_the_pairing() {
+        let (share1, share2) = ("ZiTjk3OD6puSVM/JV3CYopI=", "LkGP/xyvysz9JqOtdpOmJ8A=");
+        let fresh: SplitResult =
+            serde_wasm_bindgen::from_value(wasm_refresh(share1, share2).unwrap()).unwrap();
+        assert_ne!(fresh.share1, share1);
+        assert_eq!(
+            wasm_recover(&fresh.share1, &fresh.share2).unwrap(),
+            "Hello, World!"
+        );
+        // An old share paired with a new one recovers noise
+        let mixed = wasm_recover_bytes(
+            BASE64.decode(share1).unwrap(),
+            BASE64.decode(&fresh.share2).unwrap(),
+        )
+        .unwrap();
+        assert_ne!(mixed, b"Hello, World!");
+
+        assert_eq!(
+            thrown(wasm_refresh(share1, "LkGP/xyvysz9JqOtdpOmJ8E=").unwrap_err()),
+            ("INVALID_CHECKSUM".to_owned(), Some(2.0))
+        );
+    }
+
+    #[test]
+    fn test_share_errors_map_to_their_own_codes() {
+        let errors = [
+            (ShareError::InvalidChecksum, "INVALID_CHECKSUM"),
+            (ShareError::TooFewShares, "TOO_FEW_SHARES"),
+            (ShareError::WrongPassphrase, "WRONG_PASSPHRASE"),
+            (ShareError::CorruptedBundle, "CORRUPTED_BUNDLE"),
+            (ShareError::InvalidKdfParams, "INVALID_KDF_PARAMS"),
+            (ShareError::InvalidSignature, "INVALID_SIGNATURE"),
+            (ShareError::InvalidPadBlock, "INVALID_PAD_BLOCK"),
+            (ShareError::InvalidLabel, "INVALID_LABEL"),
+            (ShareError::UnsupportedFormat, "UNSUPPORTED_FORMAT"),
+            (ShareError::EmptyPayload, "EMPTY_PAYLOAD"),
+            (ShareError::DifferentSplits, "DIFFERENT_SPLITS"),
+            (ShareError::NoArmor, "NO_ARMOR"),
+            (
+                ShareError::DecoyLengthMismatch { required: 13 },
+                "DECOY_LENGTH_MISMATCH",
+            ),
+            (ShareError::InvalidGridRow { row: 2 }, "INVALID_GRID_ROW"),
+        ];
+        for (error, code) in errors {
+            assert_eq!(error_code(&error), code);
+        }
+    }
+
+    #[test]
+    fn test_armor_survives_quoting_and_tells_missing_from_corrupt() {
+        let armored = wasm_armor("ZiTjk3OD6puSVM/JV3CYopI=").unwrap();
+        let quoted = format!("> {}", armored.trim_end().replace('\n', "\n> "));
+        let reply = format!("Sure, here's mine:\n\n{quoted}\nCheers");
+        let share1 = wasm_dearmor(&reply).unwrap();
+        assert_eq!(
+            wasm_recover(&share1, "LkGP/xyvysz9JqOtdpOmJ8A=").unwrap(),
+            "Hello, World!"
+        );
+
+        assert_eq!(
+            thrown(wasm_dearmor("no share in here").unwrap_err()).0,
+            "NO_ARMOR"
+        );
+        let cut_off = armored.replace(crate::armor::ARMOR_END, "");
+        assert_eq!(
+            thrown(wasm_dearmor(&cut_off).unwrap_err()).0,
+            "INVALID_ARMOR"
+        );
+        let corrupt = wasm_armor("LkGP/xyvysz9JqOtdpOmJ8E=").unwrap();
+        assert_eq!(
+            thrown(wasm_dearmor(&corrupt).unwrap_err()).0,
+            "INVALID_CHECKSUM"
+        );
+    }
+
+    #[test]
+    fn test_input_limit_holds_at_the_exact_boundary() {
+        /// Puts the default limit back even if an assertion fails
+        struct Restore;
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                wasm_set_max_input_len(DEFAULT_MAX_INPUT_LEN);
+            }
+        }
+        let _restore = Restore;
+        assert_eq!(wasm_max_input_len(), DEFAULT_MAX_INPUT_LEN);
+        let over = encode_shares(
+            crate::split_secret([b'x'; 17]).unwrap(),
+            ShareEncoding::Base64,
+        );
+
+        wasm_set_max_input_len(16);
+        let at: SplitResult =
+            serde_wasm_bindgen::from_value(wasm_split("x".repeat(16)).unwrap()).unwrap();
+        assert_eq!(
+            wasm_recover(&at.share1, &at.share2).unwrap(),
+            "x".repeat(16)
+        );
+        assert!(wasm_split_bytes(vec![0; 16]).is_ok());
+
+        let error = wasm_split("x".repeat(17)).unwrap_err();
+        let message = js_sys::Reflect::get(&error, &"message".into())
+            .unwrap()
+            .as_string()
+            .unwrap();
+        assert_eq!(thrown(error).0, "SECRET_TOO_LARGE");
+        assert!(message.contains("WasmSplitter"), "{}", message);
+        assert_eq!(
+            thrown(wasm_split_bytes(vec![0; 17]).unwrap_err()).0,
+            "SECRET_TOO_LARGE"
+        );
+        assert_eq!(
+            thrown(wasm_split_with_options("x".repeat(17), JsValue::UNDEFINED).unwrap_err()).0,
+            "SECRET_TOO_LARGE"
+        );
+        assert_eq!(
+            thrown(wasm_recover(&over.share1, &over.share2).unwrap_err()).0,
+            "SECRET_TOO_LARGE"
+        );
+        let raw = |share: &str| BASE64.decode(share).unwrap();
+        assert_eq!(
+            thrown(wasm_recover_bytes(raw(&over.share1), raw(&over.share2)).unwrap_err()).0,
+            "SECRET_TOO_LARGE"
+        );
+    }
+
+    #[test]
+    fn test_unaffordable_calls_throw_instead_of_aborting() {
+        assert_eq!(
+            thrown(reserve_working_set(usize::MAX).unwrap_err()).0,
+            "OUT_OF_MEMORY"
+        );
+        assert!(reserve_working_set(1024).is_ok());
+    }
+
+    #[test]
+    fn test_share_to_qr_svg() {
+        let svg = wasm_share_to_qr_svg("ZiTjk3OD6puSVM/JV3CYopI=", "m").unwrap();
+        assert!(svg.contains("<svg") && svg.ends_with("</svg>"));
+        assert!(!svg.contains("href") && !svg.contains("<style"));
+
+        assert!(wasm_share_to_qr_svg("ZiTjk3OD6puSVM/JV3CYopI=", "X").is_err());
+        assert!(wasm_share_to_qr_svg("LkGP/xyvysz9JqOtdpOmJ8E=", "M").is_err());
+    }
+
+    #[test]
+    fn test_oversized_share_reports_capacity() {
+        let shares = crate::split_secret([0x42; 1500]).unwrap();
+        let share = BASE64.encode(&shares.share1);
+        assert!(wasm_share_to_qr_svg(&share, "L").is_ok());
+        let error = wasm_share_to_qr_svg(&share, "H").unwrap_err();
+        let field = |name| {
+            js_sys::Reflect::get(&error, &JsValue::from_str(name))
+                .unwrap()
+                .as_string()
+                .unwrap()
+        };
+        assert_eq!(field("code"), "SHARE_TOO_LARGE");
+        assert!(
+            field("message").contains("over the 1273"),
+            "{}",
+            field("message")
+        );
+    }
 }
@@ -0,0 +1,728 @@
+//! End-to-end tests for the `xplit` binary
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const SHARE1: &str = "ZiTjk3OD6puSVM/JV3CYopI=";
+const SHARE2: &str = "LkGP/xyvysz9JqOtdpOmJ8A=";
+
+/// Run `xplit` with piped stdio, feeding `stdin`, and require success
+fn xplit(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run xplit");
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "xplit {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+/// Split `secret` and return the two base64 shares
+fn split(secret: &[u8]) -> (String, String) {
+    let output = xplit(&["split", "--in", "-"], secret);
+    let text = String::from_utf8(output.stdout).unwrap();
+    let mut shares = text
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().1.to_owned());
+    (shares.next().unwrap(), shares.next().unwrap())
+}
+
+#[test]
+fn test_recover_through_pipe_is_byte_exact() {
+    for flags in [&[][..], &["--no-newline"], &["--raw"]] {
+        let mut args = vec!["recover", SHARE1, SHARE2];
+        args.extend_from_slice(flags);
+        assert_eq!(
+            xplit(&args, b"").stdout,
+            b"Hello, World!",
+            "flags {flags:?}"
+        );
+    }
+}
+
+#[test]
+fn test_recover_crc_le_reads_little_endian_trailers() {
+    let (little1, little2) = ("ZiTjk3OD6puSVM/JV5KimHA=", "LkGP/xyvysz9JqOtdsAnppM=");
+    let output = xplit(&["recover", "--crc-le", little1, little2], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", little1, little2])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_recover_reads_past_share_labels() {
+    let labeled = |share: &str| {
+        let share = BASE64.decode(share).unwrap();
+        BASE64.encode(xplit::label::label_share(&share, "prod DB password").unwrap())
+    };
+    let output = xplit(&["recover", &labeled(SHARE1), &labeled(SHARE2)], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+}
+
+#[test]
+fn test_split_roles_label_each_share() {
+    let output = xplit(&["split", "--roles", "CFO,CTO", "Hello, World!"], b"");
+    let text = String::from_utf8(output.stdout).unwrap();
+    let shares: Vec<&str> = text
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().1)
+        .collect();
+
+    let recovered = xplit(&["recover", shares[0], shares[1]], b"").stdout;
+    assert_eq!(recovered, b"Hello, World!");
+    for (share, role) in shares.iter().zip(["CFO", "CTO"]) {
+        let report = String::from_utf8(xplit(&["verify", share], b"").stdout).unwrap();
+        assert!(report.contains(&format!("label: {role}")), "{report}");
+    }
+}
+
+#[test]
+fn test_recover_accepts_both_shares_in_one_argument() {
+    let output = xplit(&["recover", &format!("{SHARE1}:{SHARE2}")], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", &format!("{SHARE1}:{SHARE2}:{SHARE1}")])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_grid_output_recovers_and_locates_typos() {
+    let output = xplit(&["split", "--grid", "Hello, World!"], b"");
+    let text = String::from_utf8(output.stdout).unwrap();
+    let grids: Vec<&str> = text.split("Share ").skip(1).collect();
+    assert_eq!(grids.len(), 2, "{text}");
+
+    let dir = std::env::temp_dir().join(format!("xplit-grid-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = [dir.join("share1.txt"), dir.join("share2.txt")];
+    for (path, grid) in paths.iter().zip(&grids) {
+        std::fs::write(path, grid.split_once(":\n").unwrap().1).unwrap();
+    }
+    let args = paths.each_ref().map(|path| format!("@{}", path.display()));
+    let output = xplit(&["recover", &args[0], &args[1]], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+
+    let grid = std::fs::read_to_string(&paths[1]).unwrap();
+    let row = grid.lines().nth(1).unwrap();
+    let wrong = if row.as_bytes()[4] == b'A' { "B" } else { "A" };
+    let typo = grid.replacen(row, &format!("{}{wrong}{}", &row[..4], &row[5..]), 1);
+    std::fs::write(&paths[1], typo).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", &args[0], &args[1]])
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Grid row 1 failed"), "{stderr}");
+}
+
+#[test]
+fn test_recover_reads_the_grid_fixture_next_to_base64() {
+    // The same fixture the wasm tests paste, so both read grids alike
+    let grid = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/hello-share1.grid"
+    );
+    let output = xplit(&["recover", &format!("@{grid}"), SHARE2], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+}
+
+#[test]
+fn test_fingerprint_ignores_share_order() {
+    let forward = xplit(&["fingerprint", SHARE1, SHARE2], b"").stdout;
+    let backward = xplit(&["fingerprint", SHARE2, SHARE1], b"").stdout;
+    assert_eq!(forward, backward);
+    assert_eq!(String::from_utf8(forward).unwrap().trim().len(), 23);
+}
+
+#[test]
+fn test_verify_prints_share_fingerprints() {
+    // The same fixtures are pinned in the wasm tests
+    let output = xplit(&["verify", "--pair", SHARE1, SHARE2], b"").stdout;
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.contains("Share 1 fingerprint: 3d:6e:73:d0:ce:5c:19:11"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Share 2 fingerprint: 13:93:e2:41:28:6b:65:78"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_verify_manifest_catches_a_substituted_share() {
+    let dir = std::env::temp_dir().join(format!("xplit-manifest-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let manifest = dir.join("shares.manifest");
+    let manifest = manifest.to_str().unwrap();
+    xplit(&["manifest", SHARE1, SHARE2, "--out", manifest], b"");
+
+    let output = xplit(&["verify-manifest", manifest, SHARE2, SHARE1], b"");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Pair: OK"), "{stdout}");
+
+    let (other, _) = split(b"Hello, World!");
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["verify-manifest", manifest, SHARE1, &other])
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Share 2: MISMATCH"), "{stdout}");
+}
+
+#[test]
+fn test_raw_recover_streams_share_files() {
+    let dir = std::env::temp_dir().join(format!("xplit-stream-recover-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_owned();
+    let secret: Vec<u8> = (0..200_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    std::fs::write(path("secret"), &secret).unwrap();
+    xplit(
+        &["split", "--in", &path("secret"), "--output", &path("s")],
+        b"",
+    );
+    let (share1, share2) = (path("s.share1"), path("s.share2"));
+
+    let output = xplit(
+        &["recover", "--in1", &share1, "--in2", &share2, "--raw"],
+        b"",
+    );
+    assert_eq!(output.stdout, secret);
+
+    // A damaged share: the streamed --out file is removed, and --verify-first
+    // writes nothing at all
+    let text = std::fs::read_to_string(&share2).unwrap();
+    let damaged = text.replacen('A', "B", 1);
+    assert_ne!(damaged, text);
+    std::fs::write(&share2, damaged).unwrap();
+    let recover = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_xplit"))
+            .args(["recover", "--in1", &share1, "--in2", &share2, "--raw"])
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+    let streamed = recover(&["--out", &path("out")]);
+    assert!(!streamed.status.success());
+    assert!(!dir.join("out").exists());
+    let verified = recover(&["--verify-first"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(!verified.status.success());
+    assert!(verified.stdout.is_empty());
+}
+
+#[test]
+fn test_recover_force_ignores_a_damaged_checksum() {
+    let damaged = "LkGP/xyvysz9JqOtdpOmJ8E=";
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", SHARE1, damaged])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = xplit(&["recover", "--force", SHARE1, damaged], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("share2 fails its checksum"), "{stderr}");
+}
+
+#[test]
+fn test_recover_force_strips_v1_headers() {
+    let shares = xplit::v1::split_secret_v1(b"Hello, World!", Some("vault")).unwrap();
+    let mut damaged = shares.share2;
+    *damaged.last_mut().unwrap() ^= 0xFF;
+
+    let output = xplit(
+        &[
+            "recover",
+            "--force",
+            &BASE64.encode(&shares.share1),
+            &BASE64.encode(&damaged),
+        ],
+        b"",
+    );
+    assert_eq!(output.stdout, b"Hello, World!");
+}
+
+#[test]
+fn test_recover_check_never_prints_the_secret() {
+    let output = xplit(&["recover", "--check", SHARE1, SHARE2], b"");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with("Recovery would succeed: 13 bytes"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Hello"));
+
+    let mut corrupted = BASE64.decode(SHARE2).unwrap();
+    corrupted[0] ^= 1;
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", "--check", SHARE1, &BASE64.encode(corrupted)])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_no_crc_shares_roundtrip_and_refuse_mixing() {
+    let output = xplit(&["split", "--no-crc", "Hello, World!"], b"");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let shares: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().1)
+        .collect();
+    assert_eq!(BASE64.decode(shares[0]).unwrap().len(), 13);
+
+    let recovered = xplit(&["recover", "--no-crc", shares[0], shares[1]], b"");
+    assert_eq!(recovered.stdout, b"Hello, World!");
+
+    for args in [
+        &["recover", shares[0], SHARE2][..],
+        &["recover", "--no-crc", shares[0], SHARE2],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("split together"), "{stderr}");
+    }
+}
+
+#[test]
+fn test_recover_raw_writes_binary_verbatim() {
+    let secret = [0x00, 0xff, 0x80, 0x0a];
+    let (share1, share2) = split(&secret);
+
+    let raw = xplit(&["recover", &share1, &share2, "--raw"], b"");
+    assert_eq!(raw.stdout, secret);
+
+    let hex = xplit(&["recover", &share1, &share2], b"");
+    assert_eq!(hex.stdout, b"Binary data (hex): 00ff800a");
+}
+
+#[test]
+fn test_format_templates() {
+    let output = xplit(
+        &[
+            "recover",
+            SHARE1,
+            SHARE2,
+            "--format",
+            r"S={secret}\tH={secret_hex}\nL={len}",
+        ],
+        b"",
+    );
+    assert_eq!(
+        output.stdout,
+        b"S=Hello, World!\tH=48656c6c6f2c20576f726c6421\nL=13"
+    );
+
+    let output = xplit(
+        &[
+            "split",
+            "--in",
+            "-",
+            "--label",
+            "vault",
+            "--format",
+            r"{label}\n{share1}\n{share2}",
+        ],
+        b"Hello, World!",
+    );
+    let text = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = text.split('\n').collect();
+    assert_eq!(lines[0], "vault");
+    assert_eq!(
+        xplit(&["recover", lines[1], lines[2]], b"").stdout,
+        b"Hello, World!"
+    );
+}
+
+#[test]
+fn test_unknown_format_placeholder_fails() {
+    let status = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", SHARE1, SHARE2, "--format", "{plaintext}"])
+        .output()
+        .unwrap()
+        .status;
+    assert!(!status.success());
+}
+
+#[test]
+fn test_at_file_share_arguments() {
+    let dir = std::env::temp_dir().join(format!("xplit-at-file-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("share1.txt");
+    std::fs::write(&path, format!("{SHARE1}\n")).unwrap();
+    let at_path = format!("@{}", path.display());
+
+    let output = xplit(&["recover", &at_path, "@-"], SHARE2.as_bytes());
+    assert_eq!(output.stdout, b"Hello, World!");
+
+    // Without `@` an existing path is still a literal (and not valid base64)
+    let status = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", path.to_str().unwrap(), SHARE2])
+        .output()
+        .unwrap()
+        .status;
+    assert!(!status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_rotate_keeps_the_secret_and_refuses_to_overwrite() {
+    let dir = std::env::temp_dir().join(format!("xplit-rotate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_owned();
+    std::fs::write(path("a"), SHARE1).unwrap();
+    std::fs::write(path("b"), SHARE2).unwrap();
+
+    let args = [
+        "rotate",
+        "--in1",
+        &path("a"),
+        "--in2",
+        &path("b"),
+        "--out1",
+        &path("a2"),
+        "--out2",
+        &path("b2"),
+    ];
+    xplit(&args, b"");
+    let rotated = std::fs::read_to_string(path("a2")).unwrap();
+    assert_ne!(rotated.trim(), SHARE1);
+    let output = xplit(
+        &[
+            "recover",
+            &format!("@{}", path("a2")),
+            &format!("@{}", path("b2")),
+        ],
+        b"",
+    );
+    assert_eq!(output.stdout, b"Hello, World!");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(args)
+        .output()
+        .unwrap()
+        .status;
+    assert!(!status.success());
+    assert_eq!(std::fs::read_to_string(path("a2")).unwrap(), rotated);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_streamed_split_to_files_roundtrips() {
+    let dir = std::env::temp_dir().join(format!("xplit-stream-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let secret: Vec<u8> = (0..=255u8).cycle().take(200_000).collect();
+    let input = dir.join("secret.bin");
+    std::fs::write(&input, &secret).unwrap();
+    let prefix = dir.join("vault");
+
+    xplit(
+        &[
+            "split",
+            "--in",
+            input.to_str().unwrap(),
+            "-o",
+            prefix.to_str().unwrap(),
+        ],
+        b"",
+    );
+    let output = xplit(
+        &[
+            "recover",
+            "--raw",
+            &format!("@{}.share1", prefix.display()),
+            &format!("@{}.share2", prefix.display()),
+        ],
+        b"",
+    );
+    assert_eq!(output.stdout, secret);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_to_files_keeps_roles() {
+    let dir = std::env::temp_dir().join(format!("xplit-stream-roles-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("secret.txt");
+    std::fs::write(&input, b"Hello, World!").unwrap();
+    let prefix = dir.join("vault");
+
+    xplit(
+        &[
+            "split",
+            "--in",
+            input.to_str().unwrap(),
+            "-o",
+            prefix.to_str().unwrap(),
+            "--roles",
+            "CFO,CTO",
+        ],
+        b"",
+    );
+    for (n, role) in [(1, "CFO"), (2, "CTO")] {
+        let share = format!("@{}.share{n}", prefix.display());
+        let report = String::from_utf8(xplit(&["verify", &share], b"").stdout).unwrap();
+        assert!(report.contains(&format!("label: {role}")), "{report}");
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_closed_stdout_is_not_an_error() {
+    // `xplit fingerprint ... | head -0`: the reader is gone before the write
+    let (reader, writer) = std::io::pipe().unwrap();
+    drop(reader);
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["fingerprint", SHARE1, SHARE2])
+        .stdout(writer)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_utf8_strictness_flags() {
+    let (share1, share2) = split(&[b'a', 0xff, b'b']);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["recover", "--strict-utf8", &share1, &share2])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+
+    let output = xplit(&["recover", "--lossy-utf8", &share1, &share2], b"");
+    assert_eq!(output.stdout, "a\u{fffd}b".as_bytes());
+
+    let output = xplit(&["recover", "--strict-utf8", SHARE1, SHARE2], b"");
+    assert_eq!(output.stdout, b"Hello, World!");
+}
+
+#[test]
+fn test_dry_run_predicts_share_sizes() {
+    let dir = std::env::temp_dir().join(format!("xplit-dry-run-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("secret.txt");
+    std::fs::write(&input, "Hello, World!").unwrap();
+    let prefix = dir.join("vault");
+    let args = [
+        "split",
+        "--in",
+        input.to_str().unwrap(),
+        "-o",
+        prefix.to_str().unwrap(),
+    ];
+
+    let mut dry_run = args.to_vec();
+    dry_run.push("--dry-run");
+    let report = String::from_utf8(xplit(&dry_run, b"").stdout).unwrap();
+    assert!(report.contains("Secret: 13 bytes"), "{report}");
+    assert!(
+        report.contains("Share 1: 17 bytes (24 base64 characters)"),
+        "{report}"
+    );
+    let share_file = dir.join("vault.share1");
+    assert!(!share_file.exists());
+
+    xplit(&args, b"");
+    // 24 base64 characters plus the trailing newline
+    assert_eq!(std::fs::metadata(&share_file).unwrap().len(), 25);
+
+    // No pairing id is drawn, so templated names show a placeholder
+    let report = String::from_utf8(
+        xplit(
+            &[
+                "split",
+                "--in",
+                input.to_str().unwrap(),
+                "--name-template",
+                "{id}-{index}",
+                "--dry-run",
+            ],
+            b"",
+        )
+        .stdout,
+    )
+    .unwrap();
+    assert!(report.contains("{id}-1"), "{report}");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_holder_sheets_use_template_file() {
+    let dir = std::env::temp_dir().join(format!("xplit-holder-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let template = dir.join("sheet.txt");
+    std::fs::write(&template, "{holder} holds {index}, ask {other}: {share}\n").unwrap();
+    let prefix = dir.join("vault");
+
+    let output = xplit(
+        &[
+            "split",
+            "-o",
+            prefix.to_str().unwrap(),
+            "--holder",
+            "alice",
+            "--holder",
+            "bob",
+            "--holder-template",
+            template.to_str().unwrap(),
+            "Hello, World!",
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+
+    let share2 = std::fs::read_to_string(dir.join("vault.share2")).unwrap();
+    let sheet = std::fs::read_to_string(dir.join("vault.share2.bob.txt")).unwrap();
+    assert_eq!(sheet, format!("bob holds 2, ask alice: {share2}"));
+    assert!(dir.join("vault.share1.alice.txt").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_to_drives_verifies_both() {
+    let dir = std::env::temp_dir().join(format!("xplit-drives-{}", std::process::id()));
+    let (usb1, usb2) = (dir.join("usb1"), dir.join("usb2"));
+    std::fs::create_dir_all(&usb1).unwrap();
+    std::fs::create_dir_all(&usb2).unwrap();
+    let drives = |second: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_xplit"))
+            .args(["split", "--drive1", usb1.to_str().unwrap(), "--drive2"])
+            .arg(second)
+            .args(["--label", "key", "Hello, World!"])
+            .output()
+            .unwrap()
+    };
+
+    let missing = drives(&dir.join("not-mounted"));
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("Drive 2 not found"));
+    assert!(!usb1.join("key.share1").exists());
+
+    let output = drives(&usb2);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Both drives verified"));
+    let recovered = xplit(
+        &[
+            "recover",
+            &format!("@{}", usb1.join("key.share1").display()),
+            &format!("@{}", usb2.join("key.share2").display()),
+        ],
+        b"",
+    );
+    assert_eq!(recovered.stdout, b"Hello, World!");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_chunked_shares_report_damaged_chunks() {
+    let secret: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let output = xplit(&["split", "--in", "-", "--chunk-size", "256"], &secret);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let shares: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split_once(": ").unwrap().1)
+        .collect();
+
+    let recovered = xplit(&["recover", "--raw", shares[0], shares[1]], b"");
+    assert_eq!(recovered.stdout, secret);
+
+    // Flip a byte in the second chunk (header 8 bytes, chunks of 256 + 4)
+    let mut damaged = BASE64.decode(shares[1]).unwrap();
+    damaged[8 + 260 + 10] ^= 1;
+    let damaged = BASE64.encode(damaged);
+    let output = Command::new(env!("CARGO_BIN_EXE_xplit"))
+        .args(["verify", &damaged])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("chunk 2 (bytes 256..512)"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_padded_shares_hide_secret_length() {
+    let split = |secret: &str| {
+        let output = xplit(&["split", secret, "--pad", "16"], b"");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        stdout
+            .lines()
+            .map(|line| line.split_once(": ").unwrap().1.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let short = split("a");
+    let long = split("Hello, World!");
+    assert_eq!(short[0].len(), long[0].len());
+
+    let recovered = xplit(&["recover", &long[0], &long[1]], b"");
+    assert_eq!(recovered.stdout, b"Hello, World!");
+    let verified = xplit(&["verify", &short[1]], b"");
+    let stdout = String::from_utf8(verified.stdout).unwrap();
+    assert!(stdout.contains("padded: yes (block 16)"), "{stdout}");
+}
+
+#[test]
+fn test_compare_reports_match_without_the_secret() {
+    // sha256("Hello, World!")
+    let sha256 = "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f";
+    let output = xplit(&["compare", SHARE1, SHARE2, "--sha256", sha256], b"");
+    assert_eq!(output.stdout, b"MATCH (13 bytes, sha256)\n");
+
+    let compare = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_xplit"))
+            .arg("compare")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let mismatch = compare(&[SHARE1, SHARE2, "--blake3", &"00".repeat(32)]);
+    assert_eq!(mismatch.status.code(), Some(3));
+    assert_eq!(mismatch.stdout, b"MISMATCH (13 bytes, blake3)\n");
+
+    let failed = compare(&[SHARE1, "AAAAAAAA", "--sha256", sha256]);
+    assert_eq!(failed.status.code(), Some(4));
+    assert!(failed.stdout.is_empty());
+}
@@ -0,0 +1,164 @@
+// Type-level test for the generated wasm bindings: compiles only while the
+// .d.ts describes the objects the functions really return and throw.
+//
+// Run with `just wasm-types`, which builds the package into target/wasm-types.
+
+import init, {
+    wasm_armor,
+    wasm_bundle,
+    wasm_create_decoy,
+    wasm_dearmor,
+    wasm_fingerprint,
+    wasm_inspect,
+    wasm_max_input_len,
+    wasm_pair_id,
+    wasm_protect_share,
+    wasm_recover,
+    wasm_recover_2of3,
+    wasm_recover_bytes,
+    wasm_recover_n,
+    wasm_refresh,
+    wasm_set_max_input_len,
+    wasm_share_to_qr_svg,
+    wasm_split,
+    wasm_split_2of3,
+    wasm_split_async,
+    wasm_split_bytes,
+    wasm_split_n,
+    wasm_split_with_options,
+    wasm_unbundle,
+    wasm_unprotect_share,
+    wasm_verify_share,
+    wasm_version,
+    WasmRecoverer,
+    WasmSplitter,
+} from "../../target/wasm-types/xplit.js";
+import type {
+    KdfOptions,
+    ShareInfo,
+    ShareReport,
+    Split2of3Result,
+    SplitAsyncOptions,
+    SplitBytesResult,
+    SplitOptions,
+    SplitResult,
+    SplitterChunks,
+    SplitterTrailers,
+    VersionInfo,
+    XplitError,
+} from "../../target/wasm-types/xplit.js";
+
+function describe(error: unknown): string {
+    const { code, share, message } = error as XplitError;
+    const which: number | null = share;
+    switch (code) {
+        case "TRUNCATED_BASE64":
+            return `Share ${which} looks cut off`;
+        case "INVALID_CHECKSUM":
+            return which === null ? "A share is damaged" : `Share ${which} is damaged`;
+        case "DECOY_LENGTH_MISMATCH": {
+            const needed: number | undefined = (error as XplitError).required_len;
+            return `The decoy must be ${needed} bytes long`;
+        }
+        default:
+            return message;
+    }
+}
+
+async function main(): Promise<void> {
+    await init();
+
+    const version: VersionInfo = wasm_version();
+    const canProtect: boolean = version.features.includes("protect");
+    // @ts-expect-error only optional features are listed
+    version.features.includes("cli");
+
+    wasm_set_max_input_len(8 * 1024 * 1024);
+    const limit: number = wasm_max_input_len();
+    try {
+        wasm_split("x".repeat(limit + 1));
+    } catch (error) {
+        if ((error as XplitError).code === "SECRET_TOO_LARGE") {
+            // Fall back to WasmSplitter
+        }
+    }
+
+    const split: SplitResult = wasm_split("my secret message");
+    const secret: string = wasm_recover(split.share1, split.share2);
+    // @ts-expect-error shares are base64 strings, not bytes
+    const notBytes: Uint8Array = split.share1;
+
+    const three: Split2of3Result = wasm_split_2of3("my secret message");
+    const fromTwo: string = wasm_recover_2of3(three.share3, three.share1);
+    const armored: string = wasm_armor(split.share1);
+    const dearmored: string = wasm_dearmor(`> ${armored}`);
+    const rotated: SplitResult = wasm_refresh(split.share1, split.share2);
+    const costs: KdfOptions = { memoryKib: 12288, iterations: 3 };
+    const bundle: Uint8Array = wasm_bundle(split.share1, split.share2, "passphrase", costs);
+    wasm_bundle(split.share1, split.share2, "passphrase", undefined);
+    const unbundled: SplitResult = wasm_unbundle(bundle, "passphrase");
+    const locked: string = wasm_protect_share(split.share1, "passphrase", costs);
+    wasm_protect_share(split.share1, "passphrase", undefined);
+    if (wasm_inspect(locked).protected) {
+        const unlocked: string = wasm_unprotect_share(locked, "passphrase");
+    }
+    const options: SplitOptions = { padTo: 32, checksumPlacement: "suffix" };
+    const padded: SplitResult = wasm_split_with_options(secret, options);
+    wasm_split_with_options(secret, undefined);
+    wasm_split_with_options(secret, { label: "vault" });
+    const linked: SplitResult = wasm_split_with_options(secret, { encoding: "base64url" });
+    const encoding: "base64" | "base64url" = linked.encoding;
+    // @ts-expect-error unknown options are rejected
+    wasm_split_with_options(secret, { lable: "vault" });
+
+    const bytes: SplitBytesResult = wasm_split_bytes(new TextEncoder().encode(secret));
+    const controller = new AbortController();
+    const asyncOptions: SplitAsyncOptions = {
+        onProgress: (done: number, total: number) => console.log(done / total),
+        signal: controller.signal,
+    };
+    const splitAsync: SplitBytesResult = await wasm_split_async(bytes.share1, asyncOptions);
+    await wasm_split_async(bytes.share2, undefined);
+    const recovered: Uint8Array = wasm_recover_bytes(bytes.share1, bytes.share2);
+
+    const shares: string[] = wasm_split_n(recovered, 3);
+    const fromN: Uint8Array = wasm_recover_n(shares);
+    // @ts-expect-error shares are passed as base64 strings
+    wasm_recover_n([recovered]);
+
+    const report: ShareReport = wasm_verify_share(shares[0]);
+    const length: number | null = report.payload_len;
+    const code: XplitError["code"] | null = report.error_code;
+    // @ts-expect-error error codes are a closed set
+    const unknownCode: ShareReport["error_code"] = "no_such_code";
+
+    const info: ShareInfo = wasm_inspect(shares[0]);
+    const scheme: "plain" | "chunked" | "padded" | "2of3" = info.scheme;
+    const pasted: "base64" | "base64url" | "grid" = report.encoding_detected;
+    const checksumOk: boolean = info.checksum_ok;
+    const label: string | null = info.label ?? report.label;
+    const fingerprint: string = wasm_fingerprint(shares[0]);
+    const pairId: string | undefined = wasm_pair_id(shares[0]);
+
+    const svg: string = wasm_share_to_qr_svg(split.share1, "M");
+
+    const splitter = new WasmSplitter();
+    const chunks: SplitterChunks = splitter.push(fromN);
+    const trailers: SplitterTrailers = splitter.finish();
+    const recoverer = new WasmRecoverer();
+    const part: Uint8Array = recoverer.push(chunks.share1_chunk, chunks.share2_chunk);
+    recoverer.push(trailers.share1_trailer, trailers.share2_trailer);
+    const total: number = recoverer.finish();
+
+    const decoyShare2: string = wasm_create_decoy(split.share1, new Uint8Array(13));
+
+    try {
+        wasm_recover(split.share1.slice(0, -3), split.share2);
+    } catch (error) {
+        console.log(describe(error));
+    }
+
+    console.log(notBytes, rotated, padded, length, code, unknownCode, svg, part, total);
+}
+
+main();
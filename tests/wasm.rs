@@ -0,0 +1,560 @@
+//! Tests for the WASM API, run in Node.js
+//!
+//! Run with `just wasm-test`.
+
+#![cfg(target_arch = "wasm32")]
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use js_sys::{Object, Reflect, Uint8Array};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::wasm_bindgen_test;
+use xplit::wasm::{
+    ASYNC_CHUNK_SIZE, WasmRecoverer, WasmSplitter, wasm_armor, wasm_create_decoy, wasm_dearmor,
+    wasm_fingerprint, wasm_inspect, wasm_pair_id, wasm_recover, wasm_recover_2of3,
+    wasm_recover_bytes, wasm_recover_n, wasm_refresh, wasm_share_to_qr_svg, wasm_split,
+    wasm_split_2of3, wasm_split_async, wasm_split_bytes, wasm_split_json, wasm_split_n,
+    wasm_split_with_options, wasm_verify_share,
+};
+#[cfg(feature = "protect")]
+use xplit::wasm::{wasm_bundle, wasm_protect_share, wasm_unbundle, wasm_unprotect_share};
+
+fn share(shares: &JsValue, name: &str) -> Vec<u8> {
+    Uint8Array::new(&Reflect::get(shares, &JsValue::from_str(name)).unwrap()).to_vec()
+}
+
+#[wasm_bindgen_test]
+fn random_kilobyte_roundtrips() {
+    let mut secret = vec![0u8; 1024];
+    getrandom::getrandom(&mut secret).unwrap();
+
+    let shares = wasm_split_bytes(secret.clone()).unwrap();
+    let (share1, share2) = (share(&shares, "share1"), share(&shares, "share2"));
+    assert_eq!(share1.len(), 1024 + 4);
+    assert_eq!(wasm_recover_bytes(share1, share2).unwrap(), secret);
+}
+
+#[wasm_bindgen_test]
+fn empty_secret_errors_like_the_string_api() {
+    let code = |error: JsValue| {
+        Reflect::get(&error, &JsValue::from_str("code"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    assert_eq!(
+        code(wasm_split_bytes(Vec::new()).unwrap_err()),
+        "EMPTY_SECRET"
+    );
+    assert_eq!(code(wasm_split(String::new()).unwrap_err()), "EMPTY_SECRET");
+}
+
+#[wasm_bindgen_test]
+fn non_utf8_secret_recovers_as_bytes() {
+    let secret = [0xFF, 0xFE, 0x00, 0xFF];
+    let shares = wasm_split_bytes(secret.to_vec()).unwrap();
+    let (share1, share2) = (share(&shares, "share1"), share(&shares, "share2"));
+    assert_eq!(wasm_recover_bytes(share1, share2).unwrap(), secret);
+}
+
+#[wasm_bindgen_test]
+fn splits_draw_fresh_randomness() {
+    let first = wasm_split_bytes(b"same secret".to_vec()).unwrap();
+    let second = wasm_split_bytes(b"same secret".to_vec()).unwrap();
+    assert_ne!(share(&first, "share2"), share(&second, "share2"));
+}
+
+#[wasm_bindgen_test]
+fn recover_n_reads_cli_shares() {
+    // Written by `xplit split "Hello, World!"`: a 2-share split is also an N-of-N one
+    let shares = ["ZiTjk3OD6puSVM/JV3CYopI=", "LkGP/xyvysz9JqOtdpOmJ8A="];
+    let shares = shares.map(JsValue::from).to_vec();
+    assert_eq!(wasm_recover_n(shares).unwrap(), b"Hello, World!");
+}
+
+#[wasm_bindgen_test]
+fn split_n_roundtrips_binary_secrets() {
+    let secret = [0xFF, 0x00, 0x80, 0x7F];
+    let shares = wasm_split_n(secret.to_vec(), 5).unwrap();
+    assert_eq!(shares.len(), 5);
+    let shares = shares.into_iter().map(JsValue::from).collect();
+    assert_eq!(wasm_recover_n(shares).unwrap(), secret);
+}
+
+#[wasm_bindgen_test]
+fn streamed_split_matches_one_shot_recovery() {
+    const CHUNK: usize = 64 * 1024;
+    let secret: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+    let mut splitter = WasmSplitter::new().unwrap();
+    let (mut share1, mut share2) = (Vec::new(), Vec::new());
+    for chunk in secret.chunks(CHUNK) {
+        let chunks = splitter.push(chunk).unwrap();
+        share1.extend(share(&chunks, "share1_chunk"));
+        share2.extend(share(&chunks, "share2_chunk"));
+    }
+    let trailers = splitter.finish().unwrap();
+    share1.extend(share(&trailers, "share1_trailer"));
+    share2.extend(share(&trailers, "share2_trailer"));
+    assert_eq!(
+        wasm_recover_bytes(share1.clone(), share2.clone()).unwrap(),
+        secret
+    );
+
+    let mut recoverer = WasmRecoverer::new();
+    let mut output = Vec::new();
+    for (chunk1, chunk2) in share1.chunks(CHUNK).zip(share2.chunks(CHUNK)) {
+        output.extend(recoverer.push(chunk1, chunk2).unwrap());
+    }
+    assert_eq!(recoverer.finish().unwrap(), secret.len() as f64);
+    assert_eq!(output, secret);
+}
+
+#[wasm_bindgen_test]
+fn repeated_large_splits_reuse_memory() {
+    // Each round frees what the last one allocated, so after the first the
+    // heap should not need to grow
+    let secret: Vec<u8> = (0..16 * 1024 * 1024).map(|i| (i % 253) as u8).collect();
+    let mut pages = Vec::new();
+    for _ in 0..4 {
+        let shares = wasm_split_bytes(secret.clone()).unwrap();
+        let (share1, share2) = (share(&shares, "share1"), share(&shares, "share2"));
+        assert_eq!(wasm_recover_bytes(share1, share2).unwrap(), secret);
+        pages.push(core::arch::wasm32::memory_size(0));
+    }
+    assert_eq!(pages[3], pages[1], "memory pages per round: {pages:?}");
+}
+
+#[wasm_bindgen_test]
+async fn async_split_reports_progress() {
+    let secret: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 241) as u8).collect();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let on_progress = Closure::<dyn FnMut(f64, f64)>::new({
+        let calls = Rc::clone(&calls);
+        move |done, total| calls.borrow_mut().push((done, total))
+    });
+    let options = Object::new();
+    Reflect::set(&options, &"onProgress".into(), on_progress.as_ref()).unwrap();
+
+    let shares = JsFuture::from(wasm_split_async(secret.clone(), options.into()))
+        .await
+        .unwrap();
+    let (share1, share2) = (share(&shares, "share1"), share(&shares, "share2"));
+    assert_eq!(wasm_recover_bytes(share1, share2).unwrap(), secret);
+
+    let total = secret.len() as f64;
+    let expected: Vec<(f64, f64)> = (1..=secret.len() / ASYNC_CHUNK_SIZE)
+        .map(|chunks| ((chunks * ASYNC_CHUNK_SIZE) as f64, total))
+        .collect();
+    assert_eq!(*calls.borrow(), expected);
+}
+
+#[wasm_bindgen_test]
+async fn async_split_stops_once_aborted() {
+    let code = |error: JsValue| {
+        Reflect::get(&error, &JsValue::from_str("code"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    // Abort from the progress callback, as a cancel button would between chunks
+    let signal = Object::new();
+    let on_progress = Closure::<dyn FnMut(f64, f64)>::new({
+        let signal = signal.clone();
+        move |_, _| {
+            Reflect::set(&signal, &"aborted".into(), &true.into()).unwrap();
+        }
+    });
+    let options = Object::new();
+    Reflect::set(&options, &"onProgress".into(), on_progress.as_ref()).unwrap();
+    Reflect::set(&options, &"signal".into(), &signal).unwrap();
+
+    let secret = vec![7; 3 * ASYNC_CHUNK_SIZE];
+    let error = JsFuture::from(wasm_split_async(secret, options.into()))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), "CANCELLED");
+
+    let error = JsFuture::from(wasm_split_async(Vec::new(), JsValue::UNDEFINED))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), "EMPTY_SECRET");
+    let options = Object::new();
+    Reflect::set(&options, &"onprogress".into(), &JsValue::NULL).unwrap();
+    let error = JsFuture::from(wasm_split_async(vec![1], options.into()))
+        .await
+        .unwrap_err();
+    assert_eq!(code(error), "INVALID_OPTIONS");
+}
+
+#[wasm_bindgen_test]
+fn malformed_input_never_panics() {
+    // A panic would abort the instance and fail every later call, so reaching
+    // the end means each export returned or threw an XplitError
+    let valid = "ZiTjk3OD6puSVM/JV3CYopI=";
+    let mut noise = vec![0u8; 4096];
+    getrandom::getrandom(&mut noise).unwrap();
+    let mut texts: Vec<String> = [
+        "",
+        "=",
+        "====",
+        "!!!!",
+        "\u{0}",
+        "é",
+        "XPL1",
+        "WFBMMQ==",
+        "WFAx",
+        "WFBDMQ==",
+        "WFBEMQ==",
+        "LkGP/xyvysz9JqOtdpOmJ8E=",
+        "LkGP_xyvysz9JqOtdpOmJ8A",
+        "data:,abc",
+    ]
+    .map(str::to_owned)
+    .to_vec();
+    texts.extend((0..valid.len()).map(|end| valid[..end].to_owned()));
+    texts.push(String::from_utf8_lossy(&noise).into_owned());
+    texts.push("A".repeat(8 * 1024 * 1024));
+    texts.push("XPL1".repeat(1024 * 1024));
+
+    let mut blobs: Vec<Vec<u8>> = vec![Vec::new(), noise.clone(), b"XPC1\xff\xff\xff\xff".to_vec()];
+    blobs.extend((0..8).map(|end| noise[..end].to_vec()));
+    blobs.push(vec![0xFF; 16 * 1024 * 1024]);
+
+    for text in &texts {
+        let _ = wasm_split(text.clone());
+        let _ = wasm_split_json(text.clone());
+        let _ = wasm_split_with_options(text.clone(), JsValue::from_str(text));
+        let _ = wasm_recover(text, valid);
+        let _ = wasm_recover(valid, text);
+        let _ = wasm_refresh(text, text);
+        let _ = wasm_verify_share(text);
+        let _ = wasm_inspect(text);
+        let _ = wasm_fingerprint(text);
+        let _ = wasm_pair_id(text);
+        let _ = wasm_share_to_qr_svg(text, text);
+        let _ = wasm_share_to_qr_svg(text, "L");
+        let _ = wasm_recover_n(vec![JsValue::from_str(text), JsValue::from_str(valid)]);
+        let _ = wasm_recover_2of3(text, text);
+        let _ = wasm_armor(text);
+        let _ = wasm_dearmor(text);
+    }
+    for blob in &blobs {
+        let _ = wasm_split_bytes(blob.clone());
+        let _ = wasm_split_n(blob.clone(), 2);
+        let _ = wasm_recover_bytes(blob.clone(), blob.clone());
+
+        let mut splitter = WasmSplitter::new().unwrap();
+        let _ = splitter.push(blob);
+        let _ = splitter.finish();
+        let _ = splitter.push(blob);
+        let mut recoverer = WasmRecoverer::new();
+        let _ = recoverer.push(blob, &blob[blob.len() / 2..]);
+        let _ = recoverer.finish();
+        let _ = recoverer.finish();
+    }
+    #[cfg(feature = "protect")]
+    for blob in &blobs[..blobs.len() - 1] {
+        let _ = wasm_unbundle(blob.clone(), "passphrase".to_owned());
+    }
+    #[cfg(feature = "protect")]
+    for text in &texts {
+        let _ = wasm_unprotect_share(text, "passphrase".to_owned());
+        let _ = wasm_protect_share(text, "passphrase".to_owned(), JsValue::UNDEFINED);
+    }
+    let _ = wasm_recover_n(vec![JsValue::NULL, JsValue::from(1), JsValue::UNDEFINED]);
+    let _ = wasm_split_n(b"x".to_vec(), u32::MAX);
+
+    assert_eq!(
+        wasm_recover(valid, "LkGP/xyvysz9JqOtdpOmJ8A=").unwrap(),
+        "Hello, World!"
+    );
+}
+
+#[cfg(feature = "protect")]
+#[wasm_bindgen_test]
+fn bundles_roundtrip_and_tell_a_wrong_passphrase_from_damage() {
+    let code = |error: JsValue| {
+        Reflect::get(&error, &JsValue::from_str("code"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    let (share1, share2) = ("ZiTjk3OD6puSVM/JV3CYopI=", "LkGP/xyvysz9JqOtdpOmJ8A=");
+    let options = Object::new();
+    Reflect::set(&options, &"memoryKib".into(), &64.into()).unwrap();
+    Reflect::set(&options, &"iterations".into(), &1.into()).unwrap();
+    let bundle = wasm_bundle(share1, share2, "transport".to_owned(), options.into()).unwrap();
+
+    let opened = wasm_unbundle(bundle.clone(), "transport".to_owned()).unwrap();
+    let field = |name: &str| {
+        Reflect::get(&opened, &JsValue::from_str(name))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    assert_eq!(field("share1"), share1);
+    assert_eq!(field("share2"), share2);
+
+    assert_eq!(
+        code(wasm_unbundle(bundle.clone(), "guess".to_owned()).unwrap_err()),
+        "WRONG_PASSPHRASE"
+    );
+    let mut damaged = bundle;
+    *damaged.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        code(wasm_unbundle(damaged, "transport".to_owned()).unwrap_err()),
+        "CORRUPTED_BUNDLE"
+    );
+
+    let too_little = Object::new();
+    Reflect::set(&too_little, &"memoryKib".into(), &1.into()).unwrap();
+    assert_eq!(
+        code(wasm_bundle(share1, share2, "transport".to_owned(), too_little.into()).unwrap_err()),
+        "INVALID_KDF_PARAMS"
+    );
+    let typo = Object::new();
+    Reflect::set(&typo, &"memoryKB".into(), &64.into()).unwrap();
+    assert_eq!(
+        code(wasm_bundle(share1, share2, "transport".to_owned(), typo.into()).unwrap_err()),
+        "INVALID_OPTIONS"
+    );
+}
+
+#[cfg(feature = "protect")]
+#[wasm_bindgen_test]
+fn protected_shares_roundtrip_and_tell_a_wrong_passphrase_from_damage() {
+    let code = |error: JsValue| {
+        Reflect::get(&error, &JsValue::from_str("code"))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    let inspected = |share: &str, field: &str| {
+        Reflect::get(&wasm_inspect(share).unwrap(), &JsValue::from_str(field)).unwrap()
+    };
+    let share = "ZiTjk3OD6puSVM/JV3CYopI=";
+    let cheap = || {
+        let options = Object::new();
+        Reflect::set(&options, &"memoryKib".into(), &64.into()).unwrap();
+        Reflect::set(&options, &"iterations".into(), &1.into()).unwrap();
+        JsValue::from(options)
+    };
+    let locked = wasm_protect_share(share, "custodian".to_owned(), cheap()).unwrap();
+    assert_eq!(inspected(&locked, "protected"), JsValue::TRUE);
+    assert_eq!(inspected(&locked, "payload_len"), JsValue::from(13));
+    assert_eq!(inspected(share, "protected"), JsValue::FALSE);
+
+    let unlocked = wasm_unprotect_share(&locked, "custodian".to_owned()).unwrap();
+    assert_eq!(unlocked, share);
+    assert_eq!(
+        wasm_recover(&unlocked, "LkGP/xyvysz9JqOtdpOmJ8A=").unwrap(),
+        "Hello, World!"
+    );
+
+    assert_eq!(
+        code(wasm_unprotect_share(&locked, "guess".to_owned()).unwrap_err()),
+        "WRONG_PASSPHRASE"
+    );
+    let mut damaged = STANDARD.decode(&locked).unwrap();
+    *damaged.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        code(wasm_unprotect_share(&STANDARD.encode(damaged), "custodian".to_owned()).unwrap_err()),
+        "CORRUPTED_PROTECTED_SHARE"
+    );
+    assert_eq!(
+        code(wasm_unprotect_share(share, "custodian".to_owned()).unwrap_err()),
+        "CORRUPTED_PROTECTED_SHARE"
+    );
+    // Costs past the maximums are refused before anything is derived
+    let mut crafted = STANDARD.decode(&locked).unwrap();
+    crafted[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        code(wasm_unprotect_share(&STANDARD.encode(crafted), "custodian".to_owned()).unwrap_err()),
+        "CORRUPTED_PROTECTED_SHARE"
+    );
+
+    assert_eq!(
+        code(wasm_protect_share(&locked, "custodian".to_owned(), cheap()).unwrap_err()),
+        "UNSUPPORTED_FORMAT"
+    );
+    assert_eq!(
+        code(
+            wasm_protect_share("LkGP/xyvysz9JqOtdpOmJ8E=", "custodian".to_owned(), cheap())
+                .unwrap_err()
+        ),
+        "INVALID_CHECKSUM"
+    );
+}
+
+#[wasm_bindgen_test]
+fn base64url_shares_survive_uri_encoding() {
+    let text = |value: &JsValue, name: &str| {
+        Reflect::get(value, &JsValue::from_str(name))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    let options = Object::new();
+    Reflect::set(&options, &"encoding".into(), &"base64url".into()).unwrap();
+    // Long enough that both shares all but surely hold `-` and `_`
+    let secret = "x".repeat(4096);
+    let shares = wasm_split_with_options(secret.clone(), options.into()).unwrap();
+    assert_eq!(text(&shares, "encoding"), "base64url");
+
+    let (share1, share2) = (text(&shares, "share1"), text(&shares, "share2"));
+    for share in [&share1, &share2] {
+        assert_eq!(String::from(js_sys::encode_uri_component(share)), *share);
+    }
+    assert_eq!(wasm_recover(&share1, &share2).unwrap(), secret);
+    assert_eq!(text(&wasm_split(secret).unwrap(), "encoding"), "base64");
+
+    // Either alphabet is read, and the two can be mixed
+    let standard = "ZiTjk3OD6puSVM/JV3CYopI=";
+    assert_eq!(
+        wasm_recover(standard, "LkGP_xyvysz9JqOtdpOmJ8A").unwrap(),
+        "Hello, World!"
+    );
+}
+
+/// Secret and shares also used by the native tests of `xplit::parity`
+const PARITY_FIXTURE: &str = include_str!("fixtures/parity.txt");
+
+#[wasm_bindgen_test]
+fn any_two_of_three_shares_recover_and_splits_are_told_apart() {
+    let code = |error: JsValue| {
+        let field = |name: &str| Reflect::get(&error, &JsValue::from_str(name)).unwrap();
+        (field("code").as_string().unwrap(), field("share").as_f64())
+    };
+    let lines: Vec<&str> = PARITY_FIXTURE.lines().collect();
+    let (secret, shares) = (lines[0], &lines[1..4]);
+    for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+        assert_eq!(wasm_recover_2of3(shares[i], shares[j]).unwrap(), secret);
+        assert_eq!(wasm_recover_2of3(shares[j], shares[i]).unwrap(), secret);
+    }
+    for (number, share) in (1..=3).zip(shares) {
+        let info = wasm_inspect(share).unwrap();
+        let field = |name: &str| Reflect::get(&info, &JsValue::from_str(name)).unwrap();
+        assert_eq!(field("scheme"), JsValue::from_str("2of3"));
+        assert_eq!(field("index"), JsValue::from(number));
+        assert_eq!(field("payload_len"), JsValue::from(secret.len()));
+    }
+
+    let fresh = wasm_split_2of3(secret.to_owned()).unwrap();
+    let share = |name: &str| {
+        Reflect::get(&fresh, &JsValue::from_str(name))
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    assert_eq!(
+        wasm_recover_2of3(&share("share3"), &share("share2")).unwrap(),
+        secret
+    );
+    assert_eq!(
+        code(wasm_recover_2of3(shares[0], &share("share2")).unwrap_err()),
+        ("DIFFERENT_SPLITS".to_owned(), None)
+    );
+
+    // The fixture's share 3 with a payload character changed
+    let mut damaged = shares[2].to_owned();
+    damaged.replace_range(30..31, if &damaged[30..31] == "A" { "B" } else { "A" });
+    assert_eq!(
+        code(wasm_recover_2of3(shares[0], &damaged).unwrap_err()),
+        ("INVALID_CHECKSUM".to_owned(), Some(2.0))
+    );
+    assert_eq!(
+        code(wasm_recover_2of3(shares[1], shares[1]).unwrap_err()),
+        ("TOO_FEW_SHARES".to_owned(), None)
+    );
+    assert_eq!(
+        code(wasm_recover_2of3("ZiTjk3OD6puSVM/JV3CYopI=", shares[1]).unwrap_err()),
+        ("UNSUPPORTED_FORMAT".to_owned(), Some(1.0))
+    );
+}
+
+/// The shares above as `xplit split --grid` prints them
+const GRID1: &str = include_str!("fixtures/hello-share1.grid");
+const GRID2: &str = include_str!("fixtures/hello-share2.grid");
+
+#[wasm_bindgen_test]
+fn cli_grids_are_read_next_to_base64() {
+    let field =
+        |value: &JsValue, name: &str| Reflect::get(value, &JsValue::from_str(name)).unwrap();
+    assert_eq!(wasm_recover(GRID1, GRID2).unwrap(), "Hello, World!");
+    assert_eq!(
+        wasm_recover(GRID1, "LkGP/xyvysz9JqOtdpOmJ8A=").unwrap(),
+        "Hello, World!"
+    );
+    assert_eq!(
+        wasm_fingerprint(GRID1).unwrap(),
+        wasm_fingerprint("ZiTjk3OD6puSVM/JV3CYopI=").unwrap()
+    );
+
+    let report = wasm_verify_share(GRID2).unwrap();
+    assert_eq!(field(&report, "valid"), JsValue::TRUE);
+    assert_eq!(
+        field(&report, "encoding_detected"),
+        JsValue::from_str("grid")
+    );
+    let info = wasm_inspect(GRID1).unwrap();
+    assert_eq!(field(&info, "encoding_detected"), JsValue::from_str("grid"));
+    assert_eq!(field(&info, "payload_len"), JsValue::from(13));
+
+    // A typo is caught by the row's check character
+    let typo = GRID1.replacen("MYSO", "MYSP", 1);
+    let report = wasm_verify_share(&typo).unwrap();
+    assert_eq!(
+        field(&report, "error_code"),
+        JsValue::from_str("INVALID_GRID_ROW")
+    );
+    let error = wasm_recover(&typo, GRID2).unwrap_err();
+    assert_eq!(field(&error, "code"), JsValue::from_str("INVALID_GRID_ROW"));
+    assert_eq!(field(&error, "share"), JsValue::from(1));
+}
+
+#[wasm_bindgen_test]
+fn real_and_decoy_pairs_recover_their_own_secrets() {
+    let field =
+        |value: &JsValue, name: &str| Reflect::get(value, &JsValue::from_str(name)).unwrap();
+    let (real, decoy) = (b"correct horse battery", b"grocery list: eggs,tea");
+    assert_eq!(real.len(), decoy.len());
+    let shares = wasm_split_bytes(real.to_vec()).unwrap();
+    let (share1, share2) = (share(&shares, "share1"), share(&shares, "share2"));
+
+    let decoy_share2 = wasm_create_decoy(&STANDARD.encode(&share1), decoy.to_vec()).unwrap();
+    let decoy_share2 = STANDARD.decode(decoy_share2).unwrap();
+    assert_eq!(decoy_share2.len(), share2.len());
+    assert_eq!(
+        wasm_recover_bytes(share1.clone(), decoy_share2).unwrap(),
+        decoy
+    );
+    assert_eq!(wasm_recover_bytes(share1.clone(), share2).unwrap(), real);
+
+    let error = wasm_create_decoy(&STANDARD.encode(&share1), b"too short".to_vec()).unwrap_err();
+    assert_eq!(
+        field(&error, "code"),
+        JsValue::from_str("DECOY_LENGTH_MISMATCH")
+    );
+    assert_eq!(field(&error, "required_len"), JsValue::from(real.len()));
+    let error = wasm_create_decoy("ZiTjk3OD6puSVM/JV3CYopI=", b"too short".to_vec()).unwrap_err();
+    assert_eq!(field(&error, "required_len"), JsValue::from(13));
+
+    // Padding is checked on recovery, so a padded pair can't take a decoy
+    let padded = wasm_split_with_options("Hello, World!".to_owned(), {
+        let options = Object::new();
+        Reflect::set(&options, &JsValue::from_str("padTo"), &JsValue::from(32)).unwrap();
+        options.into()
+    })
+    .unwrap();
+    let padded1 = field(&padded, "share1").as_string().unwrap();
+    let error = wasm_create_decoy(&padded1, vec![b'x'; 32]).unwrap_err();
+    assert_eq!(
+        field(&error, "code"),
+        JsValue::from_str("UNSUPPORTED_FORMAT")
+    );
+    assert_eq!(field(&error, "share"), JsValue::from(1));
+}